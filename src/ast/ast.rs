@@ -1,70 +1,206 @@
-use super::super::span::Span;
+use std::fmt::Debug;
+
+#[cfg(feature = "serde")]
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+use super::super::error::ParseError;
+use super::super::span::{
+    SourceRange,
+    Span
+};
+
+/// The storage of the spans and of the errors of a syntax tree.
+///
+/// Each node is generic over its storage, so that the borrowed tree, built
+/// by the parser, and the owned tree, of `ast::owned`, share their
+/// definitions. The storage defaults to `Borrowed`.
+pub trait Storage<'a> {
+    /// The type of a span, e.g. `Span<'a>`.
+    type Span: Spanned + Clone + Debug + PartialEq;
+
+    /// The type of a parse error, e.g. `ParseError<'a>`.
+    type Error: Clone + Debug + PartialEq;
+}
+
+/// The storage of a tree borrowing its source: a span is a `Span<'a>`, and
+/// an error is a `ParseError<'a>`.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Borrowed;
+
+impl<'a> Storage<'a> for Borrowed {
+    type Span  = Span<'a>;
+    type Error = ParseError<'a>;
+}
 
 /// A token is a structure pairing a span to any data.
-#[derive(Debug, PartialEq)]
-pub struct Token<'a, T> {
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "T: Serialize, S::Span: Serialize, S::Error: Serialize", deserialize = "T: Deserialize<'de>, S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub struct Token<'a, T, S: Storage<'a> = Borrowed> {
     /// Value of the token.
     pub value: T,
 
     /// The attached span of the value.
-    pub span: Span<'a>
+    pub span: S::Span
 }
 
 impl<'a, T> Token<'a, T> {
     pub fn new(value: T, span: Span<'a>) -> Self {
         Token {
-            value: value,
-            span : span
+            value,
+            span
         }
     }
 }
 
+/// A node of the syntax tree that covers a range of the source, e.g. to
+/// point a diagnostic or a hover at it.
+///
+/// Every node implements it. A node with a `span` field covers its span;
+/// the other nodes cover their children.
+pub trait Spanned {
+    /// The range of the source covered by the node.
+    fn range(&self) -> SourceRange;
+}
+
+impl<'a> Spanned for Span<'a> {
+    fn range(&self) -> SourceRange {
+        Span::range(self)
+    }
+}
+
+impl<'a, T, S: Storage<'a>> Spanned for Token<'a, T, S> {
+    fn range(&self) -> SourceRange {
+        self.span.range()
+    }
+}
+
+#[cfg(feature = "ink-numbers")]
+mod numbers {
+    pub type Integer = i32;
+    pub type Real    = f32;
+}
+
+#[cfg(not(feature = "ink-numbers"))]
+mod numbers {
+    pub type Integer = i64;
+    pub type Real    = f64;
+}
+
+/// The integer type of the story.
+///
+/// Ink integers are 32-bit signed integers. The `ink-numbers` feature selects
+/// them; 64-bit integers are used otherwise.
+pub type Integer = numbers::Integer;
+
+/// The real type of the story.
+///
+/// Ink reals are single-precision floats. The `ink-numbers` feature selects
+/// them; double-precision floats are used otherwise.
+pub type Real = numbers::Real;
+
 /// A literal represents a fixed value, aka an atom.
-#[derive(Debug, PartialEq)]
-pub enum Literal<'a> {
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub enum Literal<'a, S: Storage<'a> = Borrowed> {
     /// An integer (eg. a binary, octal, decimal or hexadecimal number).
-    Integer(Token<'a, i64>),
+    Integer(Token<'a, Integer, S>),
 
     /// A real (eg. an exponential number).
-    Real(Token<'a, f64>),
+    Real(Token<'a, Real, S>),
 
     /// A boolean.
-    Boolean(Token<'a, bool>),
+    Boolean(Token<'a, bool, S>),
 
     /// A string.
-    String(Token<'a, String>)
+    String(Token<'a, String, S>)
+}
+
+impl<'a, S: Storage<'a>> Spanned for Literal<'a, S> {
+    fn range(&self) -> SourceRange {
+        match *self {
+            Literal::Integer(ref token) => token.span.range(),
+            Literal::Real(ref token)    => token.span.range(),
+            Literal::Boolean(ref token) => token.span.range(),
+            Literal::String(ref token)  => token.span.range()
+        }
+    }
 }
 
 /// A n-ary operation.
-#[derive(Debug, PartialEq)]
-pub enum NAryOperation<'a> {
+///
+/// Each operation holds the span of its source, parentheses included, e.g.
+/// the left operand of `(x + 1) * y` spans over `(x + 1)`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub enum NAryOperation<'a, S: Storage<'a> = Borrowed> {
     /// An operation with zero operator and one operand.
-    Nullary(Box<Expression<'a>>),
+    Nullary {
+        /// The operand.
+        operand: Box<Expression<'a, S>>,
 
-    /// An operation with one operator and one operand: `op x`.
+        /// The span of the operation.
+        span: S::Span
+    },
+
+    /// An operation with one operator and one operand: `op x` or `x op`.
     Unary {
         /// The operator.
-        operator: UnaryOperator,
+        operator: Token<'a, UnaryOperator, S>,
 
         /// The operand (`x`).
-        operand: Box<NAryOperation<'a>>
+        operand: Box<NAryOperation<'a, S>>,
+
+        /// The span of the operation.
+        span: S::Span
     },
 
     /// An operation with one operator and two operands: `x op y`.
     Binary {
         /// The operator.
-        operator: BinaryOperator,
+        operator: Token<'a, BinaryOperator, S>,
 
         /// The left operand (`x`).
-        left_operand: Box<NAryOperation<'a>>,
+        left_operand: Box<NAryOperation<'a, S>>,
 
         /// The right operand (`y`).
-        right_operand: Box<NAryOperation<'a>>
+        right_operand: Box<NAryOperation<'a, S>>,
+
+        /// The span of the operation.
+        span: S::Span
     },
 }
 
+impl<'a> NAryOperation<'a> {
+    /// The span of the operation, parentheses included.
+    pub fn span(&self) -> Span<'a> {
+        match *self {
+            NAryOperation::Nullary { span, .. } |
+            NAryOperation::Unary { span, .. }   |
+            NAryOperation::Binary { span, .. }  => span
+        }
+    }
+}
+
+impl<'a, S: Storage<'a>> Spanned for NAryOperation<'a, S> {
+    fn range(&self) -> SourceRange {
+        match *self {
+            NAryOperation::Nullary { ref span, .. } |
+            NAryOperation::Unary { ref span, .. }   |
+            NAryOperation::Binary { ref span, .. }  => span.range()
+        }
+    }
+}
+
 /// A unary operator.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum UnaryOperator {
     /// Inverts all the bits (`~x`).
     BitwiseComplement,
@@ -86,7 +222,8 @@ pub enum UnaryOperator {
 }
 
 /// A binary operator.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum BinaryOperator {
     /// `x & y`.
     BitwiseAnd,
@@ -100,9 +237,6 @@ pub enum BinaryOperator {
     /// `x >> y`.
     BitwiseShiftRight,
 
-    /// `x ^ y`.
-    BitwiseXor,
-
     /// `x / y`.
     Division,
 
@@ -115,6 +249,15 @@ pub enum BinaryOperator {
     /// `x >= y`.
     GreaterThanOrEqualTo,
 
+    /// `x ? y` or `x has y`, i.e. the list `x` contains `y`.
+    Has,
+
+    /// `x !? y` or `x hasnt y`, i.e. the list `x` does not contain `y`.
+    Hasnt,
+
+    /// `x ^ y`, i.e. the items of the list `x` that are also in `y`.
+    Intersection,
+
     /// `x < y`.
     LessThan,
 
@@ -144,28 +287,769 @@ pub enum BinaryOperator {
 }
 
 /// A variable.
-#[derive(Debug, PartialEq)]
-pub struct Variable<'a> (pub Span<'a>);
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub struct Variable<'a, S: Storage<'a> = Borrowed>(pub S::Span);
+
+impl<'a, S: Storage<'a>> Spanned for Variable<'a, S> {
+    fn range(&self) -> SourceRange {
+        self.0.range()
+    }
+}
 
 /// An expression.
-#[derive(Debug, PartialEq)]
-pub enum Expression<'a> {
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub enum Expression<'a, S: Storage<'a> = Borrowed> {
     /// A literal.
-    Literal(Literal<'a>),
+    Literal(Literal<'a, S>),
 
     /// A n-ary operation.
-    NAryOperation(NAryOperation<'a>),
+    NAryOperation(NAryOperation<'a, S>),
 
     /// A variable.
-    Variable(Variable<'a>)
+    Variable(Variable<'a, S>)
+}
+
+impl<'a, S: Storage<'a>> Spanned for Expression<'a, S> {
+    fn range(&self) -> SourceRange {
+        match *self {
+            Expression::Literal(ref literal)         => literal.range(),
+            Expression::NAryOperation(ref operation) => operation.range(),
+            Expression::Variable(ref variable)       => variable.range()
+        }
+    }
+}
+
+/// The kind of a declaration.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DeclarationKind {
+    /// A statement, e.g. `~ x = 1`.
+    Statement,
+
+    /// A global variable, e.g. `VAR x = 1`.
+    Global,
+
+    /// A global constant, e.g. `CONST x = 1`.
+    Constant
+}
+
+/// An item of a list declaration, e.g. `(b = 2)` in `LIST l = a, (b = 2)`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub struct ListItem<'a, S: Storage<'a> = Borrowed> {
+    /// The name of the item (`b`).
+    pub name: S::Span,
+
+    /// The explicit value of the item (`2`), if any.
+    pub value: Option<Token<'a, Integer, S>>,
+
+    /// Whether the item is in the initial value of the list, i.e. it is
+    /// between parentheses.
+    pub included: bool,
+
+    /// The span of the item, with its parentheses (`(b = 2)`).
+    pub span: S::Span
+}
+
+impl<'a, S: Storage<'a>> Spanned for ListItem<'a, S> {
+    fn range(&self) -> SourceRange {
+        self.span.range()
+    }
 }
 
 /// A statement.
-#[derive(Debug, PartialEq)]
-pub enum Statement<'a> {
-    /// A Declaration.
-    Declaration(Variable<'a>, Expression<'a>),
-
-    /// A return statement.
-    Return(Expression<'a>),
-}
\ No newline at end of file
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub enum Statement<'a, S: Storage<'a> = Borrowed> {
+    /// A declaration, e.g. `~ x = 1`, a global variable, e.g. `VAR x = 1`,
+    /// or a global constant, e.g. `CONST x = 1`.
+    Declaration {
+        /// The declared variable (`x`).
+        variable: Variable<'a, S>,
+
+        /// The assigned value (`1`).
+        value: Expression<'a, S>,
+
+        /// The kind of the declaration, given by its first token.
+        kind: DeclarationKind,
+
+        /// The span of the statement, from the tilde or the keyword to the
+        /// value (`~ x = 1`).
+        span: S::Span,
+
+        /// The comments documenting the declaration.
+        comments: Comments<'a, S>
+    },
+
+    /// A list declaration, e.g. `LIST l = a, (b = 2)`.
+    List {
+        /// The name of the list (`l`).
+        name: Variable<'a, S>,
+
+        /// The items of the list, at least one.
+        items: Vec<ListItem<'a, S>>,
+
+        /// The span of the statement, from the keyword to the last item.
+        span: S::Span,
+
+        /// The comments documenting the list.
+        comments: Comments<'a, S>
+    },
+
+    /// A return statement, e.g. `~ return x`.
+    Return {
+        /// The returned value (`x`).
+        value: Expression<'a, S>,
+
+        /// The span of the statement, from the tilde to the value
+        /// (`~ return x`).
+        span: S::Span
+    }
+}
+
+impl<'a, S: Storage<'a>> Spanned for Statement<'a, S> {
+    fn range(&self) -> SourceRange {
+        match *self {
+            Statement::Declaration { ref span, .. } => span.range(),
+            Statement::List { ref span, .. }        => span.range(),
+            Statement::Return { ref span, .. }      => span.range()
+        }
+    }
+}
+
+/// A part of a line of content.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub enum ContentPart<'a, S: Storage<'a> = Borrowed> {
+    /// A run of text, e.g. `Hello `.
+    Text(S::Span),
+
+    /// An expression whose value is printed, e.g. `{x}`.
+    ///
+    /// The span covers the curly brackets.
+    Interpolation(Token<'a, Expression<'a, S>, S>),
+
+    /// A divert, e.g. `-> london`.
+    ///
+    /// The span covers the arrow.
+    Divert(Token<'a, Divert<'a, S>, S>)
+}
+
+impl<'a, S: Storage<'a>> Spanned for ContentPart<'a, S> {
+    fn range(&self) -> SourceRange {
+        match *self {
+            ContentPart::Text(ref span)           => span.range(),
+            ContentPart::Interpolation(ref token) => token.range(),
+            ContentPart::Divert(ref token)        => token.range()
+        }
+    }
+}
+
+/// The target of a divert.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub enum Divert<'a, S: Storage<'a> = Borrowed> {
+    /// A knot, a stitch, or a stitch of a knot, e.g. `london.first_class`.
+    ///
+    /// Each span is an identifier of the path (`london`, `first_class`).
+    Target(Vec<S::Span>),
+
+    /// The end of the story (`END`).
+    End(S::Span),
+
+    /// The end of the current flow (`DONE`).
+    Done(S::Span)
+}
+
+/// A target covers its path, from its first identifier to its last one. A
+/// target without any identifier, which the parser never produces, has an
+/// empty range at the start of the source.
+impl<'a, S: Storage<'a>> Spanned for Divert<'a, S> {
+    fn range(&self) -> SourceRange {
+        match *self {
+            Divert::Target(ref path) => {
+                match (path.first(), path.last()) {
+                    (Some(first), Some(last)) => first.range().merge(&last.range()),
+                    _                         => Span::empty().range()
+                }
+            },
+
+            Divert::End(ref span)  => span.range(),
+            Divert::Done(ref span) => span.range()
+        }
+    }
+}
+
+/// A line of content, e.g. `Hello {name}!`.
+///
+/// The parser never produces an empty content: a missing content is `None`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub struct Content<'a, S: Storage<'a> = Borrowed>(pub Vec<ContentPart<'a, S>>);
+
+/// A content covers its parts, from the first one to the last one. An empty
+/// content, which the parser never produces, has an empty range at the start
+/// of the source.
+impl<'a, S: Storage<'a>> Spanned for Content<'a, S> {
+    fn range(&self) -> SourceRange {
+        match (self.0.first(), self.0.last()) {
+            (Some(first), Some(last)) => first.range().merge(&last.range()),
+            _                         => Span::empty().range()
+        }
+    }
+}
+
+/// A line of a weave.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub enum Line<'a, S: Storage<'a> = Borrowed> {
+    /// A line of content.
+    Content(Content<'a, S>),
+
+    /// A statement, e.g. `~ x = 1`.
+    Statement(Statement<'a, S>),
+
+    /// A choice, e.g. `* Hello`.
+    Choice(Choice<'a, S>),
+
+    /// A gather, e.g. `- Goodbye`.
+    Gather(Gather<'a, S>),
+
+    /// A multiline conditional block, e.g. `{ x > 0: … - else: … }`.
+    Conditional(Conditional<'a, S>),
+
+    /// A multiline sequence block, e.g. `{ stopping: - … - … }`.
+    Sequence(Sequence<'a, S>),
+
+    /// An annotation, e.g. `TODO: describe the room`.
+    Annotation(Annotation<'a, S>),
+
+    /// A malformed line, skipped when the parser recovers from errors.
+    ///
+    /// The span covers the skipped line, and the value is the error.
+    Error(Token<'a, S::Error, S>)
+}
+
+impl<'a, S: Storage<'a>> Spanned for Line<'a, S> {
+    fn range(&self) -> SourceRange {
+        match *self {
+            Line::Content(ref content)         => content.range(),
+            Line::Statement(ref statement)     => statement.range(),
+            Line::Choice(ref choice)           => choice.range(),
+            Line::Gather(ref gather)           => gather.range(),
+            Line::Conditional(ref conditional) => conditional.range(),
+            Line::Sequence(ref sequence)       => sequence.range(),
+            Line::Annotation(ref annotation)   => annotation.range(),
+            Line::Error(ref token)             => token.range()
+        }
+    }
+}
+
+/// An annotation, i.e. a note for the writers that is not part of the story.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub enum Annotation<'a, S: Storage<'a> = Borrowed> {
+    /// A writing task, e.g. `TODO: describe the room`.
+    ///
+    /// The span covers the message only (`describe the room`).
+    Todo(S::Span)
+}
+
+impl<'a, S: Storage<'a>> Spanned for Annotation<'a, S> {
+    fn range(&self) -> SourceRange {
+        match *self {
+            Annotation::Todo(ref span) => span.range()
+        }
+    }
+}
+
+/// A weave is a block of lines.
+///
+/// It is the body of knots, stitches, choices, conditional branches and
+/// sequence branches.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub struct Weave<'a, S: Storage<'a> = Borrowed> {
+    /// The lines of the weave.
+    pub lines: Vec<Line<'a, S>>,
+
+    /// The span of the weave, from the start of its first line to the end
+    /// of its last line. An empty weave has an empty span where its first
+    /// line would be.
+    pub span: S::Span
+}
+
+impl<'a, S: Storage<'a>> Spanned for Weave<'a, S> {
+    fn range(&self) -> SourceRange {
+        self.span.range()
+    }
+}
+
+/// A choice, e.g. `* Hello [back] right back to you!`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub struct Choice<'a, S: Storage<'a> = Borrowed> {
+    /// The nesting level, i.e. the number of bullets.
+    pub depth: usize,
+
+    /// Whether the choice is sticky (`+`) or consumed once chosen (`*`).
+    pub sticky: bool,
+
+    /// The label of the choice, e.g. `greet` in `* (greet) Hello`, to divert
+    /// to it.
+    pub label: Option<S::Span>,
+
+    /// The content printed both in the choice and in the output (`Hello `).
+    pub start: Option<Content<'a, S>>,
+
+    /// The content printed only in the choice (`back`).
+    pub choice_only: Option<Content<'a, S>>,
+
+    /// The content printed only in the output (` right back to you!`).
+    pub output: Option<Content<'a, S>>,
+
+    /// The lines following the choice.
+    pub body: Weave<'a, S>,
+
+    /// The span of the choice, from its first bullet to the end of its
+    /// body, or of its line if the body is empty.
+    pub span: S::Span
+}
+
+impl<'a, S: Storage<'a>> Spanned for Choice<'a, S> {
+    fn range(&self) -> SourceRange {
+        self.span.range()
+    }
+}
+
+/// A gather, e.g. `- Goodbye`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub struct Gather<'a, S: Storage<'a> = Borrowed> {
+    /// The nesting level, i.e. the number of dashes.
+    pub depth: usize,
+
+    /// The label of the gather, e.g. `end` in `- (end) Goodbye`, to divert
+    /// to it.
+    pub label: Option<S::Span>,
+
+    /// The content of the gather line.
+    pub content: Option<Content<'a, S>>,
+
+    /// The span of the gather line, from its first dash.
+    pub span: S::Span
+}
+
+impl<'a, S: Storage<'a>> Spanned for Gather<'a, S> {
+    fn range(&self) -> SourceRange {
+        self.span.range()
+    }
+}
+
+/// A branch of a conditional block.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub struct ConditionalBranch<'a, S: Storage<'a> = Borrowed> {
+    /// The condition of the branch, `None` for the `else` branch.
+    pub condition: Option<Expression<'a, S>>,
+
+    /// The lines of the branch.
+    pub body: Weave<'a, S>,
+
+    /// The span of the branch, from its condition or the `else` keyword to
+    /// the end of its body, or to its colon if the body is empty. The dash
+    /// marking the branch is not covered.
+    pub span: S::Span
+}
+
+impl<'a, S: Storage<'a>> Spanned for ConditionalBranch<'a, S> {
+    fn range(&self) -> SourceRange {
+        self.span.range()
+    }
+}
+
+/// A multiline conditional block.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub struct Conditional<'a, S: Storage<'a> = Borrowed> {
+    /// The branches of the conditional.
+    pub branches: Vec<ConditionalBranch<'a, S>>,
+
+    /// The span of the block, from the opening curly bracket to the
+    /// closing one.
+    pub span: S::Span
+}
+
+impl<'a, S: Storage<'a>> Spanned for Conditional<'a, S> {
+    fn range(&self) -> SourceRange {
+        self.span.range()
+    }
+}
+
+/// The kind of a sequence.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SequenceKind {
+    /// Go through the branches and stick on the last one (`stopping`).
+    Stopping,
+
+    /// Go through the branches and loop (`cycle`).
+    Cycle,
+
+    /// Go through the branches and then show nothing (`once`).
+    Once,
+
+    /// Pick a random branch (`shuffle`).
+    Shuffle
+}
+
+/// A multiline sequence block.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub struct Sequence<'a, S: Storage<'a> = Borrowed> {
+    /// The kind of the sequence.
+    pub kind: SequenceKind,
+
+    /// The branches of the sequence.
+    pub branches: Vec<Weave<'a, S>>,
+
+    /// The span of the block, from the opening curly bracket to the
+    /// closing one.
+    pub span: S::Span
+}
+
+impl<'a, S: Storage<'a>> Spanned for Sequence<'a, S> {
+    fn range(&self) -> SourceRange {
+        self.span.range()
+    }
+}
+
+/// The comments documenting a knot, a stitch or a declaration, e.g.
+/// `// The capital.`.
+///
+/// A comment is the one recognized by `rules::comments::comment`, i.e.
+/// without its delimiters.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub struct Comments<'a, S: Storage<'a> = Borrowed> {
+    /// The comments above the header or the declaration, up to the first
+    /// blank line.
+    pub leading: Vec<S::Span>,
+
+    /// The comments following the header or the declaration on its line.
+    pub trailing: Vec<S::Span>
+}
+
+impl<'a, S: Storage<'a>> Default for Comments<'a, S> {
+    fn default() -> Self {
+        Comments {
+            leading : vec![],
+            trailing: vec![]
+        }
+    }
+}
+
+/// A stitch, e.g. `= in_first_class`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub struct Stitch<'a, S: Storage<'a> = Borrowed> {
+    /// The name of the stitch.
+    pub name: S::Span,
+
+    /// The lines of the stitch.
+    pub body: Weave<'a, S>,
+
+    /// The comments documenting the stitch.
+    pub comments: Comments<'a, S>,
+
+    /// The span of the stitch, from its header to the end of its body. The
+    /// comments are not covered.
+    pub span: S::Span
+}
+
+impl<'a, S: Storage<'a>> Spanned for Stitch<'a, S> {
+    fn range(&self) -> SourceRange {
+        self.span.range()
+    }
+}
+
+/// A knot, e.g. `== london ==`, or a function, e.g. `== function lerp(a, b, k) ==`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub struct Knot<'a, S: Storage<'a> = Borrowed> {
+    /// The name of the knot.
+    pub name: S::Span,
+
+    /// Whether the knot is a function.
+    pub function: bool,
+
+    /// The parameters of the knot.
+    pub parameters: Vec<Variable<'a, S>>,
+
+    /// The lines before the first stitch.
+    pub body: Weave<'a, S>,
+
+    /// The stitches of the knot.
+    pub stitches: Vec<Stitch<'a, S>>,
+
+    /// The comments documenting the knot.
+    pub comments: Comments<'a, S>,
+
+    /// The span of the knot, from its header to the end of its last stitch
+    /// or of its body. The comments are not covered.
+    pub span: S::Span
+}
+
+impl<'a, S: Storage<'a>> Spanned for Knot<'a, S> {
+    fn range(&self) -> SourceRange {
+        self.span.range()
+    }
+}
+
+/// A story.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "S::Span: Serialize, S::Error: Serialize", deserialize = "S::Span: Deserialize<'de>, S::Error: Deserialize<'de>")))]
+pub struct Story<'a, S: Storage<'a> = Borrowed> {
+    /// The lines before the first knot.
+    pub body: Weave<'a, S>,
+
+    /// The knots of the story.
+    pub knots: Vec<Knot<'a, S>>,
+
+    /// The span of the story, i.e. the whole source.
+    pub span: S::Span
+}
+
+impl<'a, S: Storage<'a>> Spanned for Story<'a, S> {
+    fn range(&self) -> SourceRange {
+        self.span.range()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Content,
+        ContentPart,
+        Divert,
+        Line,
+        Spanned,
+        Story,
+        Token
+    };
+    use options::ParserOptions;
+
+    const SOURCE: &str = "\
+Hello {name}! -> london
+* Go [now] away
+  Gone
+- Back
+{ x > 0:
+  Positive
+- else:
+  Negative
+}
+{ stopping:
+- A
+- B
+}
+-> END
+== london ==
+The capital.
+= first_class // Fancy.
+Fancy.
+";
+
+    fn story() -> Story<'static> {
+        ::parse(SOURCE, ParserOptions::default()).unwrap()
+    }
+
+    fn text<S: Spanned>(node: &S) -> &'static str {
+        &SOURCE[node.range().byte_range()]
+    }
+
+    fn line(story: &Story<'static>, index: usize) -> &'static str {
+        text(&story.body.lines[index])
+    }
+
+    #[test]
+    fn case_story_range() {
+        assert_eq!(text(&story()), SOURCE);
+    }
+
+    #[test]
+    fn case_weave_range() {
+        let story = story();
+
+        assert!(text(&story.body).starts_with("Hello {name}!"));
+        assert!(text(&story.body).ends_with("}\n-> END"));
+        assert_eq!(text(&story.knots[0].body), "The capital.");
+    }
+
+    #[test]
+    fn case_weave_empty_range() {
+        let story = ::parse("== london ==\n= first_class\n", ParserOptions::default()).unwrap();
+
+        assert_eq!(story.knots[0].body.range().byte_range(), 13..13);
+    }
+
+    #[test]
+    fn case_line_range() {
+        let story = story();
+
+        assert_eq!(line(&story, 0), "Hello {name}! -> london");
+        assert_eq!(line(&story, 1), "* Go [now] away\n  Gone");
+
+        // A divert covers its arrow.
+        assert_eq!(line(&story, 5), "-> END");
+    }
+
+    #[test]
+    fn case_content_range() {
+        let story = story();
+
+        match story.body.lines[0] {
+            Line::Content(ref content) => assert_eq!(text(content), "Hello {name}! -> london"),
+            _                          => panic!("expected a line of content")
+        }
+    }
+
+    #[test]
+    fn case_choice_range() {
+        let story = story();
+
+        match story.body.lines[1] {
+            Line::Choice(ref choice) => {
+                assert_eq!(text(choice), "* Go [now] away\n  Gone");
+                assert_eq!(text(&choice.body), "Gone");
+            },
+            _ => panic!("expected a choice")
+        }
+    }
+
+    #[test]
+    fn case_gather_range() {
+        let story = story();
+
+        match story.body.lines[2] {
+            Line::Gather(ref gather) => assert_eq!(text(gather), "- Back"),
+            _                        => panic!("expected a gather")
+        }
+    }
+
+    #[test]
+    fn case_conditional_range() {
+        let story = story();
+
+        match story.body.lines[3] {
+            Line::Conditional(ref conditional) => {
+                assert_eq!(text(conditional), "{ x > 0:\n  Positive\n- else:\n  Negative\n}");
+            },
+            _ => panic!("expected a conditional")
+        }
+    }
+
+    #[test]
+    fn case_conditional_branch_range() {
+        let story = story();
+
+        match story.body.lines[3] {
+            Line::Conditional(ref conditional) => {
+                assert_eq!(text(&conditional.branches[0]), "x > 0:\n  Positive");
+                assert_eq!(text(&conditional.branches[1]), "else:\n  Negative");
+            },
+            _ => panic!("expected a conditional")
+        }
+    }
+
+    #[test]
+    fn case_sequence_range() {
+        let story = story();
+
+        match story.body.lines[4] {
+            Line::Sequence(ref sequence) => {
+                assert_eq!(text(sequence), "{ stopping:\n- A\n- B\n}");
+                assert_eq!(text(&sequence.branches[1]), "B");
+            },
+            _ => panic!("expected a sequence")
+        }
+    }
+
+    #[test]
+    fn case_divert_range() {
+        let story = story();
+        let divert = |index: usize, part: usize| {
+            match story.body.lines[index] {
+                Line::Content(ref content) => {
+                    match content.0[part] {
+                        ContentPart::Divert(ref token) => text(&token.value),
+                        _                              => panic!("expected a divert")
+                    }
+                },
+                _ => panic!("expected a line of content")
+            }
+        };
+
+        assert_eq!(divert(0, 3), "london");
+        assert_eq!(divert(5, 0), "END");
+
+        let path = ::parse("-> london.first_class\n", ParserOptions::default()).unwrap();
+
+        match path.body.lines[0] {
+            Line::Content(ref content) => {
+                match content.0[0] {
+                    ContentPart::Divert(ref token @ Token { value: Divert::Target(_), .. }) => {
+                        assert_eq!(token.value.range().byte_range(), 3..21);
+                        assert_eq!(token.range().byte_range(), 0..21);
+                    },
+                    _ => panic!("expected a divert to a target")
+                }
+            },
+            _ => panic!("expected a line of content")
+        }
+    }
+
+    #[test]
+    fn case_empty_range() {
+        let content: Content = Content(Vec::new());
+        let divert : Divert  = Divert::Target(Vec::new());
+
+        assert_eq!(content.range().byte_range(), 0..0);
+        assert_eq!(divert.range().byte_range(), 0..0);
+    }
+
+    #[test]
+    fn case_knot_range() {
+        let story = story();
+
+        assert_eq!(text(&story.knots[0]), "== london ==\nThe capital.\n= first_class // Fancy.\nFancy.");
+    }
+
+    #[test]
+    fn case_stitch_range() {
+        let story = story();
+
+        assert_eq!(text(&story.knots[0].stitches[0]), "= first_class // Fancy.\nFancy.");
+    }
+}
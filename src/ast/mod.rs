@@ -1 +1,72 @@
-pub mod ast;
\ No newline at end of file
+//! The syntax tree of a story.
+//!
+//! `ast` defines the nodes, generic over the storage of their spans, `owned`
+//! names the nodes owning their spans, `visit` walks the tree, and `print`
+//! prints it back to Ink.
+//!
+//! # Serialization
+//!
+//! With the `serde` feature, the borrowed nodes implement `Serialize`, and
+//! the owned nodes implement both `Serialize` and `Deserialize`. Both trees
+//! share their definitions, and so their schema: a serialized `ast::Story`
+//! can be deserialized into an `owned::Story`.
+//!
+//! The schema is the one of the serde derives, and it is stable: renaming
+//! a node, a field or a variant is a breaking change. In JSON:
+//!
+//! * A struct is an object with the names of its fields, e.g. a `Knot` is
+//!   `{ "name": …, "function": …, "parameters": […], "body": …, "stitches": […], "comments": …, "span": … }`,
+//!   its `Weave` is `{ "lines": […], "span": … }`, and its `Comments` are
+//!   `{ "leading": […], "trailing": […] }`.
+//! * A struct wrapping a single node is that node, e.g. a `Content` is an
+//!   array of parts, and a `Variable` is a span.
+//! * A variant without data is its name, e.g. the `SequenceKind::Cycle`
+//!   kind is `"Cycle"`; any other variant is an object with the name of the
+//!   variant as the single key, e.g. `{ "Text": <span> }` or
+//!   `{ "Declaration": { "variable": …, "value": …, "kind": …, "span": …, "comments": … } }`.
+//! * A `Token` is `{ "value": …, "span": <span> }`.
+//! * A span is `{ "offset": 4, "line": 1, "column": 5, "file": 0, "fragment": "x" }`,
+//!   with the byte offset, the line and the column in Unicode scalar
+//!   values both starting at 1, and the identifier of the file in the
+//!   source map.
+//! * An integer or a real is a number, a boolean is a boolean, a string is
+//!   a string, and a missing optional node is `null`.
+//! * The error of a `Line::Error` holds the span where the parser has
+//!   failed, e.g. `{ "Unexpected": { "span": …, "expected": [{ "Token": "}" }] } }`
+//!   or `{ "Invalid": { "span": …, "kind": "IntegerOverflow" } }`.
+//!
+//! For instance, the line `~ x = true` is:
+//!
+//! ```json
+//! {
+//!   "Statement": {
+//!     "Declaration": {
+//!       "variable": { "offset": 2, "line": 1, "column": 3, "file": 0, "fragment": "x" },
+//!       "value": {
+//!         "NAryOperation": {
+//!           "Nullary": {
+//!             "operand": {
+//!               "Literal": {
+//!                 "Boolean": {
+//!                   "value": true,
+//!                   "span" : { "offset": 6, "line": 1, "column": 7, "file": 0, "fragment": "true" }
+//!                 }
+//!               }
+//!             },
+//!             "span": { "offset": 6, "line": 1, "column": 7, "file": 0, "fragment": "true" }
+//!           }
+//!         }
+//!       },
+//!       "kind": "Statement",
+//!       "span": { "offset": 0, "line": 1, "column": 1, "file": 0, "fragment": "~ x = true" },
+//!       "comments": { "leading": [], "trailing": [] }
+//!     }
+//!   }
+//! }
+//! ```
+
+#[allow(clippy::module_inception)]
+pub mod ast;
+pub mod owned;
+pub mod print;
+pub mod visit;
@@ -0,0 +1,649 @@
+//! An owned syntax tree, independent of the lifetime of the source.
+//!
+//! The nodes of `ast::ast` are generic over their `Storage`. The owned tree
+//! is the same tree with the `Owned` storage, where a `Span` is replaced by
+//! an `OwnedSpan` and a `ParseError` by an `OwnedParseError`; this module
+//! names its nodes, e.g. `owned::Story` is `ast::Story<'static, Owned>`. An
+//! owned story can be cached, kept after the source is dropped, or sent to
+//! another thread. Convert a node with `IntoOwned::into_owned`.
+//!
+//! Both trees share their definitions, and so their serde schema. A new
+//! node only needs an alias and an `IntoOwned` implementation here.
+
+use std::ops::Range;
+
+use super::ast::{
+    self,
+    BinaryOperator,
+    DeclarationKind,
+    Integer,
+    Real,
+    SequenceKind,
+    Spanned,
+    Storage,
+    UnaryOperator
+};
+use error::OwnedParseError;
+use source_map::FileId;
+use span::{
+    Position,
+    SourceRange,
+    Span
+};
+
+/// Convert a node borrowing the source into an owned node.
+pub trait IntoOwned {
+    /// The owned node.
+    type Owned;
+
+    /// Convert the node, copying the spanned fragments of the source.
+    fn into_owned(self) -> Self::Owned;
+}
+
+impl<T: IntoOwned> IntoOwned for Vec<T> {
+    type Owned = Vec<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        self.into_iter().map(IntoOwned::into_owned).collect()
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Option<T> {
+    type Owned = Option<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        self.map(IntoOwned::into_owned)
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Box<T> {
+    type Owned = Box<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        Box::new((*self).into_owned())
+    }
+}
+
+/// An owned span: a fragment of the source with its position.
+///
+/// The line and the column are resolved once, when the span is converted.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OwnedSpan {
+    /// The byte offset of the fragment, starting at 0.
+    pub offset: usize,
+
+    /// The line number, starting at 1.
+    pub line: u32,
+
+    /// The column number, in Unicode scalar values, starting at 1.
+    pub column: u32,
+
+    /// The file of the fragment, see `SourceMap`.
+    pub file: FileId,
+
+    /// The fragment that is spanned.
+    pub fragment: String
+}
+
+impl OwnedSpan {
+    /// Extract the entire slice of the span.
+    pub fn as_slice(&self) -> &str {
+        &self.fragment
+    }
+
+    /// The position of the first byte of the span.
+    pub fn start(&self) -> Position {
+        Position {
+            offset: self.offset,
+            line  : self.line,
+            column: self.column
+        }
+    }
+
+    /// The position following the last byte of the span.
+    pub fn end(&self) -> Position {
+        self.start().advance(&self.fragment)
+    }
+
+    /// The byte range of the span, to slice the source.
+    pub fn byte_range(&self) -> Range<usize> {
+        self.offset..self.offset + self.fragment.len()
+    }
+}
+
+impl<'a> From<Span<'a>> for OwnedSpan {
+    fn from(span: Span<'a>) -> Self {
+        OwnedSpan {
+            offset  : span.offset,
+            line    : span.line(),
+            column  : span.column(),
+            file    : span.file,
+            fragment: span.as_slice().to_string()
+        }
+    }
+}
+
+impl<'a> IntoOwned for Span<'a> {
+    type Owned = OwnedSpan;
+
+    fn into_owned(self) -> Self::Owned {
+        OwnedSpan::from(self)
+    }
+}
+
+impl Spanned for OwnedSpan {
+    fn range(&self) -> SourceRange {
+        SourceRange::new(self.start(), self.end())
+    }
+}
+
+/// The storage of a tree owning its spans: a span is an `OwnedSpan`, and an
+/// error is an `OwnedParseError`.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Owned;
+
+impl<'a> Storage<'a> for Owned {
+    type Span  = OwnedSpan;
+    type Error = OwnedParseError;
+}
+
+/// An owned `ast::Token`.
+pub type Token<T> = ast::Token<'static, T, Owned>;
+
+/// An owned `ast::Literal`.
+pub type Literal = ast::Literal<'static, Owned>;
+
+/// An owned `ast::NAryOperation`.
+pub type NAryOperation = ast::NAryOperation<'static, Owned>;
+
+/// An owned `ast::Variable`.
+pub type Variable = ast::Variable<'static, Owned>;
+
+/// An owned `ast::Expression`.
+pub type Expression = ast::Expression<'static, Owned>;
+
+/// An owned `ast::ListItem`.
+pub type ListItem = ast::ListItem<'static, Owned>;
+
+/// An owned `ast::Statement`.
+pub type Statement = ast::Statement<'static, Owned>;
+
+/// An owned `ast::ContentPart`.
+pub type ContentPart = ast::ContentPart<'static, Owned>;
+
+/// An owned `ast::Divert`.
+pub type Divert = ast::Divert<'static, Owned>;
+
+/// An owned `ast::Content`.
+pub type Content = ast::Content<'static, Owned>;
+
+/// An owned `ast::Line`.
+pub type Line = ast::Line<'static, Owned>;
+
+/// An owned `ast::Annotation`.
+pub type Annotation = ast::Annotation<'static, Owned>;
+
+/// An owned `ast::Weave`.
+pub type Weave = ast::Weave<'static, Owned>;
+
+/// An owned `ast::Choice`.
+pub type Choice = ast::Choice<'static, Owned>;
+
+/// An owned `ast::Gather`.
+pub type Gather = ast::Gather<'static, Owned>;
+
+/// An owned `ast::ConditionalBranch`.
+pub type ConditionalBranch = ast::ConditionalBranch<'static, Owned>;
+
+/// An owned `ast::Conditional`.
+pub type Conditional = ast::Conditional<'static, Owned>;
+
+/// An owned `ast::Sequence`.
+pub type Sequence = ast::Sequence<'static, Owned>;
+
+/// An owned `ast::Comments`.
+pub type Comments = ast::Comments<'static, Owned>;
+
+/// An owned `ast::Stitch`.
+pub type Stitch = ast::Stitch<'static, Owned>;
+
+/// An owned `ast::Knot`.
+pub type Knot = ast::Knot<'static, Owned>;
+
+/// An owned `ast::Story`.
+pub type Story = ast::Story<'static, Owned>;
+
+macro_rules! impl_into_owned_for_copy {
+    ($($type:ty),*) => (
+        $(
+            impl IntoOwned for $type {
+                type Owned = $type;
+
+                fn into_owned(self) -> Self::Owned {
+                    self
+                }
+            }
+        )*
+    )
+}
+
+impl_into_owned_for_copy!(
+    Integer,
+    Real,
+    bool,
+    usize,
+    UnaryOperator,
+    BinaryOperator,
+    DeclarationKind,
+    SequenceKind
+);
+
+impl IntoOwned for String {
+    type Owned = String;
+
+    fn into_owned(self) -> Self::Owned {
+        self
+    }
+}
+
+macro_rules! impl_into_owned_for_struct {
+    ($($node:ident { $($field:ident),* }),*) => (
+        $(
+            impl<'a> IntoOwned for ast::$node<'a> {
+                type Owned = $node;
+
+                fn into_owned(self) -> Self::Owned {
+                    ast::$node {
+                        $($field: self.$field.into_owned()),*
+                    }
+                }
+            }
+        )*
+    )
+}
+
+impl_into_owned_for_struct!(
+    ListItem { name, value, included, span },
+    Weave { lines, span },
+    Choice { depth, sticky, label, start, choice_only, output, body, span },
+    Gather { depth, label, content, span },
+    ConditionalBranch { condition, body, span },
+    Conditional { branches, span },
+    Sequence { kind, branches, span },
+    Comments { leading, trailing },
+    Stitch { name, body, comments, span },
+    Knot { name, function, parameters, body, stitches, comments, span },
+    Story { body, knots, span }
+);
+
+impl<'a, T: IntoOwned> IntoOwned for ast::Token<'a, T> {
+    type Owned = Token<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        ast::Token {
+            value: self.value.into_owned(),
+            span : self.span.into_owned()
+        }
+    }
+}
+
+impl<'a> IntoOwned for ast::Variable<'a> {
+    type Owned = Variable;
+
+    fn into_owned(self) -> Self::Owned {
+        ast::Variable(self.0.into_owned())
+    }
+}
+
+impl<'a> IntoOwned for ast::Content<'a> {
+    type Owned = Content;
+
+    fn into_owned(self) -> Self::Owned {
+        ast::Content(self.0.into_owned())
+    }
+}
+
+impl<'a> IntoOwned for ast::Literal<'a> {
+    type Owned = Literal;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            ast::Literal::Integer(token) => ast::Literal::Integer(token.into_owned()),
+            ast::Literal::Real(token)    => ast::Literal::Real(token.into_owned()),
+            ast::Literal::Boolean(token) => ast::Literal::Boolean(token.into_owned()),
+            ast::Literal::String(token)  => ast::Literal::String(token.into_owned())
+        }
+    }
+}
+
+impl<'a> IntoOwned for ast::NAryOperation<'a> {
+    type Owned = NAryOperation;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            ast::NAryOperation::Nullary { operand, span } => {
+                ast::NAryOperation::Nullary {
+                    operand: operand.into_owned(),
+                    span   : span.into_owned()
+                }
+            },
+
+            ast::NAryOperation::Unary { operator, operand, span } => {
+                ast::NAryOperation::Unary {
+                    operator: operator.into_owned(),
+                    operand : operand.into_owned(),
+                    span    : span.into_owned()
+                }
+            },
+
+            ast::NAryOperation::Binary { operator, left_operand, right_operand, span } => {
+                ast::NAryOperation::Binary {
+                    operator     : operator.into_owned(),
+                    left_operand : left_operand.into_owned(),
+                    right_operand: right_operand.into_owned(),
+                    span         : span.into_owned()
+                }
+            }
+        }
+    }
+}
+
+impl<'a> IntoOwned for ast::Expression<'a> {
+    type Owned = Expression;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            ast::Expression::Literal(literal)         => ast::Expression::Literal(literal.into_owned()),
+            ast::Expression::NAryOperation(operation) => ast::Expression::NAryOperation(operation.into_owned()),
+            ast::Expression::Variable(variable)       => ast::Expression::Variable(variable.into_owned())
+        }
+    }
+}
+
+impl<'a> IntoOwned for ast::Statement<'a> {
+    type Owned = Statement;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            ast::Statement::Declaration { variable, value, kind, span, comments } => {
+                ast::Statement::Declaration {
+                    variable: variable.into_owned(),
+                    value   : value.into_owned(),
+                    kind,
+                    span    : span.into_owned(),
+                    comments: comments.into_owned()
+                }
+            },
+
+            ast::Statement::List { name, items, span, comments } => {
+                ast::Statement::List {
+                    name    : name.into_owned(),
+                    items   : items.into_owned(),
+                    span    : span.into_owned(),
+                    comments: comments.into_owned()
+                }
+            },
+
+            ast::Statement::Return { value, span } => {
+                ast::Statement::Return {
+                    value: value.into_owned(),
+                    span : span.into_owned()
+                }
+            }
+        }
+    }
+}
+
+impl<'a> IntoOwned for ast::ContentPart<'a> {
+    type Owned = ContentPart;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            ast::ContentPart::Text(span)           => ast::ContentPart::Text(span.into_owned()),
+            ast::ContentPart::Interpolation(token) => ast::ContentPart::Interpolation(token.into_owned()),
+            ast::ContentPart::Divert(token)        => ast::ContentPart::Divert(token.into_owned())
+        }
+    }
+}
+
+impl<'a> IntoOwned for ast::Divert<'a> {
+    type Owned = Divert;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            ast::Divert::Target(path) => ast::Divert::Target(path.into_owned()),
+            ast::Divert::End(span)    => ast::Divert::End(span.into_owned()),
+            ast::Divert::Done(span)   => ast::Divert::Done(span.into_owned())
+        }
+    }
+}
+
+impl<'a> IntoOwned for ast::Line<'a> {
+    type Owned = Line;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            ast::Line::Content(content)         => ast::Line::Content(content.into_owned()),
+            ast::Line::Statement(statement)     => ast::Line::Statement(statement.into_owned()),
+            ast::Line::Choice(choice)           => ast::Line::Choice(choice.into_owned()),
+            ast::Line::Gather(gather)           => ast::Line::Gather(gather.into_owned()),
+            ast::Line::Conditional(conditional) => ast::Line::Conditional(conditional.into_owned()),
+            ast::Line::Sequence(sequence)       => ast::Line::Sequence(sequence.into_owned()),
+            ast::Line::Annotation(annotation)   => ast::Line::Annotation(annotation.into_owned()),
+            ast::Line::Error(error)             => ast::Line::Error(error.into_owned())
+        }
+    }
+}
+
+impl<'a> IntoOwned for ast::Annotation<'a> {
+    type Owned = Annotation;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            ast::Annotation::Todo(span) => ast::Annotation::Todo(span.into_owned())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ContentPart,
+        IntoOwned,
+        Line,
+        OwnedSpan,
+        Story
+    };
+    use ast::ast::{
+        self,
+        Spanned
+    };
+    use options::ParserOptions;
+    use source_map::FileId;
+
+    use std::thread;
+
+    fn parse_owned(source: &str) -> Story {
+        ::parse(source, ParserOptions::default()).unwrap().into_owned()
+    }
+
+    #[test]
+    fn case_into_owned() {
+        let source = "Hello\n== london ==\n= first_class\nWorld {x + 1}\n".to_string();
+        let story  = parse_owned(&source);
+
+        drop(source);
+
+        assert_eq!(story.knots[0].name.as_slice(), "london");
+        assert_eq!(story.knots[0].stitches[0].name.line, 3);
+
+        match story.knots[0].stitches[0].body.lines[0] {
+            Line::Content(ast::Content(ref parts)) => {
+                assert_eq!(
+                    parts[0],
+                    ContentPart::Text(
+                        OwnedSpan {
+                            offset  : 33,
+                            line    : 4,
+                            column  : 1,
+                            file    : FileId::default(),
+                            fragment: "World ".to_string()
+                        }
+                    )
+                );
+                assert_eq!(parts[1].range().byte_range(), 39..46);
+            },
+
+            _ => panic!("expected a line of content")
+        }
+    }
+
+    #[test]
+    fn case_into_owned_error() {
+        let options = ParserOptions {
+            recover: true,
+            ..ParserOptions::default()
+        };
+        let story   = ::parse("Hello } world\n", options).unwrap().into_owned();
+
+        match story.body.lines[0] {
+            Line::Error(ref error) => {
+                assert_eq!(error.span.as_slice(), "Hello } world");
+                assert_eq!(error.value.span().column, 7);
+                assert_eq!(error.value.to_string(), "1:7: unexpected `}`");
+            },
+
+            _ => panic!("expected an error line")
+        }
+    }
+
+    #[test]
+    fn case_into_owned_across_threads() {
+        let story = parse_owned("~ x = 1\n-> END\n");
+        let count = thread::spawn(move || story.body.lines.len()).join().unwrap();
+
+        assert_eq!(count, 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn case_serialize_span() {
+        use nom::Slice;
+        use serde_json;
+        use span::Span;
+
+        let span = Span::new("Hello\nWörld\n").slice(6..12);
+
+        assert_eq!(
+            serde_json::to_value(span).unwrap(),
+            json!({ "offset": 6, "line": 2, "column": 1, "file": 0, "fragment": "Wörld" })
+        );
+        assert_eq!(serde_json::to_value(span.into_owned()).unwrap(), serde_json::to_value(span).unwrap());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn case_serialize_error_line() {
+        use serde_json;
+
+        let options = ParserOptions {
+            recover: true,
+            ..ParserOptions::default()
+        };
+        let story   = ::parse("{\n", options).unwrap();
+        let line    = &story.body.lines[0];
+
+        assert_eq!(
+            serde_json::to_value(line).unwrap(),
+            json!({
+                "Error": {
+                    "value": {
+                        "Unexpected": {
+                            "span"    : { "offset": 2, "line": 2, "column": 1, "file": 0, "fragment": "" },
+                            "expected": [{ "Token": "}" }]
+                        }
+                    },
+                    "span" : { "offset": 0, "line": 1, "column": 1, "file": 0, "fragment": "{" }
+                }
+            })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn case_serde_round_trip() {
+        use serde_json;
+
+        let source = "Hello {x}!\n~ x = 1 + 2 * 3\n* Hello [back] right back -> london\n- Bye\n{ stopping:\n- A\n- B\n}\n{ x:\n  C\n- else:\n  D\n}\n== london(y) ==\n= first_class\n~ return y\n-> END\n";
+        let story  = ::parse(source, ParserOptions::default()).unwrap();
+        let json   = serde_json::to_string(&story).unwrap();
+
+        assert_eq!(serde_json::from_str::<Story>(&json).unwrap(), story.into_owned());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn case_into_owned_every_variant() {
+        use serde_json;
+
+        let source  = "\
+VAR g = \"hi\"
+CONST c = 1
+LIST l = a, (b = 2)
+Hello {x}! -> DONE
+~ x = -1 + 2.5 * 3
+~ y = true && !false
+TODO: describe the room
+* Go [back] right -> london
++ Stay
+- Bye -> END
+{ x > 0:
+  A
+- else:
+  B
+}
+{ stopping:
+- A
+}
+{ cycle:
+- A
+}
+{ once:
+- A
+}
+{ shuffle:
+- A
+}
+Oops }
+{99999999999999999999}
+== function f(a) ==
+~ return a
+== london ==
+= first_class
+Hi
+";
+        let options = ParserOptions {
+            recover: true,
+            ..ParserOptions::default()
+        };
+        let story   = ::parse(source, options).unwrap();
+        let json    = serde_json::to_value(&story).unwrap();
+        let text    = json.to_string();
+
+        for variant in &[
+            "Content", "Statement", "Choice", "Gather", "Conditional", "Sequence", "Annotation", "Error",
+            "Declaration", "List", "Return", "Statement", "Global", "Constant", "Text", "Interpolation", "Divert", "Target", "End", "Done",
+            "Integer", "Real", "Boolean", "String", "Nullary", "Unary", "Binary", "Variable",
+            "Stopping", "Cycle", "Once", "Shuffle", "Todo", "Unexpected", "Invalid"
+        ] {
+            assert!(text.contains(&format!("\"{}\"", variant)), "no `{}` in the story", variant);
+        }
+
+        let owned = story.into_owned();
+
+        assert_eq!(serde_json::to_value(&owned).unwrap(), json);
+        assert_eq!(serde_json::from_value::<Story>(json).unwrap(), owned);
+    }
+}
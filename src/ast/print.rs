@@ -0,0 +1,939 @@
+//! Print a syntax tree back to Ink.
+//!
+//! The printed source parses to an equivalent tree, i.e. the same tree up
+//! to the spans. The layout is normalized: a nested weave is indented with
+//! two spaces, an operation gets the fewest parentheses that keep its
+//! shape, and the blank lines of the source are lost. The comments
+//! documenting the knots, the stitches and the declarations are kept, as
+//! single line comments when possible; the other comments are lost.
+//!
+//! The texts and the names are printed as they are. A tree built by hand
+//! must hold texts that the parser recognizes as texts, e.g. without a
+//! curly bracket.
+
+use std::fmt::Write;
+
+use super::ast::{
+    Annotation,
+    BinaryOperator,
+    Choice,
+    Comments,
+    Conditional,
+    Content,
+    ContentPart,
+    DeclarationKind,
+    Divert,
+    Expression,
+    Gather,
+    Knot,
+    Line,
+    ListItem,
+    Literal,
+    NAryOperation,
+    Sequence,
+    SequenceKind,
+    Statement,
+    Stitch,
+    Story,
+    Token,
+    UnaryOperator,
+    Weave
+};
+use rules::expressions::operations::{
+    BINARY_OPERATORS,
+    UNARY_OPERATORS,
+    Associativity,
+    BinaryOperatorDefinition,
+    Fixity,
+    UnaryOperatorDefinition
+};
+use rules::identifier::is_identifier;
+use span::Span;
+use tokens;
+
+/// The indentation of a nested weave.
+const INDENTATION: &str = "  ";
+
+/// Print a story.
+pub fn print(story: &Story) -> String {
+    let mut printer = Printer::new();
+
+    printer.story(story);
+
+    printer.output
+}
+
+/// Print a weave.
+pub fn print_weave(weave: &Weave) -> String {
+    let mut printer = Printer::new();
+
+    printer.weave(weave);
+
+    printer.output
+}
+
+/// Print an expression.
+pub fn print_expression(expression: &Expression) -> String {
+    let mut output = String::new();
+
+    write_expression(&mut output, expression);
+
+    output
+}
+
+/// A printer writing lines at an indentation level.
+struct Printer {
+    output     : String,
+    indentation: usize
+}
+
+impl Printer {
+    fn new() -> Self {
+        Printer {
+            output     : String::new(),
+            indentation: 0
+        }
+    }
+
+    /// Start a line at the current indentation.
+    fn start_line(&mut self) {
+        for _ in 0..self.indentation {
+            self.output.push_str(INDENTATION);
+        }
+    }
+
+    fn end_line(&mut self) {
+        self.output.push('\n');
+    }
+
+    /// Separate a header from the previous lines by a blank line.
+    fn blank_line(&mut self) {
+        if !self.output.is_empty() {
+            self.end_line();
+        }
+    }
+
+    /// Print a weave one level deeper.
+    fn nested_weave(&mut self, weave: &Weave) {
+        self.indentation += 1;
+        self.weave(weave);
+        self.indentation -= 1;
+    }
+
+    fn story(&mut self, story: &Story) {
+        self.weave(&story.body);
+
+        for knot in &story.knots {
+            self.knot(knot);
+        }
+    }
+
+    /// Print the comments above a header or a declaration, one per line.
+    fn leading_comments(&mut self, comments: &Comments) {
+        for comment in &comments.leading {
+            self.start_line();
+            write_comment(&mut self.output, comment.as_slice(), false);
+            self.end_line();
+        }
+    }
+
+    /// Print the comments following a header or a declaration on its line.
+    /// Only the last one can be a single line comment.
+    fn trailing_comments(&mut self, comments: &Comments) {
+        let count = comments.trailing.len();
+
+        for (index, comment) in comments.trailing.iter().enumerate() {
+            self.output.push(' ');
+            write_comment(&mut self.output, comment.as_slice(), index + 1 < count);
+        }
+    }
+
+    fn knot(&mut self, knot: &Knot) {
+        self.blank_line();
+        self.leading_comments(&knot.comments);
+        self.start_line();
+        self.output.push_str(tokens::KNOT);
+        self.output.push(' ');
+
+        if knot.function {
+            self.output.push_str(tokens::FUNCTION);
+            self.output.push(' ');
+        }
+
+        self.output.push_str(knot.name.as_slice());
+
+        if !knot.parameters.is_empty() {
+            let parameters: Vec<&str> = knot.parameters.iter().map(|parameter| parameter.0.as_slice()).collect();
+
+            self.output.push_str(tokens::LEFT_PARENTHESIS);
+            self.output.push_str(&parameters.join(&format!("{} ", tokens::COMMA)));
+            self.output.push_str(tokens::RIGHT_PARENTHESIS);
+        }
+
+        self.output.push(' ');
+        self.output.push_str(tokens::KNOT);
+        self.trailing_comments(&knot.comments);
+        self.end_line();
+        self.weave(&knot.body);
+
+        for stitch in &knot.stitches {
+            self.stitch(stitch);
+        }
+    }
+
+    fn stitch(&mut self, stitch: &Stitch) {
+        self.blank_line();
+        self.leading_comments(&stitch.comments);
+        self.start_line();
+        self.output.push_str(tokens::STITCH);
+        self.output.push(' ');
+        self.output.push_str(stitch.name.as_slice());
+        self.trailing_comments(&stitch.comments);
+        self.end_line();
+        self.weave(&stitch.body);
+    }
+
+    fn weave(&mut self, weave: &Weave) {
+        for line in &weave.lines {
+            self.line(line);
+        }
+    }
+
+    fn line(&mut self, line: &Line) {
+        match *line {
+            Line::Content(ref content) => {
+                self.start_line();
+                write_content(&mut self.output, content);
+                self.end_line();
+            },
+
+            Line::Statement(ref statement) => {
+                let comments = match *statement {
+                    Statement::Declaration { ref comments, .. } | Statement::List { ref comments, .. } => Some(comments),
+                    Statement::Return { .. }                                                            => None
+                };
+
+                if let Some(comments) = comments {
+                    self.leading_comments(comments);
+                }
+
+                self.start_line();
+                write_statement(&mut self.output, statement);
+
+                if let Some(comments) = comments {
+                    self.trailing_comments(comments);
+                }
+
+                self.end_line();
+            },
+
+            Line::Choice(ref choice)           => self.choice(choice),
+            Line::Gather(ref gather)           => self.gather(gather),
+            Line::Conditional(ref conditional) => self.conditional(conditional),
+            Line::Sequence(ref sequence)       => self.sequence(sequence),
+
+            Line::Annotation(Annotation::Todo(message)) => {
+                self.start_line();
+                self.output.push_str(tokens::TODO);
+                self.output.push_str(tokens::COLON);
+
+                if !message.as_slice().is_empty() {
+                    self.output.push(' ');
+                    self.output.push_str(message.as_slice());
+                }
+
+                self.end_line();
+            },
+
+            // The malformed line is printed as it is, so that it is
+            // reported again.
+            Line::Error(ref error) => {
+                self.start_line();
+                self.output.push_str(error.span.as_slice());
+                self.end_line();
+            }
+        }
+    }
+
+    fn choice(&mut self, choice: &Choice) {
+        let bullet = if choice.sticky { tokens::STICKY_CHOICE } else { tokens::CHOICE };
+
+        self.start_line();
+        write_bullets(&mut self.output, bullet, choice.depth);
+        write_label(&mut self.output, &choice.label);
+
+        match choice.start {
+            Some(ref start) => {
+                self.output.push(' ');
+                write_content(&mut self.output, start);
+            },
+
+            None if choice.choice_only.is_some() || choice.output.is_some() => self.output.push(' '),
+            None                                                            => {}
+        }
+
+        // Without a start, the square brackets are kept even when empty,
+        // e.g. `* []Bye`, since the output would be parsed as the start of
+        // the choice otherwise.
+        match (&choice.choice_only, &choice.output) {
+            (Some(choice_only), _) => {
+                self.output.push_str(tokens::LEFT_SQUARE_BRACKET);
+                write_content(&mut self.output, choice_only);
+                self.output.push_str(tokens::RIGHT_SQUARE_BRACKET);
+            },
+
+            (None, Some(_)) if choice.start.is_none() => {
+                self.output.push_str(tokens::LEFT_SQUARE_BRACKET);
+                self.output.push_str(tokens::RIGHT_SQUARE_BRACKET);
+            },
+
+            _ => {}
+        }
+
+        if let Some(ref output) = choice.output {
+            write_content(&mut self.output, output);
+        }
+
+        self.end_line();
+        self.nested_weave(&choice.body);
+    }
+
+    fn gather(&mut self, gather: &Gather) {
+        self.start_line();
+        write_bullets(&mut self.output, tokens::GATHER, gather.depth);
+        write_label(&mut self.output, &gather.label);
+
+        if let Some(ref content) = gather.content {
+            self.output.push(' ');
+            write_content(&mut self.output, content);
+        }
+
+        self.end_line();
+    }
+
+    /// Print a conditional. The first branch is on the opening line, unless
+    /// it is an `else` branch.
+    fn conditional(&mut self, conditional: &Conditional) {
+        let branches = &conditional.branches;
+
+        self.start_line();
+        self.output.push_str(tokens::LEFT_CURLY_BRACKET);
+
+        let rest = match branches.first() {
+            Some(branch) if branch.condition.is_some() => {
+                self.output.push(' ');
+                write_branch_condition(&mut self.output, &branch.condition);
+                self.end_line();
+                self.nested_weave(&branch.body);
+
+                &branches[1..]
+            },
+
+            _ => {
+                self.end_line();
+
+                &branches[..]
+            }
+        };
+
+        for branch in rest {
+            self.start_line();
+            self.output.push_str(tokens::GATHER);
+            self.output.push(' ');
+            write_branch_condition(&mut self.output, &branch.condition);
+            self.end_line();
+            self.nested_weave(&branch.body);
+        }
+
+        self.start_line();
+        self.output.push_str(tokens::RIGHT_CURLY_BRACKET);
+        self.end_line();
+    }
+
+    /// Print a sequence. A branch made of a single line of content is
+    /// printed on the line of its dash.
+    fn sequence(&mut self, sequence: &Sequence) {
+        self.start_line();
+        self.output.push_str(tokens::LEFT_CURLY_BRACKET);
+        self.output.push(' ');
+        self.output.push_str(sequence_kind_token(sequence.kind));
+        self.output.push_str(tokens::COLON);
+        self.end_line();
+
+        for branch in &sequence.branches {
+            self.start_line();
+            self.output.push_str(tokens::GATHER);
+
+            match branch.lines.as_slice() {
+                [Line::Content(ref content)] => {
+                    self.output.push(' ');
+                    write_content(&mut self.output, content);
+                    self.end_line();
+                },
+
+                _ => {
+                    self.end_line();
+                    self.nested_weave(branch);
+                }
+            }
+        }
+
+        self.start_line();
+        self.output.push_str(tokens::RIGHT_CURLY_BRACKET);
+        self.end_line();
+    }
+}
+
+/// Write the bullets of a choice or of a gather, e.g. `* *`.
+fn write_bullets(output: &mut String, bullet: &str, depth: usize) {
+    let bullets = vec![bullet; depth];
+
+    output.push_str(&bullets.join(" "));
+}
+
+/// Write the label of a choice or of a gather after its bullets, e.g.
+/// ` (greet)`.
+fn write_label(output: &mut String, label: &Option<Span>) {
+    if let Some(ref label) = *label {
+        output.push(' ');
+        output.push_str(tokens::LEFT_PARENTHESIS);
+        output.push_str(label.as_slice());
+        output.push_str(tokens::RIGHT_PARENTHESIS);
+    }
+}
+
+/// Write the condition of a branch with its colon, e.g. `x > 0:` or
+/// `else:`.
+fn write_branch_condition(output: &mut String, condition: &Option<Expression>) {
+    match *condition {
+        Some(ref condition) => write_expression(output, condition),
+        None                => output.push_str(tokens::ELSE)
+    }
+
+    output.push_str(tokens::COLON);
+}
+
+/// Write a comment, as a single line comment (`// …`) unless it must be
+/// `delimited` or it spans several lines (`/* … */`).
+fn write_comment(output: &mut String, comment: &str, delimited: bool) {
+    if delimited || comment.contains('\n') {
+        output.push_str(tokens::BLOCK_COMMENT_OPEN);
+        output.push_str(comment);
+        output.push_str(tokens::BLOCK_COMMENT_CLOSE);
+    } else {
+        output.push_str(tokens::INLINE_COMMENT);
+        output.push_str(comment);
+    }
+}
+
+fn sequence_kind_token(kind: SequenceKind) -> &'static str {
+    match kind {
+        SequenceKind::Stopping => tokens::STOPPING,
+        SequenceKind::Cycle    => tokens::CYCLE,
+        SequenceKind::Once     => tokens::ONCE,
+        SequenceKind::Shuffle  => tokens::SHUFFLE
+    }
+}
+
+fn write_content(output: &mut String, content: &Content) {
+    for part in &content.0 {
+        match *part {
+            ContentPart::Text(text) => output.push_str(text.as_slice()),
+
+            ContentPart::Interpolation(ref token) => {
+                output.push_str(tokens::LEFT_CURLY_BRACKET);
+                write_expression(output, &token.value);
+                output.push_str(tokens::RIGHT_CURLY_BRACKET);
+            },
+
+            ContentPart::Divert(ref token) => {
+                output.push_str(tokens::DIVERT);
+                output.push(' ');
+                write_divert(output, &token.value);
+            }
+        }
+    }
+}
+
+fn write_divert(output: &mut String, divert: &Divert) {
+    match *divert {
+        Divert::Target(ref path) => {
+            let path: Vec<&str> = path.iter().map(|name| name.as_slice()).collect();
+
+            output.push_str(&path.join(tokens::DOT));
+        },
+
+        Divert::End(_)  => output.push_str(tokens::END),
+        Divert::Done(_) => output.push_str(tokens::DONE)
+    }
+}
+
+fn write_statement(output: &mut String, statement: &Statement) {
+    let keyword = match *statement {
+        Statement::Declaration { kind, .. } => declaration_keyword(kind),
+        Statement::List { .. }              => tokens::LIST,
+        Statement::Return { .. }            => tokens::STATEMENT
+    };
+
+    output.push_str(keyword);
+
+    output.push(' ');
+
+    match *statement {
+        Statement::Declaration { ref variable, ref value, .. } => {
+            output.push_str(variable.0.as_slice());
+            output.push(' ');
+            output.push_str(tokens::ASSIGN);
+            output.push(' ');
+            write_expression(output, value);
+        },
+
+        Statement::List { ref name, ref items, .. } => {
+            output.push_str(name.0.as_slice());
+            output.push(' ');
+            output.push_str(tokens::ASSIGN);
+            output.push(' ');
+
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(tokens::COMMA);
+                    output.push(' ');
+                }
+
+                write_list_item(output, item);
+            }
+        },
+
+        Statement::Return { ref value, .. } => {
+            output.push_str(tokens::RETURN);
+            output.push(' ');
+            write_expression(output, value);
+        }
+    }
+}
+
+/// The keyword or the tilde starting a declaration.
+fn declaration_keyword(kind: DeclarationKind) -> &'static str {
+    match kind {
+        DeclarationKind::Statement => tokens::STATEMENT,
+        DeclarationKind::Global    => tokens::GLOBAL_VARIABLE,
+        DeclarationKind::Constant  => tokens::CONSTANT
+    }
+}
+
+/// Write an item of a list, between parentheses if it is in the initial
+/// value of the list.
+fn write_list_item(output: &mut String, item: &ListItem) {
+    if item.included {
+        output.push_str(tokens::LEFT_PARENTHESIS);
+    }
+
+    output.push_str(item.name.as_slice());
+
+    if let Some(ref value) = item.value {
+        let _ = write!(output, " {} {}", tokens::ASSIGN, value.value);
+    }
+
+    if item.included {
+        output.push_str(tokens::RIGHT_PARENTHESIS);
+    }
+}
+
+fn write_expression(output: &mut String, expression: &Expression) {
+    match *expression {
+        Expression::Literal(ref literal)         => write_literal(output, literal),
+        Expression::NAryOperation(ref operation) => write_operation(output, operation),
+        Expression::Variable(ref variable)       => output.push_str(variable.0.as_slice())
+    }
+}
+
+/// Write a literal. An integer is written in decimal, and a real always has
+/// a fractional part or an exponent, so that it is not parsed as an
+/// integer. A large or a small real keeps its exponent, e.g. `1e300`.
+fn write_literal(output: &mut String, literal: &Literal) {
+    match *literal {
+        Literal::Integer(ref token) => {
+            let _ = write!(output, "{}", token.value);
+        },
+
+        Literal::Real(ref token) => {
+            let real = format!("{:?}", token.value);
+
+            output.push_str(&real);
+
+            if !real.contains(['.', 'e', 'E']) {
+                output.push_str(".0");
+            }
+        },
+
+        Literal::Boolean(ref token) => {
+            output.push_str(if token.value { tokens::TRUE } else { tokens::FALSE });
+        },
+
+        Literal::String(ref token) => write_string(output, &token.value)
+    }
+}
+
+/// Write a string between double quotes, with the double quotes and the
+/// backslashes escaped by a backslash.
+fn write_string(output: &mut String, string: &str) {
+    output.push('"');
+
+    for item in string.chars() {
+        if item == '"' || item == '\\' {
+            output.push('\\');
+        }
+
+        output.push(item);
+    }
+
+    output.push('"');
+}
+
+/// The tightness of an operation, to decide whether it needs parentheses
+/// as an operand.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+enum Tightness {
+    /// A binary operation, with the precedence of its operator.
+    Binary(u8),
+
+    /// An operation with a prefix operator.
+    Prefix,
+
+    /// An operation with a postfix operator.
+    Postfix,
+
+    /// An operand without operator.
+    Atom
+}
+
+/// The operation of a nullary operation wrapping an operation, or the
+/// operation itself.
+fn unwrap_operation<'o, 'a>(operation: &'o NAryOperation<'a>) -> &'o NAryOperation<'a> {
+    match *operation {
+        NAryOperation::Nullary { operand: ref expression, .. } => {
+            match **expression {
+                Expression::NAryOperation(ref operation) => unwrap_operation(operation),
+                _                                        => operation
+            }
+        },
+
+        _ => operation
+    }
+}
+
+fn tightness(operation: &NAryOperation) -> Tightness {
+    match *unwrap_operation(operation) {
+        NAryOperation::Nullary { .. } => Tightness::Atom,
+
+        NAryOperation::Unary { ref operator, .. } => {
+            match unary_definition(operator).fixity {
+                Fixity::Prefix  => Tightness::Prefix,
+                Fixity::Postfix => Tightness::Postfix
+            }
+        },
+
+        NAryOperation::Binary { ref operator, .. } => Tightness::Binary(binary_definition(operator).precedence)
+    }
+}
+
+/// Write an operation with the fewest parentheses.
+///
+/// The operand on the side opposite to the associativity of a binary
+/// operator needs parentheses when it binds as loosely as the operator,
+/// e.g. the right operand of a left-associative operator. The unary
+/// operators bind tighter than the binary operators, and the postfix
+/// operators bind tighter than the prefix operators.
+fn write_operation(output: &mut String, operation: &NAryOperation) {
+    match *unwrap_operation(operation) {
+        NAryOperation::Nullary { operand: ref expression, .. } => write_expression(output, expression),
+
+        NAryOperation::Unary { ref operator, ref operand, .. } => {
+            let definition = unary_definition(operator);
+
+            match definition.fixity {
+                Fixity::Prefix => {
+                    let mut operand_output = String::new();
+
+                    write_operand(&mut operand_output, operand, Tightness::Prefix);
+                    output.push_str(definition.token);
+
+                    // A keyword must not stick to its operand, e.g. `not x`,
+                    // nor an operator that would form another operator with
+                    // it, e.g. `- -x` and not the decrement `--x`.
+                    if definition.token.starts_with(is_identifier) || forms_operator(definition.token, &operand_output) {
+                        output.push(' ');
+                    }
+
+                    output.push_str(&operand_output);
+                },
+
+                Fixity::Postfix => {
+                    write_operand(output, operand, Tightness::Postfix);
+                    output.push_str(definition.token);
+                }
+            }
+        },
+
+        NAryOperation::Binary { ref operator, ref left_operand, ref right_operand, .. } => {
+            let definition = binary_definition(operator);
+            let (left, right) = match definition.associativity {
+                Associativity::Left  => (definition.precedence, definition.precedence + 1),
+                Associativity::Right => (definition.precedence + 1, definition.precedence)
+            };
+
+            write_operand(output, left_operand, Tightness::Binary(left));
+            output.push(' ');
+            output.push_str(definition.token);
+            output.push(' ');
+            write_operand(output, right_operand, Tightness::Binary(right));
+        }
+    }
+}
+
+/// Whether an operator token followed by the first character of `text`
+/// starts another operator, e.g. `-` followed by `-x` starts `--`.
+fn forms_operator(token: &str, text: &str) -> bool {
+    let item = match text.chars().next() {
+        Some(item) => item,
+        None       => return false
+    };
+    let joined = format!("{}{}", token, item);
+
+    UNARY_OPERATORS
+        .iter()
+        .map(|definition| definition.token)
+        .chain(BINARY_OPERATORS.iter().map(|definition| definition.token))
+        .any(|other| other.starts_with(&joined))
+}
+
+/// Write an operand, between parentheses if it binds more loosely than
+/// `minimum`.
+fn write_operand(output: &mut String, operand: &NAryOperation, minimum: Tightness) {
+    if tightness(operand) < minimum {
+        output.push_str(tokens::LEFT_PARENTHESIS);
+        write_operation(output, operand);
+        output.push_str(tokens::RIGHT_PARENTHESIS);
+    } else {
+        write_operation(output, operand);
+    }
+}
+
+/// The definition of a binary operator. When the operator has several
+/// tokens, e.g. `&&` and `and`, the token of the source is kept.
+fn binary_definition(operator: &Token<BinaryOperator>) -> &'static BinaryOperatorDefinition {
+    let mut definitions = BINARY_OPERATORS.iter().filter(|definition| definition.operator == operator.value);
+
+    definitions
+        .clone()
+        .find(|definition| definition.token == operator.span.as_slice())
+        .or_else(|| definitions.next())
+        .expect("Every binary operator is defined.")
+}
+
+/// The definition of a unary operator. When the operator has several
+/// tokens, e.g. `!` and `not`, the token of the source is kept.
+fn unary_definition(operator: &Token<UnaryOperator>) -> &'static UnaryOperatorDefinition {
+    let mut definitions = UNARY_OPERATORS.iter().filter(|definition| definition.operator == operator.value);
+
+    definitions
+        .clone()
+        .find(|definition| definition.token == operator.span.as_slice())
+        .or_else(|| definitions.next())
+        .expect("Every unary operator is defined.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        print,
+        print_expression,
+        write_operation
+    };
+    use ast::ast::{
+        BinaryOperator,
+        Expression,
+        Literal,
+        NAryOperation,
+        Token,
+        UnaryOperator
+    };
+    use options::ParserOptions;
+    use span::Span;
+
+    fn reprint(input: &str) -> String {
+        print(&::parse(input, ParserOptions::default()).unwrap())
+    }
+
+    fn literal(value: Literal) -> Box<NAryOperation> {
+        Box::new(NAryOperation::Nullary { operand: Box::new(Expression::Literal(value)), span: Span::empty() })
+    }
+
+    fn integer(value: ::ast::ast::Integer) -> Box<NAryOperation<'static>> {
+        literal(Literal::Integer(Token::new(value, Span::empty())))
+    }
+
+    fn binary<'a>(operator: BinaryOperator, left_operand: Box<NAryOperation<'a>>, right_operand: Box<NAryOperation<'a>>) -> Box<NAryOperation<'a>> {
+        Box::new(NAryOperation::Binary {
+            operator: Token::new(operator, Span::empty()),
+            left_operand,
+            right_operand,
+            span    : Span::empty()
+        })
+    }
+
+    fn unary(operator: UnaryOperator, operand: Box<NAryOperation>) -> Box<NAryOperation> {
+        Box::new(NAryOperation::Unary {
+            operator: Token::new(operator, Span::empty()),
+            operand,
+            span    : Span::empty()
+        })
+    }
+
+    fn expression(operation: &NAryOperation) -> String {
+        let mut output = String::new();
+
+        write_operation(&mut output, operation);
+
+        output
+    }
+
+    #[test]
+    fn case_print_story() {
+        let input = "\
+Hello {name}!
+~ x = 1 + 2 * 3
+TODO: describe the room
+-> london
+
+== london ==
+* Hello [back] right back -> first_class
+  * * [Stay]
+    Nice.
+  - - Done
++ []Leave
+- Bye
+{ x > 0:
+  Positive
+- else:
+  Negative
+}
+{ stopping:
+- A
+-
+  B
+  C
+}
+
+= first_class
+-> END
+
+== function lerp(a, b, k) ==
+~ return (b - a) * k + a
+";
+
+        assert_eq!(reprint(input), input);
+    }
+
+    #[test]
+    fn case_print_comments() {
+        let input  = "Hello\n// Ignored.\n\n// The capital.\n/* Of\n   England. */\n== london == /* One. */ // Two.\n/* First. */ = first_class /* Three. */\n";
+        let output = "Hello\n\n// The capital.\n/* Of\n   England. */\n== london == /* One. */ // Two.\n\n// First. \n= first_class // Three. \n";
+
+        assert_eq!(reprint(input), output);
+        assert_eq!(reprint(output), output);
+    }
+
+    #[test]
+    fn case_print_declaration_comments() {
+        let input  = "// The score.\nVAR  score = 0 /* Points. */\n~ x = 1 // Health.\n";
+        let output = "// The score.\nVAR score = 0 // Points. \n~ x = 1 // Health.\n";
+
+        assert_eq!(reprint(input), output);
+        assert_eq!(reprint(output), output);
+    }
+
+    #[test]
+    fn case_print_is_a_fixed_point() {
+        let input  = "Hi  ->london.first_class\n{\n- x: A\n- else: B\n}\n~ y = ((1)) + (2 - 3) - (4 - 5)\n== london ==\n= first_class\n*\t Go [A]  B\n";
+        let output = reprint(input);
+
+        assert_eq!(output, "Hi  -> london.first_class\n{ x:\n  A\n- else:\n  B\n}\n~ y = 1 + (2 - 3) - (4 - 5)\n\n== london ==\n\n= first_class\n* Go [A]  B\n");
+        assert_eq!(reprint(&output), output);
+    }
+
+    #[test]
+    fn case_print_keeps_the_operator_tokens() {
+        assert_eq!(reprint("{not x and y or z mod 2 has w}\n"), "{not x and y or z mod 2 has w}\n");
+        assert_eq!(reprint("{!x && (y || z)}\n"), "{!x && (y || z)}\n");
+    }
+
+    #[test]
+    fn case_print_parentheses() {
+        use ast::ast::BinaryOperator::*;
+        use ast::ast::UnaryOperator::*;
+
+        assert_eq!(expression(&binary(Subtraction, integer(1), binary(Subtraction, integer(2), integer(3)))), "1 - (2 - 3)");
+        assert_eq!(expression(&binary(Subtraction, binary(Subtraction, integer(1), integer(2)), integer(3))), "1 - 2 - 3");
+        assert_eq!(expression(&binary(Multiplication, binary(Addition, integer(1), integer(2)), integer(3))), "(1 + 2) * 3");
+        assert_eq!(expression(&binary(Addition, integer(1), binary(Multiplication, integer(2), integer(3)))), "1 + 2 * 3");
+        assert_eq!(expression(&binary(LogicalOr, binary(Equal, integer(1), integer(2)), binary(LogicalAnd, integer(3), integer(4)))), "1 == 2 || 3 && 4");
+        assert_eq!(expression(&unary(Minus, binary(Addition, integer(1), integer(2)))), "-(1 + 2)");
+        assert_eq!(expression(&unary(Minus, unary(Minus, integer(1)))), "- -1");
+        assert_eq!(expression(&unary(Negate, unary(Increment, integer(1)))), "!1++");
+        assert_eq!(expression(&unary(Increment, unary(Negate, integer(1)))), "(!1)++");
+        assert_eq!(expression(&binary(Subtraction, integer(1), unary(Minus, integer(2)))), "1 - -2");
+    }
+
+    #[test]
+    fn case_print_lists_and_constants() {
+        let input = "// The kettle.\nLIST kettle = cold, (boiling = 5) // States.\nCONST max = 3\n";
+
+        assert_eq!(reprint(input), input);
+    }
+
+    #[test]
+    fn case_print_parentheses_reparse() {
+        let input = "{-(1 + 2) * 3 - (4 % (5 / 6))}\n{(x++)-- + -(-y)}\n{(a | b) & (c ^ d) << 1}\n";
+
+        assert_eq!(reprint(input), "{-(1 + 2) * 3 - 4 % (5 / 6)}\n{x++-- + - -y}\n{(a | b) & (c ^ d) << 1}\n");
+        assert_eq!(reprint(&reprint(input)), reprint(input));
+    }
+
+    #[test]
+    fn case_print_nested_prefix_operators() {
+        let input = "{-(-a) + +(+b) + !(!c) + not (not d)}\n";
+
+        assert_eq!(reprint(input), "{- -a + + +b + !!c + not not d}\n");
+        assert_eq!(reprint(&reprint(input)), reprint(input));
+    }
+
+    #[test]
+    fn case_print_choice_only() {
+        let input = "* [Leave]\n  * * [Stay] here\n+ []Bye\n";
+
+        assert_eq!(reprint(input), input);
+    }
+
+    #[test]
+    fn case_print_labels() {
+        let input = "* (greet) Hello\n  * * (stay)[Stay]\n- (end) Bye\n-  (done)\n";
+
+        assert_eq!(reprint(input), "* (greet) Hello\n  * * (stay) [Stay]\n- (end) Bye\n- (done)\n");
+        assert_eq!(reprint(&reprint(input)), reprint(input));
+    }
+
+    #[test]
+    fn case_print_literals() {
+        let string = Expression::Literal(Literal::String(Token::new("say \"hi\" \\o/".to_string(), Span::empty())));
+
+        assert_eq!(print_expression(&string), "\"say \\\"hi\\\" \\\\o/\"");
+        assert_eq!(reprint("{\"a\\\"b\\\\c\\d\"}\n"), "{\"a\\\"b\\\\cd\"}\n");
+        assert_eq!(reprint("{0x1F + 0b11 + 017 + 1.5e3 + 2.}\n"), "{31 + 3 + 15 + 1500.0 + 2.0}\n");
+        assert_eq!(reprint("{true || false}\n"), "{true || false}\n");
+        assert_eq!(reprint("{1e30 + 1.5e-7}\n"), "{1e30 + 1.5e-7}\n");
+
+        #[cfg(not(feature = "ink-numbers"))]
+        assert_eq!(reprint("{1e300}\n"), "{1e300}\n");
+    }
+}
@@ -0,0 +1,584 @@
+//! Traversals of the syntax tree.
+//!
+//! A `Visitor` has a method per node type, whose default implementation
+//! calls the `walk_*` function of the node, i.e. visits the children of the
+//! node. An analysis overrides the methods of the nodes it cares about, and
+//! calls the `walk_*` function from an overridden method to keep visiting
+//! the children. `VisitorMut` is the same for a mutable tree.
+//!
+//! The children are visited in the order of the source, except the operator
+//! of a unary operation, which is always visited before its operand.
+
+use super::ast::{
+    Annotation,
+    BinaryOperator,
+    Choice,
+    Conditional,
+    ConditionalBranch,
+    Content,
+    ContentPart,
+    Divert,
+    Expression,
+    Gather,
+    Knot,
+    Line,
+    Literal,
+    NAryOperation,
+    Sequence,
+    Statement,
+    Stitch,
+    Story,
+    Token,
+    UnaryOperator,
+    Variable,
+    Weave
+};
+use error::ParseError;
+use span::Span;
+
+/// A visitor of a syntax tree borrowed for `'s`, whose spans borrow a source
+/// for `'a`.
+///
+/// The nodes are borrowed for `'s`, so that a visitor can keep references to
+/// them, e.g. to the knot being visited.
+pub trait Visitor<'s, 'a: 's> {
+    fn visit_story(&mut self, story: &'s Story<'a>) {
+        walk_story(self, story)
+    }
+
+    fn visit_knot(&mut self, knot: &'s Knot<'a>) {
+        walk_knot(self, knot)
+    }
+
+    fn visit_stitch(&mut self, stitch: &'s Stitch<'a>) {
+        walk_stitch(self, stitch)
+    }
+
+    fn visit_weave(&mut self, weave: &'s Weave<'a>) {
+        walk_weave(self, weave)
+    }
+
+    fn visit_line(&mut self, line: &'s Line<'a>) {
+        walk_line(self, line)
+    }
+
+    fn visit_choice(&mut self, choice: &'s Choice<'a>) {
+        walk_choice(self, choice)
+    }
+
+    fn visit_gather(&mut self, gather: &'s Gather<'a>) {
+        walk_gather(self, gather)
+    }
+
+    fn visit_conditional(&mut self, conditional: &'s Conditional<'a>) {
+        walk_conditional(self, conditional)
+    }
+
+    fn visit_conditional_branch(&mut self, branch: &'s ConditionalBranch<'a>) {
+        walk_conditional_branch(self, branch)
+    }
+
+    fn visit_sequence(&mut self, sequence: &'s Sequence<'a>) {
+        walk_sequence(self, sequence)
+    }
+
+    fn visit_annotation(&mut self, _annotation: &'s Annotation<'a>) {}
+
+    fn visit_error(&mut self, _error: &'s Token<'a, ParseError<'a>>) {}
+
+    fn visit_content(&mut self, content: &'s Content<'a>) {
+        walk_content(self, content)
+    }
+
+    fn visit_content_part(&mut self, part: &'s ContentPart<'a>) {
+        walk_content_part(self, part)
+    }
+
+    fn visit_text(&mut self, _text: &'s Span<'a>) {}
+
+    fn visit_divert(&mut self, _divert: &'s Token<'a, Divert<'a>>) {}
+
+    fn visit_statement(&mut self, statement: &'s Statement<'a>) {
+        walk_statement(self, statement)
+    }
+
+    fn visit_expression(&mut self, expression: &'s Expression<'a>) {
+        walk_expression(self, expression)
+    }
+
+    fn visit_nary_operation(&mut self, operation: &'s NAryOperation<'a>) {
+        walk_nary_operation(self, operation)
+    }
+
+    fn visit_unary_operator(&mut self, _operator: &'s Token<'a, UnaryOperator>) {}
+
+    fn visit_binary_operator(&mut self, _operator: &'s Token<'a, BinaryOperator>) {}
+
+    fn visit_literal(&mut self, _literal: &'s Literal<'a>) {}
+
+    fn visit_variable(&mut self, _variable: &'s Variable<'a>) {}
+}
+
+/// Visit the root weave, then the knots.
+pub fn walk_story<'s, 'a: 's, V: Visitor<'s, 'a> + ?Sized>(visitor: &mut V, story: &'s Story<'a>) {
+    visitor.visit_weave(&story.body);
+
+    for knot in &story.knots {
+        visitor.visit_knot(knot);
+    }
+}
+
+/// Visit the parameters, the weave before the first stitch, then the
+/// stitches.
+pub fn walk_knot<'s, 'a: 's, V: Visitor<'s, 'a> + ?Sized>(visitor: &mut V, knot: &'s Knot<'a>) {
+    for parameter in &knot.parameters {
+        visitor.visit_variable(parameter);
+    }
+
+    visitor.visit_weave(&knot.body);
+
+    for stitch in &knot.stitches {
+        visitor.visit_stitch(stitch);
+    }
+}
+
+pub fn walk_stitch<'s, 'a: 's, V: Visitor<'s, 'a> + ?Sized>(visitor: &mut V, stitch: &'s Stitch<'a>) {
+    visitor.visit_weave(&stitch.body);
+}
+
+pub fn walk_weave<'s, 'a: 's, V: Visitor<'s, 'a> + ?Sized>(visitor: &mut V, weave: &'s Weave<'a>) {
+    for line in &weave.lines {
+        visitor.visit_line(line);
+    }
+}
+
+pub fn walk_line<'s, 'a: 's, V: Visitor<'s, 'a> + ?Sized>(visitor: &mut V, line: &'s Line<'a>) {
+    match *line {
+        Line::Content(ref content)         => visitor.visit_content(content),
+        Line::Statement(ref statement)     => visitor.visit_statement(statement),
+        Line::Choice(ref choice)           => visitor.visit_choice(choice),
+        Line::Gather(ref gather)           => visitor.visit_gather(gather),
+        Line::Conditional(ref conditional) => visitor.visit_conditional(conditional),
+        Line::Sequence(ref sequence)       => visitor.visit_sequence(sequence),
+        Line::Annotation(ref annotation)   => visitor.visit_annotation(annotation),
+        Line::Error(ref error)             => visitor.visit_error(error)
+    }
+}
+
+/// Visit the contents of the choice, then its weave.
+pub fn walk_choice<'s, 'a: 's, V: Visitor<'s, 'a> + ?Sized>(visitor: &mut V, choice: &'s Choice<'a>) {
+    for content in [&choice.start, &choice.choice_only, &choice.output].iter().filter_map(|content| content.as_ref()) {
+        visitor.visit_content(content);
+    }
+
+    visitor.visit_weave(&choice.body);
+}
+
+pub fn walk_gather<'s, 'a: 's, V: Visitor<'s, 'a> + ?Sized>(visitor: &mut V, gather: &'s Gather<'a>) {
+    if let Some(ref content) = gather.content {
+        visitor.visit_content(content);
+    }
+}
+
+pub fn walk_conditional<'s, 'a: 's, V: Visitor<'s, 'a> + ?Sized>(visitor: &mut V, conditional: &'s Conditional<'a>) {
+    for branch in &conditional.branches {
+        visitor.visit_conditional_branch(branch);
+    }
+}
+
+/// Visit the condition, if any, then the weave of the branch.
+pub fn walk_conditional_branch<'s, 'a: 's, V: Visitor<'s, 'a> + ?Sized>(visitor: &mut V, branch: &'s ConditionalBranch<'a>) {
+    if let Some(ref condition) = branch.condition {
+        visitor.visit_expression(condition);
+    }
+
+    visitor.visit_weave(&branch.body);
+}
+
+pub fn walk_sequence<'s, 'a: 's, V: Visitor<'s, 'a> + ?Sized>(visitor: &mut V, sequence: &'s Sequence<'a>) {
+    for branch in &sequence.branches {
+        visitor.visit_weave(branch);
+    }
+}
+
+pub fn walk_content<'s, 'a: 's, V: Visitor<'s, 'a> + ?Sized>(visitor: &mut V, content: &'s Content<'a>) {
+    for part in &content.0 {
+        visitor.visit_content_part(part);
+    }
+}
+
+pub fn walk_content_part<'s, 'a: 's, V: Visitor<'s, 'a> + ?Sized>(visitor: &mut V, part: &'s ContentPart<'a>) {
+    match *part {
+        ContentPart::Text(ref text)           => visitor.visit_text(text),
+        ContentPart::Interpolation(ref token) => visitor.visit_expression(&token.value),
+        ContentPart::Divert(ref divert)       => visitor.visit_divert(divert)
+    }
+}
+
+/// Visit the variable of a declaration, then its value, the name of a list,
+/// or the value of a return statement.
+pub fn walk_statement<'s, 'a: 's, V: Visitor<'s, 'a> + ?Sized>(visitor: &mut V, statement: &'s Statement<'a>) {
+    match *statement {
+        Statement::Declaration { ref variable, ref value, .. } => {
+            visitor.visit_variable(variable);
+            visitor.visit_expression(value);
+        },
+
+        Statement::List { ref name, .. } => visitor.visit_variable(name),
+
+        Statement::Return { ref value, .. } => visitor.visit_expression(value)
+    }
+}
+
+pub fn walk_expression<'s, 'a: 's, V: Visitor<'s, 'a> + ?Sized>(visitor: &mut V, expression: &'s Expression<'a>) {
+    match *expression {
+        Expression::Literal(ref literal)         => visitor.visit_literal(literal),
+        Expression::NAryOperation(ref operation) => visitor.visit_nary_operation(operation),
+        Expression::Variable(ref variable)       => visitor.visit_variable(variable)
+    }
+}
+
+pub fn walk_nary_operation<'s, 'a: 's, V: Visitor<'s, 'a> + ?Sized>(visitor: &mut V, operation: &'s NAryOperation<'a>) {
+    match *operation {
+        NAryOperation::Nullary { operand: ref expression, .. } => visitor.visit_expression(expression),
+
+        NAryOperation::Unary { ref operator, ref operand, .. } => {
+            visitor.visit_unary_operator(operator);
+            visitor.visit_nary_operation(operand);
+        },
+
+        NAryOperation::Binary { ref operator, ref left_operand, ref right_operand, .. } => {
+            visitor.visit_nary_operation(left_operand);
+            visitor.visit_binary_operator(operator);
+            visitor.visit_nary_operation(right_operand);
+        }
+    }
+}
+
+/// A visitor of a mutable syntax tree, like `Visitor`.
+pub trait VisitorMut<'a> {
+    fn visit_story_mut(&mut self, story: &mut Story<'a>) {
+        walk_story_mut(self, story)
+    }
+
+    fn visit_knot_mut(&mut self, knot: &mut Knot<'a>) {
+        walk_knot_mut(self, knot)
+    }
+
+    fn visit_stitch_mut(&mut self, stitch: &mut Stitch<'a>) {
+        walk_stitch_mut(self, stitch)
+    }
+
+    fn visit_weave_mut(&mut self, weave: &mut Weave<'a>) {
+        walk_weave_mut(self, weave)
+    }
+
+    fn visit_line_mut(&mut self, line: &mut Line<'a>) {
+        walk_line_mut(self, line)
+    }
+
+    fn visit_choice_mut(&mut self, choice: &mut Choice<'a>) {
+        walk_choice_mut(self, choice)
+    }
+
+    fn visit_gather_mut(&mut self, gather: &mut Gather<'a>) {
+        walk_gather_mut(self, gather)
+    }
+
+    fn visit_conditional_mut(&mut self, conditional: &mut Conditional<'a>) {
+        walk_conditional_mut(self, conditional)
+    }
+
+    fn visit_conditional_branch_mut(&mut self, branch: &mut ConditionalBranch<'a>) {
+        walk_conditional_branch_mut(self, branch)
+    }
+
+    fn visit_sequence_mut(&mut self, sequence: &mut Sequence<'a>) {
+        walk_sequence_mut(self, sequence)
+    }
+
+    fn visit_annotation_mut(&mut self, _annotation: &mut Annotation<'a>) {}
+
+    fn visit_error_mut(&mut self, _error: &mut Token<'a, ParseError<'a>>) {}
+
+    fn visit_content_mut(&mut self, content: &mut Content<'a>) {
+        walk_content_mut(self, content)
+    }
+
+    fn visit_content_part_mut(&mut self, part: &mut ContentPart<'a>) {
+        walk_content_part_mut(self, part)
+    }
+
+    fn visit_text_mut(&mut self, _text: &mut Span<'a>) {}
+
+    fn visit_divert_mut(&mut self, _divert: &mut Token<'a, Divert<'a>>) {}
+
+    fn visit_statement_mut(&mut self, statement: &mut Statement<'a>) {
+        walk_statement_mut(self, statement)
+    }
+
+    fn visit_expression_mut(&mut self, expression: &mut Expression<'a>) {
+        walk_expression_mut(self, expression)
+    }
+
+    fn visit_nary_operation_mut(&mut self, operation: &mut NAryOperation<'a>) {
+        walk_nary_operation_mut(self, operation)
+    }
+
+    fn visit_unary_operator_mut(&mut self, _operator: &mut Token<'a, UnaryOperator>) {}
+
+    fn visit_binary_operator_mut(&mut self, _operator: &mut Token<'a, BinaryOperator>) {}
+
+    fn visit_literal_mut(&mut self, _literal: &mut Literal<'a>) {}
+
+    fn visit_variable_mut(&mut self, _variable: &mut Variable<'a>) {}
+}
+
+pub fn walk_story_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, story: &mut Story<'a>) {
+    visitor.visit_weave_mut(&mut story.body);
+
+    for knot in &mut story.knots {
+        visitor.visit_knot_mut(knot);
+    }
+}
+
+pub fn walk_knot_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, knot: &mut Knot<'a>) {
+    for parameter in &mut knot.parameters {
+        visitor.visit_variable_mut(parameter);
+    }
+
+    visitor.visit_weave_mut(&mut knot.body);
+
+    for stitch in &mut knot.stitches {
+        visitor.visit_stitch_mut(stitch);
+    }
+}
+
+pub fn walk_stitch_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, stitch: &mut Stitch<'a>) {
+    visitor.visit_weave_mut(&mut stitch.body);
+}
+
+pub fn walk_weave_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, weave: &mut Weave<'a>) {
+    for line in &mut weave.lines {
+        visitor.visit_line_mut(line);
+    }
+}
+
+pub fn walk_line_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, line: &mut Line<'a>) {
+    match *line {
+        Line::Content(ref mut content)         => visitor.visit_content_mut(content),
+        Line::Statement(ref mut statement)     => visitor.visit_statement_mut(statement),
+        Line::Choice(ref mut choice)           => visitor.visit_choice_mut(choice),
+        Line::Gather(ref mut gather)           => visitor.visit_gather_mut(gather),
+        Line::Conditional(ref mut conditional) => visitor.visit_conditional_mut(conditional),
+        Line::Sequence(ref mut sequence)       => visitor.visit_sequence_mut(sequence),
+        Line::Annotation(ref mut annotation)   => visitor.visit_annotation_mut(annotation),
+        Line::Error(ref mut error)             => visitor.visit_error_mut(error)
+    }
+}
+
+pub fn walk_choice_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, choice: &mut Choice<'a>) {
+    for content in [&mut choice.start, &mut choice.choice_only, &mut choice.output].iter_mut().filter_map(|content| content.as_mut()) {
+        visitor.visit_content_mut(content);
+    }
+
+    visitor.visit_weave_mut(&mut choice.body);
+}
+
+pub fn walk_gather_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, gather: &mut Gather<'a>) {
+    if let Some(ref mut content) = gather.content {
+        visitor.visit_content_mut(content);
+    }
+}
+
+pub fn walk_conditional_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, conditional: &mut Conditional<'a>) {
+    for branch in &mut conditional.branches {
+        visitor.visit_conditional_branch_mut(branch);
+    }
+}
+
+pub fn walk_conditional_branch_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, branch: &mut ConditionalBranch<'a>) {
+    if let Some(ref mut condition) = branch.condition {
+        visitor.visit_expression_mut(condition);
+    }
+
+    visitor.visit_weave_mut(&mut branch.body);
+}
+
+pub fn walk_sequence_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, sequence: &mut Sequence<'a>) {
+    for branch in &mut sequence.branches {
+        visitor.visit_weave_mut(branch);
+    }
+}
+
+pub fn walk_content_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, content: &mut Content<'a>) {
+    for part in &mut content.0 {
+        visitor.visit_content_part_mut(part);
+    }
+}
+
+pub fn walk_content_part_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, part: &mut ContentPart<'a>) {
+    match *part {
+        ContentPart::Text(ref mut text)           => visitor.visit_text_mut(text),
+        ContentPart::Interpolation(ref mut token) => visitor.visit_expression_mut(&mut token.value),
+        ContentPart::Divert(ref mut divert)       => visitor.visit_divert_mut(divert)
+    }
+}
+
+pub fn walk_statement_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, statement: &mut Statement<'a>) {
+    match *statement {
+        Statement::Declaration { ref mut variable, ref mut value, .. } => {
+            visitor.visit_variable_mut(variable);
+            visitor.visit_expression_mut(value);
+        },
+
+        Statement::List { ref mut name, .. } => visitor.visit_variable_mut(name),
+
+        Statement::Return { ref mut value, .. } => visitor.visit_expression_mut(value)
+    }
+}
+
+pub fn walk_expression_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, expression: &mut Expression<'a>) {
+    match *expression {
+        Expression::Literal(ref mut literal)         => visitor.visit_literal_mut(literal),
+        Expression::NAryOperation(ref mut operation) => visitor.visit_nary_operation_mut(operation),
+        Expression::Variable(ref mut variable)       => visitor.visit_variable_mut(variable)
+    }
+}
+
+pub fn walk_nary_operation_mut<'a, V: VisitorMut<'a> + ?Sized>(visitor: &mut V, operation: &mut NAryOperation<'a>) {
+    match *operation {
+        NAryOperation::Nullary { operand: ref mut expression, .. } => visitor.visit_expression_mut(expression),
+
+        NAryOperation::Unary { ref mut operator, ref mut operand, .. } => {
+            visitor.visit_unary_operator_mut(operator);
+            visitor.visit_nary_operation_mut(operand);
+        },
+
+        NAryOperation::Binary { ref mut operator, ref mut left_operand, ref mut right_operand, .. } => {
+            visitor.visit_nary_operation_mut(left_operand);
+            visitor.visit_binary_operator_mut(operator);
+            visitor.visit_nary_operation_mut(right_operand);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        walk_knot,
+        Visitor,
+        VisitorMut
+    };
+    use ast::ast::{
+        BinaryOperator,
+        Integer,
+        Knot,
+        Literal,
+        Token,
+        Variable
+    };
+    use options::ParserOptions;
+
+    /// Collect the variables, with the knot they are in.
+    struct Variables<'s, 'a: 's> {
+        knot     : Option<&'s Knot<'a>>,
+        variables: Vec<(Option<&'a str>, &'a str)>
+    }
+
+    impl<'s, 'a: 's> Visitor<'s, 'a> for Variables<'s, 'a> {
+        fn visit_knot(&mut self, knot: &'s Knot<'a>) {
+            self.knot = Some(knot);
+            walk_knot(self, knot);
+        }
+
+        fn visit_variable(&mut self, variable: &'s Variable<'a>) {
+            self.variables.push((self.knot.map(|knot| knot.name.as_slice()), variable.0.as_slice()));
+        }
+    }
+
+    #[test]
+    fn case_visitor() {
+        let story = ::parse(
+            "~ x = 1
+* {y} Hello
+- Bye {z}
+== london ==
+{stopping:
+  - {w}
+}
+== function f(a) ==
+~ return a + b
+",
+            ParserOptions::default()
+        ).unwrap();
+
+        let mut visitor = Variables { knot: None, variables: Vec::new() };
+        visitor.visit_story(&story);
+
+        assert_eq!(
+            visitor.variables,
+            vec![
+                (None, "x"),
+                (None, "y"),
+                (None, "z"),
+                (Some("london"), "w"),
+                (Some("f"), "a"),
+                (Some("f"), "a"),
+                (Some("f"), "b")
+            ]
+        );
+    }
+
+    /// Count the binary operators.
+    struct Operators(usize);
+
+    impl<'s, 'a: 's> Visitor<'s, 'a> for Operators {
+        fn visit_binary_operator(&mut self, _operator: &'s Token<'a, BinaryOperator>) {
+            self.0 += 1;
+        }
+    }
+
+    #[test]
+    fn case_visitor_operators() {
+        let story = ::parse("~ x = (1 + 2) * 3 - -4\n{x == 5}\n", ParserOptions::default()).unwrap();
+
+        let mut visitor = Operators(0);
+        visitor.visit_story(&story);
+
+        assert_eq!(visitor.0, 4);
+    }
+
+    /// Double the integers.
+    struct Double;
+
+    impl<'a> VisitorMut<'a> for Double {
+        fn visit_literal_mut(&mut self, literal: &mut Literal<'a>) {
+            if let Literal::Integer(ref mut token) = *literal {
+                token.value *= 2;
+            }
+        }
+    }
+
+    #[test]
+    fn case_visitor_mut() {
+        let mut story = ::parse("~ x = 1 + 2\n* {3} Hello\n", ParserOptions::default()).unwrap();
+        Double.visit_story_mut(&mut story);
+
+        let mut integers = Integers(Vec::new());
+        integers.visit_story(&story);
+
+        assert_eq!(integers.0, vec![2, 4, 6]);
+    }
+
+    /// Collect the integers.
+    struct Integers(Vec<Integer>);
+
+    impl<'s, 'a: 's> Visitor<'s, 'a> for Integers {
+        fn visit_literal(&mut self, literal: &'s Literal<'a>) {
+            if let Literal::Integer(ref token) = *literal {
+                self.0.push(token.value);
+            }
+        }
+    }
+}
@@ -0,0 +1,1224 @@
+//! A lossless concrete syntax tree.
+//!
+//! The parser skips the whitespaces, the line endings and the comments, so
+//! a `Story` cannot give the source back. A concrete syntax tree keeps every
+//! byte of the source in its tokens, the trivia included: the text of the
+//! tree is the source, and a tool can rewrite a part of the source without
+//! losing the comments of the writers.
+//!
+//! The tree is built from a source and from the story parsed from it. Its
+//! nodes follow the shape of the story, and each node refers to the node of
+//! the story it comes from, see `AstNode`.
+
+use std::ptr;
+
+use ast::ast::{
+    Annotation,
+    Comments,
+    ConditionalBranch,
+    ContentPart,
+    DeclarationKind,
+    Divert,
+    Expression,
+    Knot,
+    Line,
+    Literal,
+    NAryOperation,
+    SequenceKind,
+    Spanned,
+    Statement,
+    Stitch,
+    Story,
+    Weave
+};
+use rules::comments::{
+    comment,
+    todo
+};
+use rules::identifier::is_identifier;
+use span::{
+    SourceRange,
+    Span
+};
+use tokens;
+
+use nom::Slice;
+
+/// The kind of a token or of a node.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum SyntaxKind {
+    /// A run of spaces and tabs.
+    Whitespace,
+
+    /// A line ending (`\n` or `\r\n`).
+    Newline,
+
+    /// A comment, single line (`// …`) or delimited (`/* … */`). A single
+    /// line comment does not include its line ending.
+    Comment,
+
+    /// A run of text of a line of content.
+    Text,
+
+    /// The name of a knot, a stitch or a variable.
+    Identifier,
+
+    /// A keyword, e.g. `else`, `return` or `END`.
+    Keyword,
+
+    /// A number, a boolean or a string.
+    Literal,
+
+    /// The operator of an operation.
+    Operator,
+
+    /// A punctuation, e.g. `{`, `->` or `==`.
+    Punctuation,
+
+    /// A malformed line, see `Line::Error`.
+    Error,
+
+    /// A story, the root of the tree.
+    Story,
+
+    /// A knot, with its header, its lines and its stitches.
+    Knot,
+
+    /// A stitch, with its header and its lines.
+    Stitch,
+
+    /// A line of content.
+    Content,
+
+    /// A statement.
+    Statement,
+
+    /// A choice, with its body.
+    Choice,
+
+    /// A gather.
+    Gather,
+
+    /// A multiline conditional.
+    Conditional,
+
+    /// A branch of a multiline conditional.
+    ConditionalBranch,
+
+    /// A multiline sequence.
+    Sequence,
+
+    /// A branch of a multiline sequence.
+    SequenceBranch,
+
+    /// An annotation.
+    Annotation,
+
+    /// A malformed line.
+    ErrorLine,
+
+    /// An interpolation, with its curly brackets.
+    Interpolation,
+
+    /// A divert, with its arrow.
+    Divert,
+
+    /// A unary operation.
+    UnaryOperation,
+
+    /// A binary operation.
+    BinaryOperation
+}
+
+impl SyntaxKind {
+    /// Check whether the kind is a trivia, i.e. a token skipped by the
+    /// parser.
+    pub fn is_trivia(self) -> bool {
+        matches!(self, SyntaxKind::Whitespace | SyntaxKind::Newline | SyntaxKind::Comment)
+    }
+}
+
+/// The node of a story a syntax node comes from.
+#[derive(Debug, Copy, Clone)]
+pub enum AstNode<'s, 'a: 's> {
+    /// The story.
+    Story(&'s Story<'a>),
+
+    /// A knot.
+    Knot(&'s Knot<'a>),
+
+    /// A stitch.
+    Stitch(&'s Stitch<'a>),
+
+    /// A line of a weave.
+    Line(&'s Line<'a>),
+
+    /// A branch of a conditional.
+    ConditionalBranch(&'s ConditionalBranch<'a>),
+
+    /// A branch of a sequence.
+    SequenceBranch(&'s Weave<'a>),
+
+    /// An interpolation or a divert.
+    ContentPart(&'s ContentPart<'a>),
+
+    /// A unary or a binary operation.
+    Operation(&'s NAryOperation<'a>)
+}
+
+impl<'s, 'a: 's> AstNode<'s, 'a> {
+    /// Check whether two values refer to the same node of the story.
+    pub fn is(&self, other: &Self) -> bool {
+        match (*self, *other) {
+            (AstNode::Story(left), AstNode::Story(right))                         => ptr::eq(left, right),
+            (AstNode::Knot(left), AstNode::Knot(right))                           => ptr::eq(left, right),
+            (AstNode::Stitch(left), AstNode::Stitch(right))                       => ptr::eq(left, right),
+            (AstNode::Line(left), AstNode::Line(right))                           => ptr::eq(left, right),
+            (AstNode::ConditionalBranch(left), AstNode::ConditionalBranch(right)) => ptr::eq(left, right),
+            (AstNode::SequenceBranch(left), AstNode::SequenceBranch(right))       => ptr::eq(left, right),
+            (AstNode::ContentPart(left), AstNode::ContentPart(right))             => ptr::eq(left, right),
+            (AstNode::Operation(left), AstNode::Operation(right))                 => ptr::eq(left, right),
+            _                                                                     => false
+        }
+    }
+}
+
+/// A token, i.e. a leaf of the tree.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct SyntaxToken<'a> {
+    /// The kind of the token.
+    pub kind: SyntaxKind,
+
+    /// The source of the token.
+    pub span: Span<'a>
+}
+
+impl<'a> SyntaxToken<'a> {
+    /// The text of the token.
+    pub fn text(&self) -> &'a str {
+        self.span.as_slice()
+    }
+}
+
+impl<'a> Spanned for SyntaxToken<'a> {
+    fn range(&self) -> SourceRange {
+        self.span.range()
+    }
+}
+
+/// A child of a node.
+#[derive(Debug)]
+pub enum SyntaxElement<'s, 'a: 's> {
+    /// A node.
+    Node(SyntaxNode<'s, 'a>),
+
+    /// A token.
+    Token(SyntaxToken<'a>)
+}
+
+/// A node of the tree. Its span is the source of all its tokens.
+#[derive(Debug)]
+pub struct SyntaxNode<'s, 'a: 's> {
+    /// The kind of the node.
+    pub kind: SyntaxKind,
+
+    /// The source of the node.
+    pub span: Span<'a>,
+
+    /// The nodes and the tokens of the node, in the order of the source.
+    pub children: Vec<SyntaxElement<'s, 'a>>,
+
+    /// The node of the story the node comes from.
+    pub ast: AstNode<'s, 'a>
+}
+
+impl<'s, 'a: 's> SyntaxNode<'s, 'a> {
+    /// The text of the node, trivia included.
+    pub fn text(&self) -> &'a str {
+        self.span.as_slice()
+    }
+
+    /// The tokens of the node and of its descendants, in the order of the
+    /// source.
+    pub fn tokens(&self) -> Vec<&SyntaxToken<'a>> {
+        let mut tokens = Vec::new();
+
+        self.collect_tokens(&mut tokens);
+
+        tokens
+    }
+
+    fn collect_tokens<'t>(&'t self, tokens: &mut Vec<&'t SyntaxToken<'a>>) {
+        for child in &self.children {
+            match *child {
+                SyntaxElement::Node(ref node)   => node.collect_tokens(tokens),
+                SyntaxElement::Token(ref token) => tokens.push(token)
+            }
+        }
+    }
+
+    /// The node and its descendant nodes, in pre-order.
+    pub fn descendants(&self) -> Vec<&SyntaxNode<'s, 'a>> {
+        let mut nodes = vec![self];
+
+        for child in &self.children {
+            if let SyntaxElement::Node(ref node) = *child {
+                nodes.extend(node.descendants());
+            }
+        }
+
+        nodes
+    }
+
+    /// Find the node coming from a node of the story.
+    pub fn find(&self, ast: AstNode<'s, 'a>) -> Option<&SyntaxNode<'s, 'a>> {
+        self.descendants().into_iter().find(|node| node.ast.is(&ast))
+    }
+}
+
+impl<'s, 'a: 's> Spanned for SyntaxNode<'s, 'a> {
+    fn range(&self) -> SourceRange {
+        self.span.range()
+    }
+}
+
+/// Build the concrete syntax tree of a source, from the story parsed from
+/// it, e.g. `build(Span::new(input), &story)`.
+///
+/// The text of the tree is always the source: a part of the source that
+/// the story does not explain is kept as punctuation tokens.
+pub fn build<'s, 'a: 's>(source: Span<'a>, story: &'s Story<'a>) -> SyntaxNode<'s, 'a> {
+    let mut builder = Builder {
+        source,
+        position: 0,
+        stack   : Vec::new()
+    };
+
+    builder.story(story);
+
+    builder.stack.pop().expect("The story node is open.").node
+}
+
+/// The punctuations, the longest first.
+const PUNCTUATIONS: &[&str] = &[
+    tokens::TUNNEL_END,
+    tokens::KNOT,
+    tokens::DIVERT,
+    tokens::INLINE_COMMENT,
+    tokens::LEFT_CURLY_BRACKET,
+    tokens::RIGHT_CURLY_BRACKET,
+    tokens::LEFT_PARENTHESIS,
+    tokens::RIGHT_PARENTHESIS,
+    tokens::LEFT_SQUARE_BRACKET,
+    tokens::RIGHT_SQUARE_BRACKET,
+    tokens::COLON,
+    tokens::COMMA,
+    tokens::DOT
+];
+
+/// A node being built, with the number of parentheses it has opened.
+struct Frame<'s, 'a: 's> {
+    node       : SyntaxNode<'s, 'a>,
+    parentheses: usize
+}
+
+/// A builder walking the story in the order of the source, and cutting the
+/// source into tokens on the way.
+///
+/// The positions are byte offsets relative to the source.
+struct Builder<'s, 'a: 's> {
+    source  : Span<'a>,
+    position: usize,
+    stack   : Vec<Frame<'s, 'a>>
+}
+
+impl<'s, 'a: 's> Builder<'s, 'a> {
+    fn rest(&self) -> &'a str {
+        &self.source.as_slice()[self.position..]
+    }
+
+    fn relative(&self, span: &Span) -> usize {
+        span.offset - self.source.offset
+    }
+
+    fn open(&mut self, kind: SyntaxKind, ast: AstNode<'s, 'a>) {
+        self.stack.push(Frame {
+            node: SyntaxNode {
+                kind,
+                span    : self.source.slice(self.position..self.position),
+                children: Vec::new(),
+                ast
+            },
+            parentheses: 0
+        });
+    }
+
+    fn close(&mut self) {
+        let mut frame = self.stack.pop().expect("A node is open.");
+        let start     = self.relative(&frame.node.span);
+
+        frame.node.span = self.source.slice(start..self.position);
+
+        self.stack
+            .last_mut()
+            .expect("The story node is open.")
+            .node
+            .children
+            .push(SyntaxElement::Node(frame.node));
+    }
+
+    /// Close an operation, with the parentheses around it, e.g. `(1 + 2)`.
+    ///
+    /// The range of an operation does not cover its parentheses, so the
+    /// opening parenthesis is added to an enclosing node first. It is moved
+    /// into the operation when the closing parenthesis follows.
+    fn close_operation(&mut self) {
+        loop {
+            let parenthesized = self.rest().trim_start_matches([' ', '\t']).starts_with(tokens::RIGHT_PARENTHESIS);
+
+            if !parenthesized {
+                break;
+            }
+
+            if self.stack.last().is_some_and(|frame| frame.parentheses > 0) || self.adopt_parenthesis() {
+                self.trivia(false);
+                self.punctuation(tokens::RIGHT_PARENTHESIS);
+            } else {
+                break;
+            }
+        }
+
+        self.close();
+    }
+
+    /// Move the opening parenthesis preceding the current node, with the
+    /// trivia following it, into the current node.
+    ///
+    /// The parenthesis is the last token of the nearest enclosing node that
+    /// has children: the nodes in between start at the same position.
+    fn adopt_parenthesis(&mut self) -> bool {
+        let top       = self.stack.len() - 1;
+        let enclosing = match (0..top).rev().find(|&index| !self.stack[index].node.children.is_empty()) {
+            Some(index) => index,
+            None        => return false
+        };
+
+        let frame = &mut self.stack[enclosing];
+        let index = frame.node.children.iter().rposition(|child| {
+            match *child {
+                SyntaxElement::Token(ref token) => !token.kind.is_trivia(),
+                SyntaxElement::Node(_)          => true
+            }
+        });
+
+        let index = match index {
+            Some(index) if frame.parentheses > 0 && is_left_parenthesis(&frame.node.children[index]) => index,
+            _                                                                                         => return false
+        };
+
+        let moved: Vec<SyntaxElement> = frame.node.children.drain(index..).collect();
+        frame.parentheses -= 1;
+
+        let start = match moved[0] {
+            SyntaxElement::Token(ref token) => token.span,
+            SyntaxElement::Node(ref node)   => node.span
+        };
+        let start = self.source.slice(self.relative(&start)..self.relative(&start));
+
+        for frame in &mut self.stack[enclosing + 1..] {
+            frame.node.span = start;
+        }
+
+        let frame = &mut self.stack[top];
+
+        frame.node.children.splice(0..0, moved);
+        frame.parentheses += 1;
+
+        true
+    }
+
+    /// Add a token up to `end`. An empty token is not added.
+    fn token(&mut self, kind: SyntaxKind, end: usize) {
+        if end <= self.position {
+            return;
+        }
+
+        let token = SyntaxToken {
+            kind,
+            span: self.source.slice(self.position..end)
+        };
+
+        if kind == SyntaxKind::Punctuation {
+            let frame = self.stack.last_mut().expect("A node is open.");
+
+            match token.text() {
+                tokens::LEFT_PARENTHESIS  => frame.parentheses += 1,
+                tokens::RIGHT_PARENTHESIS => frame.parentheses = frame.parentheses.saturating_sub(1),
+                _                         => {}
+            }
+        }
+
+        self.stack.last_mut().expect("A node is open.").node.children.push(SyntaxElement::Token(token));
+        self.position = end;
+    }
+
+    /// Add a token for a span of the story, after the source before it.
+    fn leaf(&mut self, span: &Span, kind: SyntaxKind) {
+        let start = self.relative(span);
+
+        self.gap(start, true);
+        self.token(kind, start + span.as_slice().len());
+    }
+
+    /// Add a token if the source continues with `text`.
+    fn expect(&mut self, text: &str, kind: SyntaxKind) {
+        if self.rest().starts_with(text) {
+            let end = self.position + text.len();
+
+            self.token(kind, end);
+        }
+    }
+
+    fn punctuation(&mut self, text: &str) {
+        self.expect(text, SyntaxKind::Punctuation);
+    }
+
+    /// The length of the trivia starting the rest of the source, if any.
+    ///
+    /// The line endings are trivia only if `newlines` is set, and then a
+    /// `// TODO` comment is not a trivia since it starts an annotation.
+    fn trivia_token(&self, newlines: bool) -> Option<(SyntaxKind, usize)> {
+        let rest = self.rest();
+
+        let blanks = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+
+        if blanks > 0 {
+            return Some((SyntaxKind::Whitespace, blanks));
+        }
+
+        if newlines {
+            if rest.starts_with('\n') {
+                return Some((SyntaxKind::Newline, 1));
+            }
+
+            if rest.starts_with("\r\n") {
+                return Some((SyntaxKind::Newline, 2));
+            }
+        }
+
+        let input = self.source.slice(self.position..);
+
+        if newlines && todo(input).is_ok() {
+            return None;
+        }
+
+        comment(input).ok().map(|(next_input, _)| {
+            let comment = &rest[..next_input.offset - input.offset];
+
+            (SyntaxKind::Comment, comment.trim_end_matches(['\r', '\n']).len())
+        })
+    }
+
+    /// Add the trivia starting the rest of the source.
+    fn trivia(&mut self, newlines: bool) {
+        while let Some((kind, length)) = self.trivia_token(newlines) {
+            let end = self.position + length;
+
+            self.token(kind, end);
+        }
+    }
+
+    /// Add a token for a part of the source the story does not explain: a
+    /// trivia, a keyword, or a punctuation.
+    fn unknown_token(&mut self, end: usize, comments: bool) {
+        let rest = &self.source.as_slice()[self.position..end];
+
+        let (kind, length) = match self.trivia_token(true) {
+            Some((SyntaxKind::Comment, _)) if !comments => self.word_or_punctuation(rest),
+            Some(trivia)                                => trivia,
+            None                                        => self.word_or_punctuation(rest)
+        };
+
+        let end = end.min(self.position + length);
+
+        self.token(kind, end);
+    }
+
+    fn word_or_punctuation(&self, rest: &str) -> (SyntaxKind, usize) {
+        let word = rest.len() - rest.trim_start_matches(is_identifier).len();
+
+        if word > 0 {
+            let kind = if rest.starts_with(|item: char| item.is_ascii_digit()) { SyntaxKind::Literal } else { SyntaxKind::Keyword };
+
+            return (kind, word);
+        }
+
+        match PUNCTUATIONS.iter().find(|punctuation| rest.starts_with(*punctuation)) {
+            Some(punctuation) => (SyntaxKind::Punctuation, punctuation.len()),
+            None              => (SyntaxKind::Punctuation, rest.chars().next().map_or(0, char::len_utf8))
+        }
+    }
+
+    /// Add the tokens of the source up to `end`.
+    fn gap(&mut self, end: usize, comments: bool) {
+        while self.position < end {
+            self.unknown_token(end, comments);
+        }
+    }
+
+    /// Add the tokens up to the end of the line, its line ending included.
+    fn rest_of_line(&mut self) {
+        loop {
+            self.trivia(false);
+
+            let rest = self.rest();
+
+            if rest.is_empty() {
+                break;
+            }
+
+            if rest.starts_with(['\r', '\n']) {
+                let length = if rest.starts_with("\r\n") { 2 } else { 1 };
+                let end    = self.position + length;
+
+                self.token(SyntaxKind::Newline, end);
+
+                break;
+            }
+
+            let end = self.position + rest.find(['\r', '\n']).unwrap_or(rest.len());
+
+            self.unknown_token(end, true);
+        }
+    }
+
+    fn story(&mut self, story: &'s Story<'a>) {
+        self.open(SyntaxKind::Story, AstNode::Story(story));
+        self.weave(&story.body);
+
+        for knot in &story.knots {
+            self.knot(knot);
+        }
+
+        let end = self.source.as_slice().len();
+
+        self.gap(end, true);
+
+        let frame = self.stack.last_mut().expect("The story node is open.");
+
+        frame.node.span = self.source;
+    }
+
+    /// Add the trivia before a knot or a stitch. The comments documenting
+    /// it belong to its node, the other trivia to the enclosing node.
+    fn leading_trivia(&mut self, comments: &Comments<'a>) {
+        match comments.leading.first() {
+            Some(comment) => {
+                let start = self.relative(comment) - tokens::INLINE_COMMENT.len();
+
+                self.gap(start, true);
+            },
+
+            None => self.trivia(true)
+        }
+    }
+
+    fn knot(&mut self, knot: &'s Knot<'a>) {
+        self.leading_trivia(&knot.comments);
+        self.open(SyntaxKind::Knot, AstNode::Knot(knot));
+        self.trivia(true);
+        self.leaf(&knot.name, SyntaxKind::Identifier);
+
+        for parameter in &knot.parameters {
+            self.leaf(&parameter.0, SyntaxKind::Identifier);
+        }
+
+        self.rest_of_line();
+        self.weave(&knot.body);
+
+        for stitch in &knot.stitches {
+            self.stitch(stitch);
+        }
+
+        self.close();
+    }
+
+    fn stitch(&mut self, stitch: &'s Stitch<'a>) {
+        self.leading_trivia(&stitch.comments);
+        self.open(SyntaxKind::Stitch, AstNode::Stitch(stitch));
+        self.trivia(true);
+        self.leaf(&stitch.name, SyntaxKind::Identifier);
+        self.rest_of_line();
+        self.weave(&stitch.body);
+        self.close();
+    }
+
+    fn weave(&mut self, weave: &'s Weave<'a>) {
+        for line in &weave.lines {
+            self.line(line);
+        }
+    }
+
+    fn line(&mut self, line: &'s Line<'a>) {
+        self.trivia(true);
+
+        let ast = AstNode::Line(line);
+
+        match *line {
+            Line::Content(ref content) => {
+                self.open(SyntaxKind::Content, ast);
+                self.content(&content.0);
+                self.rest_of_line();
+            },
+
+            Line::Statement(ref statement) => {
+                self.open(SyntaxKind::Statement, ast);
+
+                match *statement {
+                    Statement::Declaration { kind: DeclarationKind::Statement, .. }
+                    | Statement::Return { .. }          => self.punctuation(tokens::STATEMENT),
+                    Statement::Declaration { kind, .. } => self.expect(declaration_keyword(kind), SyntaxKind::Keyword),
+                    Statement::List { .. }              => self.expect(tokens::LIST, SyntaxKind::Keyword)
+                }
+
+                match *statement {
+                    Statement::Declaration { ref variable, ref value, .. } => {
+                        self.leaf(&variable.0, SyntaxKind::Identifier);
+                        self.expression(value);
+                    },
+
+                    Statement::List { ref name, ref items, .. } => {
+                        self.leaf(&name.0, SyntaxKind::Identifier);
+
+                        for item in items {
+                            self.leaf(&item.name, SyntaxKind::Identifier);
+
+                            if let Some(ref value) = item.value {
+                                self.leaf(&value.span, SyntaxKind::Literal);
+                            }
+                        }
+                    },
+
+                    Statement::Return { ref value, .. } => {
+                        self.trivia(false);
+                        self.expect(tokens::RETURN, SyntaxKind::Keyword);
+                        self.expression(value);
+                    }
+                }
+
+                self.rest_of_line();
+            },
+
+            Line::Choice(ref choice) => {
+                self.open(SyntaxKind::Choice, ast);
+                self.bullets(choice.depth, &[tokens::CHOICE, tokens::STICKY_CHOICE]);
+
+                if let Some(ref label) = choice.label {
+                    self.leaf(label, SyntaxKind::Identifier);
+                }
+
+                for content in [&choice.start, &choice.choice_only, &choice.output].iter().filter_map(|content| content.as_ref()) {
+                    self.content(&content.0);
+                }
+
+                self.rest_of_line();
+                self.weave(&choice.body);
+            },
+
+            Line::Gather(ref gather) => {
+                self.open(SyntaxKind::Gather, ast);
+                self.bullets(gather.depth, &[tokens::GATHER]);
+
+                if let Some(ref label) = gather.label {
+                    self.leaf(label, SyntaxKind::Identifier);
+                }
+
+                if let Some(ref content) = gather.content {
+                    self.content(&content.0);
+                }
+
+                self.rest_of_line();
+            },
+
+            Line::Conditional(ref conditional) => {
+                self.open(SyntaxKind::Conditional, ast);
+                self.punctuation(tokens::LEFT_CURLY_BRACKET);
+                self.trivia(false);
+
+                // The first branch is either on the opening line, or marked
+                // by a dash like the next ones.
+                let inline = !self.rest().starts_with(['\r', '\n']) && !self.rest().is_empty();
+
+                for (index, branch) in conditional.branches.iter().enumerate() {
+                    self.conditional_branch(branch, index == 0 && inline);
+                }
+
+                self.block_end();
+            },
+
+            Line::Sequence(ref sequence) => {
+                self.open(SyntaxKind::Sequence, ast);
+                self.punctuation(tokens::LEFT_CURLY_BRACKET);
+                self.trivia(false);
+                self.expect(sequence_kind_token(sequence.kind), SyntaxKind::Keyword);
+                self.rest_of_line();
+
+                for branch in &sequence.branches {
+                    self.trivia(true);
+                    self.open(SyntaxKind::SequenceBranch, AstNode::SequenceBranch(branch));
+                    self.punctuation(tokens::GATHER);
+                    self.weave(branch);
+                    self.close();
+                }
+
+                self.block_end();
+            },
+
+            Line::Annotation(ref annotation) => {
+                self.open(SyntaxKind::Annotation, ast);
+
+                let message = match *annotation {
+                    Annotation::Todo(span) => span.byte_range()
+                };
+                let start   = message.start - self.source.offset;
+
+                self.gap(start, false);
+                self.token(SyntaxKind::Text, message.end - self.source.offset);
+                self.rest_of_line();
+            },
+
+            Line::Error(ref error) => {
+                self.open(SyntaxKind::ErrorLine, ast);
+                self.leaf(&error.span, SyntaxKind::Error);
+                self.rest_of_line();
+            }
+        }
+
+        self.close();
+    }
+
+    fn bullets(&mut self, depth: usize, bullets: &[&str]) {
+        for _ in 0..depth {
+            self.trivia(false);
+
+            if let Some(bullet) = bullets.iter().find(|bullet| self.rest().starts_with(**bullet)) {
+                self.punctuation(bullet);
+            }
+        }
+    }
+
+    fn conditional_branch(&mut self, branch: &'s ConditionalBranch<'a>, inline: bool) {
+        if !inline {
+            self.trivia(true);
+        }
+
+        self.open(SyntaxKind::ConditionalBranch, AstNode::ConditionalBranch(branch));
+
+        if !inline {
+            self.punctuation(tokens::GATHER);
+        }
+
+        match branch.condition {
+            Some(ref condition) => self.expression(condition),
+
+            None => {
+                self.trivia(false);
+                self.expect(tokens::ELSE, SyntaxKind::Keyword);
+            }
+        }
+
+        self.trivia(false);
+        self.punctuation(tokens::COLON);
+        self.weave(&branch.body);
+        self.close();
+    }
+
+    /// Add the end of a block, i.e. the closing curly bracket and the end
+    /// of its line, and close the block.
+    fn block_end(&mut self) {
+        self.trivia(true);
+        self.punctuation(tokens::RIGHT_CURLY_BRACKET);
+        self.rest_of_line();
+    }
+
+    fn content(&mut self, parts: &'s [ContentPart<'a>]) {
+        for part in parts {
+            match *part {
+                ContentPart::Text(ref span) => self.leaf(span, SyntaxKind::Text),
+
+                ContentPart::Interpolation(ref token) => {
+                    let start = self.relative(&token.span);
+
+                    self.gap(start, true);
+                    self.open(SyntaxKind::Interpolation, AstNode::ContentPart(part));
+                    self.punctuation(tokens::LEFT_CURLY_BRACKET);
+                    self.expression(&token.value);
+                    self.trivia(false);
+                    self.punctuation(tokens::RIGHT_CURLY_BRACKET);
+                    self.close();
+                },
+
+                ContentPart::Divert(ref token) => {
+                    let start = self.relative(&token.span);
+
+                    self.gap(start, true);
+                    self.open(SyntaxKind::Divert, AstNode::ContentPart(part));
+                    self.punctuation(tokens::DIVERT);
+
+                    match token.value {
+                        Divert::Target(ref path) => {
+                            for name in path {
+                                self.leaf(name, SyntaxKind::Identifier);
+                            }
+                        },
+
+                        Divert::End(ref span) | Divert::Done(ref span) => self.leaf(span, SyntaxKind::Keyword)
+                    }
+
+                    self.close();
+                }
+            }
+        }
+    }
+
+    fn expression(&mut self, expression: &'s Expression<'a>) {
+        match *expression {
+            Expression::Literal(ref literal) => {
+                let span = match *literal {
+                    Literal::Integer(ref token) => token.span,
+                    Literal::Real(ref token)    => token.span,
+                    Literal::Boolean(ref token) => token.span,
+                    Literal::String(ref token)  => token.span
+                };
+
+                self.leaf(&span, SyntaxKind::Literal);
+            },
+
+            Expression::NAryOperation(ref operation) => self.operation(operation),
+            Expression::Variable(ref variable)       => self.leaf(&variable.0, SyntaxKind::Identifier)
+        }
+    }
+
+    fn operation(&mut self, operation: &'s NAryOperation<'a>) {
+        let start = start_offset(operation) - self.source.offset;
+
+        match *operation {
+            NAryOperation::Nullary { operand: ref expression, .. } => self.expression(expression),
+
+            NAryOperation::Unary { ref operator, ref operand, .. } => {
+                self.gap(start, true);
+                self.open(SyntaxKind::UnaryOperation, AstNode::Operation(operation));
+
+                // A postfix operator follows its operand, e.g. `x++`.
+                if operator.span.offset < start_offset(operand) {
+                    self.leaf(&operator.span, SyntaxKind::Operator);
+                    self.operation(operand);
+                } else {
+                    self.operation(operand);
+                    self.leaf(&operator.span, SyntaxKind::Operator);
+                }
+
+                self.close_operation();
+            },
+
+            NAryOperation::Binary { ref operator, ref left_operand, ref right_operand, .. } => {
+                self.gap(start, true);
+                self.open(SyntaxKind::BinaryOperation, AstNode::Operation(operation));
+                self.operation(left_operand);
+                self.leaf(&operator.span, SyntaxKind::Operator);
+                self.operation(right_operand);
+                self.close_operation();
+            }
+        }
+    }
+}
+
+/// The byte offset of the first token of an operation. Unlike the span of
+/// the operation, it excludes the opening parentheses, which the node adopts
+/// when it is closed.
+fn start_offset(operation: &NAryOperation) -> usize {
+    match *operation {
+        NAryOperation::Nullary { operand: ref expression, .. } => {
+            match **expression {
+                Expression::Literal(Literal::Integer(ref token)) => token.span.offset,
+                Expression::Literal(Literal::Real(ref token))    => token.span.offset,
+                Expression::Literal(Literal::Boolean(ref token)) => token.span.offset,
+                Expression::Literal(Literal::String(ref token))  => token.span.offset,
+                Expression::NAryOperation(ref operation)         => start_offset(operation),
+                Expression::Variable(ref variable)               => variable.0.offset
+            }
+        },
+
+        NAryOperation::Unary { ref operator, ref operand, .. } => operator.span.offset.min(start_offset(operand)),
+        NAryOperation::Binary { ref left_operand, .. }         => start_offset(left_operand)
+    }
+}
+
+fn is_left_parenthesis(element: &SyntaxElement) -> bool {
+    match *element {
+        SyntaxElement::Token(ref token) => token.kind == SyntaxKind::Punctuation && token.text() == tokens::LEFT_PARENTHESIS,
+        SyntaxElement::Node(_)          => false
+    }
+}
+
+/// The tilde or the keyword starting a declaration.
+fn declaration_keyword(kind: DeclarationKind) -> &'static str {
+    match kind {
+        DeclarationKind::Statement => tokens::STATEMENT,
+        DeclarationKind::Global    => tokens::GLOBAL_VARIABLE,
+        DeclarationKind::Constant  => tokens::CONSTANT
+    }
+}
+
+fn sequence_kind_token(kind: SequenceKind) -> &'static str {
+    match kind {
+        SequenceKind::Stopping => tokens::STOPPING,
+        SequenceKind::Cycle    => tokens::CYCLE,
+        SequenceKind::Once     => tokens::ONCE,
+        SequenceKind::Shuffle  => tokens::SHUFFLE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        build,
+        AstNode,
+        SyntaxElement,
+        SyntaxKind,
+        SyntaxNode
+    };
+    use ast::ast::{
+        Line,
+        Statement,
+        Story
+    };
+    use options::ParserOptions;
+    use span::Span;
+
+    const SOURCE: &str = "\
+// The story starts here.
+Hello {name}!   // A greeting.
+
+~ x = -(1 + 2) * (3)  /* three */
+TODO: describe the room
+* Hello [back] right back -> london
+  * *[Stay]
+    Nice.
+  - - Done
+-   Bye
+{ x > 0:
+  Positive
+- else:
+  Negative
+}
+{ stopping:
+- A
+-
+  B
+}
+
+// The capital.
+== london(y) ==   // trailing
+= first_class
+-> END
+
+== function lerp(a, b, k) ==
+~ return (b - a) * k + a
+";
+
+    fn tokens_text(node: &SyntaxNode) -> String {
+        node.tokens().iter().map(|token| token.text()).collect()
+    }
+
+    fn story(input: &str) -> Story<'_> {
+        ::parse(input, ParserOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn case_cst_is_lossless() {
+        let story = story(SOURCE);
+        let tree  = build(Span::new(SOURCE), &story);
+
+        assert_eq!(tree.text(), SOURCE);
+        assert_eq!(tokens_text(&tree), SOURCE);
+
+        for node in tree.descendants() {
+            assert_eq!(tokens_text(node), node.text());
+        }
+    }
+
+    #[test]
+    fn case_cst_is_lossless_with_errors() {
+        let options = ParserOptions {
+            recover: true,
+            ..ParserOptions::default()
+        };
+        let source  = "Hello } world\r\n/* a\nblock */ ~ x = 1\n== london ==\n=== broken(\nText\n";
+        let story   = ::parse(source, options).unwrap();
+        let tree    = build(Span::new(source), &story);
+
+        assert_eq!(tokens_text(&tree), source);
+        assert!(tree.descendants().iter().any(|node| node.kind == SyntaxKind::ErrorLine));
+    }
+
+    #[test]
+    fn case_cst_trivia() {
+        let source = "~ x = 1 /* one */\n";
+        let story  = story(source);
+        let tree   = build(Span::new(source), &story);
+        let tokens: Vec<(SyntaxKind, &str)> = tree.tokens().iter().map(|token| (token.kind, token.text())).collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                (SyntaxKind::Punctuation, "~"),
+                (SyntaxKind::Whitespace, " "),
+                (SyntaxKind::Identifier, "x"),
+                (SyntaxKind::Whitespace, " "),
+                (SyntaxKind::Punctuation, "="),
+                (SyntaxKind::Whitespace, " "),
+                (SyntaxKind::Literal, "1"),
+                (SyntaxKind::Whitespace, " "),
+                (SyntaxKind::Comment, "/* one */"),
+                (SyntaxKind::Newline, "\n")
+            ]
+        );
+    }
+
+    #[test]
+    fn case_cst_label() {
+        let source = "- ( end ) Bye\n";
+        let story  = story(source);
+        let tree   = build(Span::new(source), &story);
+        let tokens: Vec<(SyntaxKind, &str)> = tree.tokens().iter().map(|token| (token.kind, token.text())).collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                (SyntaxKind::Punctuation, "-"),
+                (SyntaxKind::Whitespace, " "),
+                (SyntaxKind::Punctuation, "("),
+                (SyntaxKind::Whitespace, " "),
+                (SyntaxKind::Identifier, "end"),
+                (SyntaxKind::Whitespace, " "),
+                (SyntaxKind::Punctuation, ")"),
+                (SyntaxKind::Whitespace, " "),
+                (SyntaxKind::Text, "Bye"),
+                (SyntaxKind::Newline, "\n")
+            ]
+        );
+    }
+
+    #[test]
+    fn case_cst_list() {
+        let source = "LIST l = a, (b = 2)\n";
+        let story  = story(source);
+        let tree   = build(Span::new(source), &story);
+        let tokens: Vec<(SyntaxKind, &str)> = tree.tokens().iter().map(|token| (token.kind, token.text())).collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                (SyntaxKind::Keyword, "LIST"),
+                (SyntaxKind::Whitespace, " "),
+                (SyntaxKind::Identifier, "l"),
+                (SyntaxKind::Whitespace, " "),
+                (SyntaxKind::Punctuation, "="),
+                (SyntaxKind::Whitespace, " "),
+                (SyntaxKind::Identifier, "a"),
+                (SyntaxKind::Punctuation, ","),
+                (SyntaxKind::Whitespace, " "),
+                (SyntaxKind::Punctuation, "("),
+                (SyntaxKind::Identifier, "b"),
+                (SyntaxKind::Whitespace, " "),
+                (SyntaxKind::Punctuation, "="),
+                (SyntaxKind::Whitespace, " "),
+                (SyntaxKind::Literal, "2"),
+                (SyntaxKind::Punctuation, ")"),
+                (SyntaxKind::Newline, "\n")
+            ]
+        );
+    }
+
+    #[test]
+    fn case_cst_global_variable() {
+        let source = "// Score.\nVAR score = 0 // points\n";
+        let story  = story(source);
+        let tree   = build(Span::new(source), &story);
+        let tokens: Vec<(SyntaxKind, &str)> = tree.tokens().iter().map(|token| (token.kind, token.text())).collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                (SyntaxKind::Comment, "// Score."),
+                (SyntaxKind::Newline, "\n"),
+                (SyntaxKind::Keyword, "VAR"),
+                (SyntaxKind::Whitespace, " "),
+                (SyntaxKind::Identifier, "score"),
+                (SyntaxKind::Whitespace, " "),
+                (SyntaxKind::Punctuation, "="),
+                (SyntaxKind::Whitespace, " "),
+                (SyntaxKind::Literal, "0"),
+                (SyntaxKind::Whitespace, " "),
+                (SyntaxKind::Comment, "// points"),
+                (SyntaxKind::Newline, "\n")
+            ]
+        );
+    }
+
+    #[test]
+    fn case_cst_maps_to_ast() {
+        let story = story(SOURCE);
+        let tree  = build(Span::new(SOURCE), &story);
+
+        let knot = tree.find(AstNode::Knot(&story.knots[0])).unwrap();
+
+        assert_eq!(knot.kind, SyntaxKind::Knot);
+        assert!(knot.text().starts_with("// The capital.\n== london(y) ==   // trailing\n= first_class\n"));
+        assert_eq!(story.knots[0].comments.leading[0].as_slice(), " The capital.");
+        assert_eq!(story.knots[0].comments.trailing[0].as_slice(), " trailing");
+
+        let value = match story.body.lines[1] {
+            Line::Statement(Statement::Declaration { ref value, .. }) => value,
+            _                                                         => panic!("expected a declaration")
+        };
+
+        let operation = match *value {
+            ::ast::ast::Expression::NAryOperation(ref operation) => operation,
+            _                                                    => panic!("expected an operation")
+        };
+
+        let node = tree.find(AstNode::Operation(operation)).unwrap();
+
+        assert_eq!(node.kind, SyntaxKind::BinaryOperation);
+        assert_eq!(node.text(), "-(1 + 2) * (3)");
+
+        match node.children[0] {
+            SyntaxElement::Node(ref unary) => {
+                assert_eq!(unary.kind, SyntaxKind::UnaryOperation);
+                assert_eq!(unary.text(), "-(1 + 2)");
+            },
+
+            _ => panic!("expected a unary operation")
+        }
+
+        let returned = match story.knots[1].body.lines[0] {
+            Line::Statement(Statement::Return { value: ::ast::ast::Expression::NAryOperation(ref operation), .. }) => operation,
+            _                                                                                                    => panic!("expected a return statement")
+        };
+
+        let node = tree.find(AstNode::Operation(returned)).unwrap();
+
+        assert_eq!(node.text(), "(b - a) * k + a");
+
+        // The parentheses belong to the operation they surround.
+        let texts: Vec<&str> = node.descendants().iter().map(|node| node.text()).collect();
+
+        assert_eq!(texts, vec!["(b - a) * k + a", "(b - a) * k", "(b - a)"]);
+
+        let comments: Vec<&str> = tree.tokens().iter().filter(|token| token.kind == SyntaxKind::Comment).map(|token| token.text()).collect();
+
+        assert_eq!(comments, vec!["// The story starts here.", "// A greeting.", "/* three */", "// The capital.", "// trailing"]);
+    }
+}
@@ -0,0 +1,354 @@
+use std::collections::HashSet;
+
+use super::{
+    Diagnostic,
+    Severity
+};
+use tokens;
+
+/// A stable diagnostic code, e.g. `INK0001`.
+///
+/// Codes are never reused nor renumbered: tooling and users can rely on
+/// them to look up an explanation or to suppress a diagnostic.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Code {
+    /// The identifier, e.g. `INK0001`.
+    pub id: &'static str,
+
+    /// The severity of the diagnostics having this code.
+    pub severity: Severity,
+
+    /// A one line summary.
+    pub summary: &'static str,
+
+    /// A long-form explanation, with an example.
+    pub explanation: &'static str
+}
+
+/// The syntax of the story is invalid.
+pub const UNEXPECTED_INPUT: Code = Code {
+    id         : "INK0001",
+    severity   : Severity::Error,
+    summary    : "unexpected input",
+    explanation: "\
+The parser found an input it did not expect, e.g. an unbalanced brace or an
+identifier starting with a digit:
+
+    Hello }
+
+The message lists what was expected instead. Check the syntax of the line
+around the reported position."
+};
+
+/// A string is not valid UTF-8.
+pub const INVALID_STRING_ENCODING: Code = Code {
+    id         : "INK0002",
+    severity   : Severity::Error,
+    summary    : "invalid string encoding",
+    explanation: "\
+A string literal is not correctly encoded. Stories must be encoded in UTF-8;
+convert the file to UTF-8 with your editor."
+};
+
+/// An integer literal does not fit in the integer type.
+pub const INTEGER_OVERFLOW: Code = Code {
+    id         : "INK0003",
+    severity   : Severity::Error,
+    summary    : "integer literal is too large",
+    explanation: "\
+An integer literal does not fit in the integer type, e.g.:
+
+    ~ x = 9223372036854775808
+
+Integers are 64 bits wide, or 32 bits wide with the `ink-numbers` feature,
+like the reference implementation of Ink. Use a smaller value."
+};
+
+/// A syntax of the extended dialect is used in the strict dialect.
+pub const EXTENDED_SYNTAX: Code = Code {
+    id         : "INK0004",
+    severity   : Severity::Error,
+    summary    : "syntax only supported by the extended dialect",
+    explanation: "\
+The story is parsed with the strict dialect, which only accepts the syntax of
+Ink, but uses a syntax of the extended dialect, e.g. a hexadecimal literal or
+a bitwise operator:
+
+    ~ x = 0x2a | y
+
+Rewrite the expression with the syntax of Ink, e.g. `~ x = 42`, or parse the
+story with `Dialect::Extended`."
+};
+
+/// The flow of a knot or of a stitch runs out of content without a divert.
+pub const LOOSE_END: Code = Code {
+    id         : "INK0005",
+    severity   : Severity::Warning,
+    summary    : "loose end",
+    explanation: "\
+A branch of a knot or of a stitch ends without a divert, so the flow runs out
+of content, e.g.:
+
+    == london ==
+    * Stay
+      We stayed.
+    * Leave
+      -> paris
+
+The first choice is a loose end. End the branch with a divert, e.g.
+`-> DONE`, or gather the choices with a `-` line ending with a divert."
+};
+
+/// The flow of the root content runs out without `-> END` or `-> DONE`.
+pub const MISSING_END: Code = Code {
+    id         : "INK0006",
+    severity   : Severity::Warning,
+    summary    : "missing `-> END` or `-> DONE`",
+    explanation: "\
+A branch of the root content, i.e. the lines before the first knot, ends
+without a divert, so the story may run out of content, e.g.:
+
+    Hello
+    == london ==
+
+End the branch with `-> END`, `-> DONE`, or a divert to a knot, e.g.
+`-> london`."
+};
+
+/// A divert target is not a knot, a stitch nor a label in scope.
+pub const UNKNOWN_DIVERT_TARGET: Code = Code {
+    id         : "INK0007",
+    severity   : Severity::Error,
+    summary    : "unknown divert target",
+    explanation: "\
+A divert targets a knot, a stitch or a label that does not exist, e.g.:
+
+    -> lodnon
+    == london ==
+
+A single name is a knot, a stitch of the current knot, or a label of a choice
+or of a gather of the current knot or stitch, e.g. `-> top` for `- (top)`. A
+stitch or a label of another knot is prefixed by the name of its knot, e.g.
+`-> london.first_class` or `-> london.top`, and a label of a stitch by the
+name of its stitch, e.g. `-> london.first_class.top`. The closest name in
+scope is suggested; check the spelling of the target."
+};
+
+/// A variable is never declared.
+pub const UNKNOWN_VARIABLE: Code = Code {
+    id         : "INK0008",
+    severity   : Severity::Warning,
+    summary    : "unknown variable",
+    explanation: "\
+A variable is read but never declared, e.g.:
+
+    ~ health = 10
+    You have {helth} points.
+
+A variable is declared by an assignment, by `VAR` or by `CONST` anywhere in
+the story, or is a parameter of the current knot. A list declared by `LIST`,
+and each of its items, is a variable too. The name of a knot, of a stitch of
+the current knot, or of a label of the current knot or stitch, is its read
+count. The closest name in scope is suggested; check the spelling of the
+variable.
+
+Names defined outside of the story, e.g. external functions, are not known,
+hence a warning rather than an error."
+};
+
+/// A real literal is too large for the real type.
+pub const REAL_OVERFLOW: Code = Code {
+    id         : "INK0009",
+    severity   : Severity::Error,
+    summary    : "real literal is too large",
+    explanation: "\
+A real literal is too large for the real type, where it would be infinite,
+e.g. with the `ink-numbers` feature:
+
+    ~ x = 1e39
+
+Reals are double-precision floats, or single-precision floats with the
+`ink-numbers` feature, like the reference implementation of Ink. Use a smaller
+value."
+};
+
+/// All the codes, ordered by identifier.
+pub const REGISTRY: &[Code] = &[
+    UNEXPECTED_INPUT,
+    INVALID_STRING_ENCODING,
+    INTEGER_OVERFLOW,
+    EXTENDED_SYNTAX,
+    LOOSE_END,
+    MISSING_END,
+    UNKNOWN_DIVERT_TARGET,
+    UNKNOWN_VARIABLE,
+    REAL_OVERFLOW
+];
+
+/// Find a code by its identifier, e.g. `INK0001`.
+pub fn lookup(id: &str) -> Option<&'static Code> {
+    REGISTRY.iter().find(|code| code.id == id)
+}
+
+/// Get the long-form explanation of a code, e.g. `INK0001`.
+pub fn explain(id: &str) -> Option<&'static str> {
+    lookup(id).map(|code| code.explanation)
+}
+
+/// A set of suppressed codes.
+///
+/// Only warnings can be suppressed: an error always prevents the story from
+/// being compiled.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Suppressions(HashSet<String>);
+
+impl Suppressions {
+    /// Create an empty set of suppressed codes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read the suppressed codes of a configuration, separated by commas
+    /// or whitespaces. A `#` starts a comment up to the end of the line,
+    /// e.g.:
+    ///
+    /// ```text
+    /// # Loose ends are fine in drafts.
+    /// INK0005, INK0006
+    /// ```
+    pub fn from_config(config: &str) -> Self {
+        let mut suppressions = Self::new();
+
+        for line in config.lines() {
+            let line = line.split('#').next().unwrap_or("");
+
+            suppressions.allow_all(line);
+        }
+
+        suppressions
+    }
+
+    /// Read the suppressed codes of a story, declared by `// ink-allow:`
+    /// comments anywhere in the file, e.g. `// ink-allow: INK0005`.
+    pub fn from_source(source: &str) -> Self {
+        let mut suppressions = Self::new();
+
+        for line in source.lines() {
+            let codes = line
+                .trim_start()
+                .strip_prefix(tokens::INLINE_COMMENT)
+                .map(str::trim_start)
+                .and_then(|comment| comment.strip_prefix(tokens::ALLOW))
+                .and_then(|comment| comment.strip_prefix(tokens::COLON));
+
+            if let Some(codes) = codes {
+                suppressions.allow_all(codes);
+            }
+        }
+
+        suppressions
+    }
+
+    /// Suppress a code.
+    pub fn allow(&mut self, id: &str) {
+        self.0.insert(id.to_string());
+    }
+
+    fn allow_all(&mut self, ids: &str) {
+        for id in ids.split(|item: char| item == ',' || item.is_whitespace()).filter(|id| !id.is_empty()) {
+            self.allow(id);
+        }
+    }
+
+    /// Suppress the codes of another set too.
+    pub fn extend(&mut self, other: Suppressions) {
+        self.0.extend(other.0);
+    }
+
+    /// Check whether a diagnostic is suppressed.
+    pub fn is_suppressed(&self, diagnostic: &Diagnostic) -> bool {
+        match (diagnostic.severity, diagnostic.code) {
+            (Severity::Warning, Some(id)) => self.0.contains(id),
+            _                             => false
+        }
+    }
+
+    /// Remove the suppressed diagnostics.
+    pub fn filter<'a>(&self, diagnostics: Vec<Diagnostic<'a>>) -> Vec<Diagnostic<'a>> {
+        diagnostics
+            .into_iter()
+            .filter(|diagnostic| !self.is_suppressed(diagnostic))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        explain,
+        lookup,
+        Suppressions,
+        EXTENDED_SYNTAX,
+        REAL_OVERFLOW,
+        REGISTRY
+    };
+    use diagnostic::Diagnostic;
+    use internal::ErrorKindExtension;
+    use span::Span;
+
+    #[test]
+    fn case_registry_identifiers_are_unique_and_ordered() {
+        for (index, code) in REGISTRY.iter().enumerate() {
+            assert_eq!(code.id, format!("INK{:04}", index + 1));
+        }
+    }
+
+    #[test]
+    fn case_error_kind_extension_codes_are_registered() {
+        assert_eq!(lookup(ErrorKindExtension::ExtendedSyntax.code()), Some(&EXTENDED_SYNTAX));
+        assert!(lookup(ErrorKindExtension::Identifier.code()).is_some());
+        assert!(lookup(ErrorKindExtension::IntegerOverflow.code()).is_some());
+        assert_eq!(lookup(ErrorKindExtension::RealOverflow.code()), Some(&REAL_OVERFLOW));
+        assert!(lookup(ErrorKindExtension::StringInvalidEncoding.code()).is_some());
+    }
+
+    #[test]
+    fn case_explain() {
+        assert_eq!(explain("INK0004"), Some(EXTENDED_SYNTAX.explanation));
+        assert_eq!(explain("INK9999"), None);
+    }
+
+    #[test]
+    fn case_suppressions_from_config() {
+        let suppressions = Suppressions::from_config("# Drafts.\nINK0005, INK0006 # inline\n\nINK0007");
+        let mut expected = Suppressions::new();
+        expected.allow("INK0005");
+        expected.allow("INK0006");
+        expected.allow("INK0007");
+
+        assert_eq!(suppressions, expected);
+    }
+
+    #[test]
+    fn case_suppressions_from_source() {
+        let suppressions = Suppressions::from_source("Hello\n  // ink-allow: INK0005 INK0006\n// ink-allowed: INK0007\n// INK0008\n");
+        let mut expected = Suppressions::new();
+        expected.allow("INK0005");
+        expected.allow("INK0006");
+
+        assert_eq!(suppressions, expected);
+    }
+
+    #[test]
+    fn case_suppressions_filter_warnings_only() {
+        let span        = Span::new_at("x", 0, 1, 1);
+        let warning     = Diagnostic::warning(span, "loose end".to_string()).with_code("INK0005");
+        let other       = Diagnostic::warning(span, "loose end".to_string()).with_code("INK0006");
+        let error       = Diagnostic::error(span, "extended".to_string()).with_code("INK0004");
+        let mut allowed = Suppressions::new();
+        allowed.allow("INK0005");
+        allowed.allow("INK0004");
+
+        assert_eq!(allowed.filter(vec![warning, other.clone(), error.clone()]), vec![other, error]);
+    }
+}
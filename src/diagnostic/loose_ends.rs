@@ -0,0 +1,311 @@
+use ast::ast::{
+    Choice,
+    Content,
+    ContentPart,
+    Line,
+    Statement,
+    Story,
+    Weave
+};
+use span::Span;
+
+use super::{
+    code,
+    Diagnostic
+};
+
+/// Find the loose ends of a story, i.e. the branches where the flow runs
+/// out of content without a divert.
+///
+/// A branch of the root content must end with a divert, e.g. `-> END` or
+/// `-> DONE`, and so must a branch of a knot or of a stitch. Functions are
+/// left aside since they return to their caller. Each diagnostic is a
+/// warning whose span is the last line of the dangling branch.
+///
+/// The flow of choices followed by a gather continues at the gather, so
+/// only the last lines of a weave are analysed: a trailing run of choices
+/// dangles through the body of each choice, a conditional or a sequence
+/// through each of its branches.
+pub fn loose_ends<'a>(story: &Story<'a>) -> Vec<Diagnostic<'a>> {
+    let mut diagnostics = Vec::new();
+    let mut ends        = Vec::new();
+
+    dangling(&story.body, None, &mut ends);
+
+    for end in ends.drain(..) {
+        diagnostics.push(
+            Diagnostic::warning(end, "the story may reach the end of the root content without `-> END` or `-> DONE`".to_string())
+                .with_code(code::MISSING_END.id)
+                .with_label("the flow runs out here".to_string())
+        );
+    }
+
+    for knot in story.knots.iter().filter(|knot| !knot.function) {
+        // The flow of an empty knot enters its first stitch.
+        if !knot.body.lines.is_empty() || knot.stitches.is_empty() {
+            dangling(&knot.body, Some(knot.name), &mut ends);
+        }
+
+        for end in ends.drain(..) {
+            diagnostics.push(loose_end(end, knot.name, "knot"));
+        }
+
+        for stitch in &knot.stitches {
+            dangling(&stitch.body, Some(stitch.name), &mut ends);
+
+            for end in ends.drain(..) {
+                diagnostics.push(loose_end(end, stitch.name, "stitch"));
+            }
+        }
+    }
+
+    diagnostics
+}
+
+fn loose_end<'a>(end: Span<'a>, name: Span<'a>, kind: &str) -> Diagnostic<'a> {
+    let mut diagnostic = Diagnostic::warning(
+        end,
+        format!("loose end: the flow of `{}` runs out without a divert", name.as_slice())
+    )
+        .with_code(code::LOOSE_END.id)
+        .with_label("the flow runs out here".to_string())
+        .with_note("end the branch with a divert, e.g. `-> DONE`".to_string());
+
+    if end != name {
+        diagnostic = diagnostic.with_secondary_label(name, format!("in this {}", kind));
+    }
+
+    diagnostic
+}
+
+/// Collect the span of the last line of each dangling branch of a weave.
+///
+/// `fallback` is the span reported when a branch has no line with a span,
+/// e.g. an empty knot.
+fn dangling<'a>(weave: &Weave<'a>, fallback: Option<Span<'a>>, ends: &mut Vec<Span<'a>>) {
+    let lines: Vec<&Line<'a>> = weave.lines
+        .iter()
+        .filter(|line| !matches!(**line, Line::Annotation(_) | Line::Error(_)))
+        .collect();
+
+    let last = match lines.last() {
+        Some(last) => *last,
+        None       => {
+            ends.extend(fallback);
+
+            return;
+        }
+    };
+
+    let end = match *last {
+        Line::Content(ref content) => {
+            if diverts(content) {
+                return;
+            }
+
+            content_span(content)
+        },
+
+        Line::Gather(ref gather) => {
+            match gather.content {
+                Some(ref content) if diverts(content) => return,
+                Some(ref content)                     => content_span(content),
+                None                                  => None
+            }
+        },
+
+        Line::Statement(Statement::Return { .. }) => return,
+
+        Line::Statement(Statement::Declaration { span, .. })
+        | Line::Statement(Statement::List { span, .. }) => Some(span),
+
+        Line::Choice(_) => {
+            let mut choices: Vec<&Choice<'a>> = lines
+                .iter()
+                .rev()
+                .map_while(|line| {
+                    match **line {
+                        Line::Choice(ref choice) => Some(choice),
+                        _                        => None
+                    }
+                })
+                .collect();
+
+            choices.reverse();
+
+            for choice in choices {
+                let body_is_empty = choice.body.lines.iter().all(|line| matches!(*line, Line::Annotation(_) | Line::Error(_)));
+
+                if body_is_empty && choice_diverts(choice) {
+                    continue;
+                }
+
+                dangling(&choice.body, choice_span(choice).or(fallback), ends);
+            }
+
+            return;
+        },
+
+        Line::Conditional(ref conditional) => {
+            for branch in &conditional.branches {
+                dangling(&branch.body, fallback, ends);
+            }
+
+            return;
+        },
+
+        Line::Sequence(ref sequence) => {
+            for branch in &sequence.branches {
+                dangling(branch, fallback, ends);
+            }
+
+            return;
+        },
+
+        Line::Annotation(_) | Line::Error(_) => None
+    };
+
+    ends.extend(end.or(fallback));
+}
+
+/// Check whether a line of content ends with a divert.
+fn diverts(content: &Content) -> bool {
+    matches!(content.0.last(), Some(&ContentPart::Divert(_)))
+}
+
+/// Check whether the line of a choice ends with a divert.
+fn choice_diverts(choice: &Choice) -> bool {
+    [&choice.output, &choice.choice_only, &choice.start]
+        .iter()
+        .find_map(|content| content.as_ref())
+        .is_some_and(diverts)
+}
+
+/// The span of a line of content, from its first part to its last one.
+fn content_span<'a>(content: &Content<'a>) -> Option<Span<'a>> {
+    match (content.0.first(), content.0.last()) {
+        (Some(first), Some(last)) => {
+            let first = part_span(first);
+
+            Some(first.join(&part_span(last)).unwrap_or(first))
+        },
+
+        _ => None
+    }
+}
+
+fn part_span<'a>(part: &ContentPart<'a>) -> Span<'a> {
+    match *part {
+        ContentPart::Text(span)              => span,
+        ContentPart::Interpolation(ref part) => part.span,
+        ContentPart::Divert(ref part)        => part.span
+    }
+}
+
+/// The span of the line of a choice, from its first content to its last
+/// one.
+fn choice_span<'a>(choice: &Choice<'a>) -> Option<Span<'a>> {
+    let contents = [&choice.start, &choice.choice_only, &choice.output];
+    let first    = contents.iter().find_map(|content| content.as_ref()).and_then(content_span)?;
+    let last     = contents.iter().rev().find_map(|content| content.as_ref()).and_then(content_span)?;
+
+    Some(first.join(&last).unwrap_or(first))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::loose_ends;
+    use diagnostic::Diagnostic;
+    use options::ParserOptions;
+    use span::Span;
+
+    fn ends<'a>(input: &'a str) -> Vec<Span<'a>> {
+        let story = ::parse(input, ParserOptions::default()).unwrap();
+
+        loose_ends(&story).into_iter().map(|diagnostic: Diagnostic| diagnostic.span).collect()
+    }
+
+    #[test]
+    fn case_no_loose_ends() {
+        assert_eq!(ends("Hello\n-> london\n== london ==\nBye\n-> END\n"), vec![]);
+    }
+
+    #[test]
+    fn case_root_without_end() {
+        let story       = ::parse("Hello\nWorld\n", ParserOptions::default()).unwrap();
+        let diagnostics = loose_ends(&story);
+
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic::warning(Span::new_at("World", 6, 2, 1), "the story may reach the end of the root content without `-> END` or `-> DONE`".to_string())
+                    .with_code("INK0006")
+                    .with_label("the flow runs out here".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn case_knot_without_divert() {
+        let story       = ::parse("-> london\n== london ==\nBye\n", ParserOptions::default()).unwrap();
+        let diagnostics = loose_ends(&story);
+
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic::warning(Span::new_at("Bye", 23, 3, 1), "loose end: the flow of `london` runs out without a divert".to_string())
+                    .with_code("INK0005")
+                    .with_label("the flow runs out here".to_string())
+                    .with_secondary_label(Span::new_at("london", 13, 2, 4), "in this knot".to_string())
+                    .with_note("end the branch with a divert, e.g. `-> DONE`".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn case_empty_knot() {
+        assert_eq!(ends("-> london\n== london ==\n"), vec![Span::new_at("london", 13, 2, 4)]);
+    }
+
+    #[test]
+    fn case_knot_entering_its_first_stitch() {
+        assert_eq!(ends("-> london\n== london\n= first\n-> DONE\n"), vec![]);
+    }
+
+    #[test]
+    fn case_function_is_not_a_loose_end() {
+        assert_eq!(ends("-> END\n== function f ==\nHello\n"), vec![]);
+    }
+
+    #[test]
+    fn case_choices_dangling_branches() {
+        assert_eq!(
+            ends("* A\n  Aa\n* B -> END\n* C\n  -> DONE\n* D\n"),
+            vec![Span::new_at("Aa", 6, 2, 3), Span::new_at("D", 36, 6, 3)]
+        );
+    }
+
+    #[test]
+    fn case_line_span_covers_every_part() {
+        assert_eq!(ends("Hello {name} -> london\n== london ==\nBye {name}\n"), vec![Span::new_at("Bye {name}", 36, 3, 1)]);
+        assert_eq!(ends("* Go [now] {x}\n"), vec![Span::new_at("Go [now] {x}", 2, 1, 3)]);
+    }
+
+    #[test]
+    fn case_choices_followed_by_gather() {
+        assert_eq!(ends("* A\n* B\n- Gathered -> END\n"), vec![]);
+    }
+
+    #[test]
+    fn case_conditional_dangling_branch() {
+        assert_eq!(
+            ends("{ x:\n  -> END\n- else:\n  Nope\n}\n"),
+            vec![Span::new_at("Nope", 24, 4, 3)]
+        );
+    }
+
+    #[test]
+    fn case_annotations_are_skipped() {
+        assert_eq!(ends("Hello -> END\nTODO: write more\n"), vec![]);
+    }
+}
@@ -0,0 +1,205 @@
+pub mod code;
+pub mod loose_ends;
+pub mod names;
+pub mod render;
+
+use ast::ast::{
+    Story,
+    Token
+};
+use ast::visit::Visitor;
+use error::ParseError;
+use span::Span;
+
+use nom::Slice;
+
+/// The severity of a diagnostic.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Severity {
+    /// The story cannot be compiled.
+    Error,
+
+    /// The story can be compiled, but something looks wrong.
+    Warning
+}
+
+impl Severity {
+    /// The name of the severity, as printed in the header of a diagnostic.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Severity::Error   => "error",
+            Severity::Warning => "warning"
+        }
+    }
+}
+
+/// A secondary label, i.e. a message attached to another span than the
+/// primary span of a diagnostic.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Label<'a> {
+    /// The labelled span.
+    pub span: Span<'a>,
+
+    /// The message of the label.
+    pub message: String
+}
+
+/// A machine-applicable fix, i.e. a replacement of a span that tools can
+/// apply without review.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Fix<'a> {
+    /// A human-readable message, e.g. ``did you mean `london`?``.
+    pub message: String,
+
+    /// The replaced span.
+    pub span: Span<'a>,
+
+    /// The replacement.
+    pub replacement: String
+}
+
+/// A problem found in a story, e.g. a malformed line.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic<'a> {
+    /// The severity.
+    pub severity: Severity,
+
+    /// The code, e.g. `INK0001`, if any, see `code::REGISTRY`.
+    pub code: Option<&'static str>,
+
+    /// The primary span of the problem.
+    pub span: Span<'a>,
+
+    /// A human-readable message.
+    pub message: String,
+
+    /// The message under the primary span, if any.
+    pub label: Option<String>,
+
+    /// The secondary labels.
+    pub labels: Vec<Label<'a>>,
+
+    /// The notes, printed after the source snippets.
+    pub notes: Vec<String>,
+
+    /// The fixes, printed after the notes.
+    pub fixes: Vec<Fix<'a>>
+}
+
+impl<'a> Diagnostic<'a> {
+    /// Create a diagnostic without code, labels and notes.
+    pub fn new(severity: Severity, span: Span<'a>, message: String) -> Self {
+        Diagnostic {
+            severity,
+            code    : None,
+            span,
+            message,
+            label   : None,
+            labels  : Vec::new(),
+            notes   : Vec::new(),
+            fixes   : Vec::new()
+        }
+    }
+
+    /// Create an error.
+    pub fn error(span: Span<'a>, message: String) -> Self {
+        Self::new(Severity::Error, span, message)
+    }
+
+    /// Create a warning.
+    pub fn warning(span: Span<'a>, message: String) -> Self {
+        Self::new(Severity::Warning, span, message)
+    }
+
+    /// Set the code.
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Set the message under the primary span.
+    pub fn with_label(mut self, message: String) -> Self {
+        self.label = Some(message);
+        self
+    }
+
+    /// Add a secondary label.
+    pub fn with_secondary_label(mut self, span: Span<'a>, message: String) -> Self {
+        self.labels.push(Label { span, message });
+        self
+    }
+
+    /// Add a note.
+    pub fn with_note(mut self, note: String) -> Self {
+        self.notes.push(note);
+        self
+    }
+
+    /// Add a fix replacing a span.
+    pub fn with_fix(mut self, span: Span<'a>, replacement: String, message: String) -> Self {
+        self.fixes.push(Fix { message, span, replacement });
+        self
+    }
+}
+
+/// Apply fixes to the source their spans come from.
+///
+/// The fixes are applied in the order of the source; a fix overlapping a
+/// previous one is ignored.
+pub fn apply_fixes(source: &str, fixes: &[&Fix]) -> String {
+    let mut fixes = fixes.to_vec();
+    fixes.sort_by_key(|fix| fix.span.offset);
+
+    let mut output = String::with_capacity(source.len());
+    let mut offset = 0;
+
+    for fix in fixes {
+        if fix.span.offset < offset {
+            continue;
+        }
+
+        output.push_str(&source[offset..fix.span.offset]);
+        output.push_str(&fix.replacement);
+        offset = fix.span.offset + fix.span.as_slice().len();
+    }
+
+    output.push_str(&source[offset..]);
+
+    output
+}
+
+impl<'a> From<ParseError<'a>> for Diagnostic<'a> {
+    fn from(error: ParseError<'a>) -> Self {
+        let message = error.message();
+
+        match error {
+            // The span of an unexpected input runs to the end of the input,
+            // only its first character is relevant.
+            ParseError::Unexpected { span, .. } => {
+                let length = span.as_slice().chars().next().map_or(0, char::len_utf8);
+
+                Diagnostic::error(span.slice(..length), message).with_code(code::UNEXPECTED_INPUT.id)
+            },
+
+            ParseError::Invalid { span, kind } => Diagnostic::error(span, message).with_code(kind.code())
+        }
+    }
+}
+
+/// Collect the diagnostics of the `Line::Error` nodes of a story, in the
+/// order of the source.
+pub fn collect<'a>(story: &Story<'a>) -> Vec<Diagnostic<'a>> {
+    let mut errors = Errors(Vec::new());
+    errors.visit_story(story);
+
+    errors.0
+}
+
+/// A visitor collecting the diagnostics of the `Line::Error` nodes.
+struct Errors<'a>(Vec<Diagnostic<'a>>);
+
+impl<'s, 'a: 's> Visitor<'s, 'a> for Errors<'a> {
+    fn visit_error(&mut self, error: &'s Token<'a, ParseError<'a>>) {
+        self.0.push(Diagnostic::from(error.value.clone()));
+    }
+}
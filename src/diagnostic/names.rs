@@ -0,0 +1,617 @@
+use std::cmp;
+use std::collections::HashSet;
+use std::mem;
+
+use ast::ast::{
+    Choice,
+    Divert,
+    Gather,
+    Knot,
+    Statement,
+    Stitch,
+    Story,
+    Token,
+    Variable,
+    Weave
+};
+use ast::visit::{
+    walk_choice,
+    walk_gather,
+    walk_knot,
+    walk_stitch,
+    Visitor
+};
+use span::Span;
+
+use super::{
+    code,
+    Diagnostic
+};
+
+/// Find the names that do not resolve, i.e. the divert targets and the
+/// variables that are not declared.
+///
+/// A divert target is a knot, a stitch of the current knot, or a label of
+/// the current knot or stitch, e.g. `-> london`, `-> first_class` or
+/// `-> top`. It is also a path: a stitch or a label of a knot, e.g.
+/// `-> london.first_class` or `-> london.top`, a label of a stitch of the
+/// current knot, e.g. `-> first_class.top`, or a label of a stitch of a
+/// knot, e.g. `-> london.first_class.top`. A label is the label of a choice
+/// or of a gather, e.g. `- (top)`.
+///
+/// A variable is declared anywhere in the story (`~ x = 1`), is a parameter
+/// of the current knot, or is the read count of a knot, of a stitch of the
+/// current knot, or of a label of the current knot or stitch.
+///
+/// The function calls are not parsed yet, so the names of the functions are
+/// not resolved: a function is neither a divert target nor a variable.
+///
+/// Each diagnostic suggests the closest known name in the same scope, by
+/// edit distance, with a fix replacing the unknown identifier.
+pub fn unresolved_names<'a>(story: &Story<'a>) -> Vec<Diagnostic<'a>> {
+    let mut declarations = Declarations(Names::default());
+    declarations.visit_story(story);
+
+    let mut globals = declarations.0;
+
+    for knot in story.knots.iter().filter(|knot| !knot.function) {
+        globals.insert(knot.name.as_slice());
+    }
+
+    let mut resolver = Resolver {
+        story,
+        knot       : None,
+        globals,
+        locals     : Names::default(),
+        labels     : labels(&story.body),
+        diagnostics: Vec::new()
+    };
+
+    resolver.visit_story(story);
+
+    resolver.diagnostics
+}
+
+/// A set of names, in the order of their first declaration, so that the
+/// suggestions do not depend on the hash of the names.
+#[derive(Default)]
+struct Names<'a> {
+    set    : HashSet<&'a str>,
+    ordered: Vec<Candidate<'a>>
+}
+
+impl<'a> Names<'a> {
+    fn insert(&mut self, name: &'a str) {
+        if self.set.insert(name) {
+            self.ordered.push(Candidate::new(name));
+        }
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.set.contains(name)
+    }
+}
+
+/// A visitor collecting the names of the declared variables, and of the
+/// lists and their items.
+struct Declarations<'a>(Names<'a>);
+
+impl<'s, 'a: 's> Visitor<'s, 'a> for Declarations<'a> {
+    fn visit_statement(&mut self, statement: &'s Statement<'a>) {
+        match *statement {
+            Statement::Declaration { variable: Variable(name), .. } => self.0.insert(name.as_slice()),
+
+            Statement::List { name: Variable(name), ref items, .. } => {
+                self.0.insert(name.as_slice());
+
+                for item in items {
+                    self.0.insert(item.name.as_slice());
+                }
+            },
+
+            Statement::Return { .. } => {}
+        }
+    }
+}
+
+/// A visitor collecting the labels of the choices and of the gathers of a
+/// weave, in its nested weaves too.
+struct Labels<'a>(Names<'a>);
+
+impl<'s, 'a: 's> Visitor<'s, 'a> for Labels<'a> {
+    fn visit_choice(&mut self, choice: &'s Choice<'a>) {
+        if let Some(label) = choice.label {
+            self.0.insert(label.as_slice());
+        }
+
+        walk_choice(self, choice);
+    }
+
+    fn visit_gather(&mut self, gather: &'s Gather<'a>) {
+        if let Some(label) = gather.label {
+            self.0.insert(label.as_slice());
+        }
+
+        walk_gather(self, gather);
+    }
+}
+
+/// The labels of a weave.
+fn labels<'a>(weave: &Weave<'a>) -> Names<'a> {
+    let mut labels = Labels(Names::default());
+    labels.visit_weave(weave);
+
+    labels.0
+}
+
+/// A visitor resolving the divert targets and the variables, in the scope
+/// of the knot being visited.
+struct Resolver<'s, 'a: 's> {
+    story      : &'s Story<'a>,
+    knot       : Option<&'s Knot<'a>>,
+
+    /// The names of the story: the variables and the knots.
+    globals    : Names<'a>,
+
+    /// The names of the current knot: its parameters and its stitches.
+    locals     : Names<'a>,
+
+    /// The labels of the current knot, and of the current stitch if any, or
+    /// of the root weave outside the knots.
+    labels     : Names<'a>,
+    diagnostics: Vec<Diagnostic<'a>>
+}
+
+impl<'s, 'a: 's> Visitor<'s, 'a> for Resolver<'s, 'a> {
+    fn visit_knot(&mut self, knot: &'s Knot<'a>) {
+        self.knot   = Some(knot);
+        self.locals = Names::default();
+
+        for name in knot.parameters.iter().map(|parameter| parameter.0).chain(knot.stitches.iter().map(|stitch| stitch.name)) {
+            self.locals.insert(name.as_slice());
+        }
+
+        self.labels = labels(&knot.body);
+
+        walk_knot(self, knot);
+    }
+
+    fn visit_stitch(&mut self, stitch: &'s Stitch<'a>) {
+        let knot_labels = self.knot.map_or_else(Names::default, |knot| labels(&knot.body));
+
+        self.labels = knot_labels;
+
+        for label in labels(&stitch.body).ordered {
+            self.labels.insert(label.name);
+        }
+
+        walk_stitch(self, stitch);
+    }
+
+    fn visit_divert(&mut self, divert: &'s Token<'a, Divert<'a>>) {
+        self.divert(divert);
+    }
+
+    fn visit_variable(&mut self, variable: &'s Variable<'a>) {
+        self.variable(variable.0);
+    }
+}
+
+impl<'s, 'a: 's> Resolver<'s, 'a> {
+    /// The knots that can be diverted to, i.e. all the knots except the
+    /// functions.
+    fn knots(&self) -> Vec<&'s Knot<'a>> {
+        self.story.knots.iter().filter(|knot| !knot.function).collect()
+    }
+
+    fn divert(&mut self, token: &Token<'a, Divert<'a>>) {
+        let path = match token.value {
+            Divert::Target(ref path)         => path,
+            Divert::End(_) | Divert::Done(_) => return
+        };
+
+        let knots = self.knots();
+
+        match path.as_slice() {
+            [name] => {
+                let mut candidates: Vec<&str> = self.labels.ordered.iter().map(|label| label.name).collect();
+                candidates.extend(self.knot.iter().flat_map(|knot| knot.stitches.iter().map(|stitch| stitch.name.as_slice())));
+                candidates.extend(knots.iter().map(|knot| knot.name.as_slice()));
+
+                if !candidates.contains(&name.as_slice()) {
+                    self.unknown(*name, closest(name.as_slice(), &candidates), code::UNKNOWN_DIVERT_TARGET.id, "unknown divert target");
+                }
+            },
+
+            [first, name, ref rest @ ..] => {
+                // A path starting with a knot, e.g. `-> london.first_class`
+                // or `-> london.top`, then with a stitch of the current knot,
+                // e.g. `-> first_class.top`.
+                if let Some(knot) = knots.iter().find(|knot| knot.name.as_slice() == first.as_slice()) {
+                    match (find_stitch(knot, name), rest.first()) {
+                        (Some(stitch), Some(label)) => self.label(label, stitch),
+                        (Some(_), None)             => {},
+
+                        // A label of the knot ends the path.
+                        (None, _) => {
+                            let knot_labels = labels(&knot.body);
+                            let mut candidates: Vec<&str> = knot.stitches.iter().map(|stitch| stitch.name.as_slice()).collect();
+                            candidates.extend(knot_labels.ordered.iter().map(|label| label.name));
+
+                            if !candidates.contains(&name.as_slice()) {
+                                self.unknown(*name, closest(name.as_slice(), &candidates), code::UNKNOWN_DIVERT_TARGET.id, "unknown stitch or label");
+                            }
+                        }
+                    }
+                } else if let Some(stitch) = self.knot.and_then(|knot| find_stitch(knot, first)) {
+                    self.label(name, stitch);
+                } else {
+                    let mut candidates: Vec<&str> = knots.iter().map(|knot| knot.name.as_slice()).collect();
+                    candidates.extend(self.knot.iter().flat_map(|knot| knot.stitches.iter().map(|stitch| stitch.name.as_slice())));
+
+                    self.unknown(*first, closest(first.as_slice(), &candidates), code::UNKNOWN_DIVERT_TARGET.id, "unknown knot");
+                }
+            },
+
+            [] => {}
+        }
+    }
+
+    /// Check that `label` is a label of `stitch`, at the end of a path.
+    fn label(&mut self, label: &Span<'a>, stitch: &Stitch<'a>) {
+        let stitch_labels = labels(&stitch.body);
+
+        if !stitch_labels.contains(label.as_slice()) {
+            let candidates: Vec<&str> = stitch_labels.ordered.iter().map(|candidate| candidate.name).collect();
+
+            self.unknown(*label, closest(label.as_slice(), &candidates), code::UNKNOWN_DIVERT_TARGET.id, "unknown label");
+        }
+    }
+
+    fn variable(&mut self, name: Span<'a>) {
+        if self.globals.contains(name.as_slice()) || self.locals.contains(name.as_slice()) || self.labels.contains(name.as_slice()) {
+            return;
+        }
+
+        let suggestion = closest_candidate(name.as_slice(), self.globals.ordered.iter().chain(&self.locals.ordered).chain(&self.labels.ordered).cloned());
+
+        self.unknown(name, suggestion, code::UNKNOWN_VARIABLE.id, "unknown variable");
+    }
+
+    fn unknown(&mut self, name: Span<'a>, suggestion: Option<&str>, id: &'static str, message: &str) {
+        let code           = code::lookup(id).expect("The code is registered.");
+        let mut diagnostic = Diagnostic::new(code.severity, name, format!("{} `{}`", message, name.as_slice()))
+            .with_code(id)
+            .with_label("not found in this scope".to_string());
+
+        if let Some(suggestion) = suggestion {
+            diagnostic = diagnostic.with_fix(name, suggestion.to_string(), format!("did you mean `{}`?", suggestion));
+        }
+
+        self.diagnostics.push(diagnostic);
+    }
+}
+
+/// The stitch of a knot named `name`, if any.
+fn find_stitch<'s, 'a>(knot: &'s Knot<'a>, name: &Span) -> Option<&'s Stitch<'a>> {
+    knot.stitches.iter().find(|stitch| stitch.name.as_slice() == name.as_slice())
+}
+
+/// The closest candidate to a name by edit distance, if it is close enough,
+/// i.e. at a distance of at most a third of the length of the name.
+///
+/// On a tie, the first candidate wins.
+pub fn closest<'n>(name: &str, candidates: &[&'n str]) -> Option<&'n str> {
+    closest_candidate(name, candidates.iter().map(|&candidate| Candidate::new(candidate)))
+}
+
+/// The closest candidate to a name, see `closest`. The limit of the distance
+/// decreases as closer candidates are found, so that the lower bounds discard
+/// more and more candidates.
+fn closest_candidate<'n, I>(name: &str, candidates: I) -> Option<&'n str>
+    where I: Iterator<Item = Candidate<'n>>
+{
+    let name      = Candidate::new(name);
+    let left      = name.name.chars().collect::<Vec<char>>();
+    let mut right = Vec::new();
+    let mut rows  = Rows::default();
+    let mut limit = cmp::max(1, name.length / 3);
+    let mut best  = None;
+
+    for candidate in candidates {
+        if candidate.name == name.name || candidate.lower_bound(&name) > limit {
+            continue;
+        }
+
+        right.clear();
+        right.extend(candidate.name.chars());
+
+        if let Some(distance) = rows.distance(&left, &right, limit) {
+            best = Some(candidate.name);
+
+            // Only a closer candidate can win from now on, and none is closer
+            // than a single edit.
+            if distance <= 1 {
+                break;
+            }
+
+            limit = distance - 1;
+        }
+    }
+
+    best
+}
+
+/// A name with what a cheap lower bound of its edit distance to another
+/// name needs, so that most of the candidates are discarded without
+/// computing the distance.
+#[derive(Clone, Copy)]
+struct Candidate<'n> {
+    name  : &'n str,
+
+    /// The number of characters.
+    length: usize,
+
+    /// The set of the characters, each character being a bit modulo 64.
+    mask  : u64
+}
+
+impl<'n> Candidate<'n> {
+    fn new(name: &'n str) -> Self {
+        Candidate {
+            name,
+            length: name.chars().count(),
+            mask  : name.chars().fold(0, |mask, character| mask | 1 << (character as u32 % 64))
+        }
+    }
+
+    /// A lower bound of the edit distance: the difference of the lengths,
+    /// and half the number of characters in only one of the names, since an
+    /// edit adds or removes at most two of them.
+    fn lower_bound(&self, other: &Candidate) -> usize {
+        let characters = (self.mask ^ other.mask).count_ones() as usize;
+
+        cmp::max(self.length.abs_diff(other.length), characters.div_ceil(2))
+    }
+}
+
+/// The edit distance between two strings, counted in characters.
+///
+/// It is the Levenshtein distance where swapping two adjacent characters
+/// counts as a single edit (the optimal string alignment distance), since a
+/// swap is a common typo, e.g. `lodnon`.
+pub fn edit_distance(left: &str, right: &str) -> usize {
+    let left: Vec<char>  = left.chars().collect();
+    let right: Vec<char> = right.chars().collect();
+
+    Rows::default()
+        .distance(&left, &right, usize::MAX)
+        .expect("The distance is bounded by the lengths.")
+}
+
+/// The last three rows of the distances between the prefixes of two names,
+/// kept from one distance to the next to spare the allocations.
+#[derive(Default)]
+struct Rows {
+    before  : Vec<usize>,
+    previous: Vec<usize>,
+    current : Vec<usize>
+}
+
+impl Rows {
+    /// The edit distance between two names, see `edit_distance`, if it is
+    /// at most `limit`.
+    fn distance(&mut self, left: &[char], right: &[char], limit: usize) -> Option<usize> {
+        // `previous[j]` is the distance between the first `i - 1`
+        // characters of `left` and the first `j` characters of `right`,
+        // `before[j]` between the first `i - 2`, and `current[j]` between
+        // the first `i`.
+        for row in [&mut self.before, &mut self.previous, &mut self.current] {
+            row.clear();
+            row.resize(right.len() + 1, 0);
+        }
+
+        for (j, distance) in self.previous.iter_mut().enumerate() {
+            *distance = j;
+        }
+
+        for i in 1..=left.len() {
+            self.current[0] = i;
+
+            for j in 1..=right.len() {
+                let cost = if left[i - 1] == right[j - 1] { 0 } else { 1 };
+
+                self.current[j] = cmp::min(
+                    self.previous[j - 1] + cost,
+                    cmp::min(self.previous[j], self.current[j - 1]) + 1
+                );
+
+                if i > 1 && j > 1 && left[i - 1] == right[j - 2] && left[i - 2] == right[j - 1] {
+                    self.current[j] = cmp::min(self.current[j], self.before[j - 2] + 1);
+                }
+            }
+
+            // The minimum of a row never decreases from one row to the next.
+            if self.current.iter().min().is_some_and(|&distance| distance > limit) {
+                return None;
+            }
+
+            mem::swap(&mut self.before, &mut self.previous);
+            mem::swap(&mut self.previous, &mut self.current);
+        }
+
+        Some(self.previous[right.len()]).filter(|&distance| distance <= limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        closest,
+        edit_distance,
+        unresolved_names
+    };
+    use diagnostic::{
+        apply_fixes,
+        Diagnostic
+    };
+    use options::ParserOptions;
+    use span::Span;
+
+    fn diagnostics(input: &str) -> Vec<Diagnostic<'_>> {
+        unresolved_names(&::parse(input, ParserOptions::default()).unwrap())
+    }
+
+    #[test]
+    fn case_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("london", "london"), 0);
+        assert_eq!(edit_distance("london", "lodnon"), 1);
+        assert_eq!(edit_distance("first", "frist"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("été", "ete"), 2);
+    }
+
+    #[test]
+    fn case_closest() {
+        assert_eq!(closest("lodnon", &["paris", "london"]), Some("london"));
+        assert_eq!(closest("x", &["y", "z"]), Some("y"));
+        assert_eq!(closest("rome", &["paris", "london"]), None);
+    }
+
+    #[test]
+    fn case_resolved_names() {
+        assert_eq!(diagnostics("~ x = 1\n{x} -> london\n== london(y) ==\n{y + london + first} -> first\n= first\n-> london.first\n"), vec![]);
+    }
+
+    #[test]
+    fn case_resolved_list_names() {
+        assert_eq!(diagnostics("LIST kettle = cold, (boiling)\nCONST max = 3\n{kettle has boiling && cold < max}\n"), vec![]);
+    }
+
+    #[test]
+    fn case_unknown_divert_target() {
+        assert_eq!(
+            diagnostics("-> lodnon\n== london ==\n-> END\n"),
+            vec![
+                Diagnostic::error(Span::new_at("lodnon", 3, 1, 4), "unknown divert target `lodnon`".to_string())
+                    .with_code("INK0007")
+                    .with_label("not found in this scope".to_string())
+                    .with_fix(Span::new_at("lodnon", 3, 1, 4), "london".to_string(), "did you mean `london`?".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn case_unknown_divert_target_without_suggestion() {
+        assert_eq!(
+            diagnostics("-> rome\n== london ==\n-> END\n"),
+            vec![
+                Diagnostic::error(Span::new_at("rome", 3, 1, 4), "unknown divert target `rome`".to_string())
+                    .with_code("INK0007")
+                    .with_label("not found in this scope".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn case_label_in_path() {
+        let diagnostics = diagnostics("-> london.first.top\n-> london.frist.top\n-> london.first.tpo\n== london ==\n= first\n- (top) -> END\n");
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].message, "unknown stitch or label `frist`");
+        assert_eq!(diagnostics[0].span, Span::new_at("frist", 30, 2, 11));
+        assert_eq!(diagnostics[1].message, "unknown label `tpo`");
+        assert_eq!(diagnostics[1].fixes[0].replacement, "top");
+    }
+
+    #[test]
+    fn case_labels() {
+        let input = "\
+            - (start) Hello\n\
+            * (hello) Hi -> hello\n\
+            -> start\n\
+            == london ==\n\
+            * (top) {top} -> top\n\
+            - -> london.top\n\
+            -> first.bottom\n\
+            = first\n\
+            - (bottom) -> top\n\
+            -> london.first.bottom\n\
+            -> bottom\n";
+
+        assert_eq!(diagnostics(input), vec![]);
+    }
+
+    #[test]
+    fn case_labels_are_scoped() {
+        let diagnostics = diagnostics("-> top\n-> london.bottom\n== london ==\n- (top) -> END\n= first\n- (bottom) -> END\n== paris ==\n-> top\n");
+
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0].span, Span::new_at("top", 3, 1, 4));
+        assert_eq!(diagnostics[1].message, "unknown stitch or label `bottom`");
+        assert_eq!(diagnostics[2].span, Span::new_at("top", 93, 8, 4));
+    }
+
+    #[test]
+    fn case_duplicate_declarations() {
+        let diagnostics = diagnostics("~ x = 1\n~ x = 2\n~ x = 3\n{y}\n");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].fixes[0].replacement, "x");
+    }
+
+    #[test]
+    fn case_stitch_scoping() {
+        let input       = "-> london.frist\n-> frist\n== london ==\n-> frist\n= first\n-> END\n";
+        let diagnostics = diagnostics(input);
+
+        // The stitch `first` is only suggested inside its knot, or with the
+        // knot in the path.
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0].message, "unknown stitch or label `frist`");
+        assert_eq!(diagnostics[0].fixes[0].replacement, "first");
+        assert_eq!(diagnostics[1].message, "unknown divert target `frist`");
+        assert_eq!(diagnostics[1].span, Span::new_at("frist", 19, 2, 4));
+        assert!(diagnostics[1].fixes.is_empty());
+        assert_eq!(diagnostics[2].span, Span::new_at("frist", 41, 4, 4));
+        assert_eq!(diagnostics[2].fixes[0].replacement, "first");
+    }
+
+    #[test]
+    fn case_unknown_knot_in_path() {
+        let diagnostics = diagnostics("-> londn.first\n== london ==\n= first\n-> END\n");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "unknown knot `londn`");
+        assert_eq!(diagnostics[0].fixes[0].replacement, "london");
+    }
+
+    #[test]
+    fn case_unknown_variable() {
+        let input       = "~ health = 10\nYou have {helth} points.\n";
+        let diagnostics = diagnostics(input);
+
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic::warning(Span::new_at("helth", 24, 2, 11), "unknown variable `helth`".to_string())
+                    .with_code("INK0008")
+                    .with_label("not found in this scope".to_string())
+                    .with_fix(Span::new_at("helth", 24, 2, 11), "health".to_string(), "did you mean `health`?".to_string())
+            ]
+        );
+
+        let fixes: Vec<_> = diagnostics.iter().flat_map(|diagnostic| diagnostic.fixes.iter()).collect();
+
+        assert_eq!(apply_fixes(input, &fixes), "~ health = 10\nYou have {health} points.\n");
+    }
+
+    #[test]
+    fn case_parameters_are_scoped_to_their_knot() {
+        let diagnostics = diagnostics("-> a\n== a(speed) ==\n{speed} -> b\n== b ==\n{speed} -> END\n");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].span, Span::new_at("speed", 42, 5, 2));
+    }
+}
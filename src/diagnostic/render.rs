@@ -0,0 +1,445 @@
+use std::fmt::Write;
+
+use super::{
+    Diagnostic,
+    Severity
+};
+use line_index::{
+    self,
+    ColumnUnit
+};
+use source_map::{
+    FileId,
+    Location,
+    SourceMap
+};
+use span::Span;
+
+/// The style of a rendered diagnostic.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Style {
+    /// Plain text.
+    Plain,
+
+    /// Text colored with ANSI escape codes, for terminals.
+    Colored
+}
+
+/// The options of the rendering of a diagnostic.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct RenderOptions {
+    /// The style of the text.
+    pub style: Style,
+
+    /// The width of a tabulation. When set, the tabulations of the source
+    /// are expanded to spaces, with a tab stop every `tab_width` columns, so
+    /// that the underlines are aligned whatever the width of a tabulation in
+    /// the output is, and the columns of the locations are visual columns,
+    /// see `ColumnUnit::Visual`. Otherwise, the tabulations are kept as is,
+    /// and the columns are in Unicode scalar values.
+    pub tab_width: Option<u32>
+}
+
+impl RenderOptions {
+    /// Create options for a particular style, keeping the tabulations.
+    pub fn new(style: Style) -> Self {
+        RenderOptions {
+            style,
+            tab_width: None
+        }
+    }
+
+    /// Expand the tabulations with a tab stop every `tab_width` columns.
+    pub fn with_tab_width(self, tab_width: u32) -> Self {
+        RenderOptions {
+            tab_width: Some(tab_width),
+            ..self
+        }
+    }
+}
+
+impl From<Style> for RenderOptions {
+    fn from(style: Style) -> Self {
+        RenderOptions::new(style)
+    }
+}
+
+const RESET : &str = "\x1b[0m";
+const BOLD  : &str = "\x1b[1m";
+const RED   : &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE  : &str = "\x1b[1;34m";
+
+/// Paint a text in a color, if the style is colored.
+fn paint(style: Style, color: &str, text: &str) -> String {
+    match style {
+        Style::Plain   => text.to_string(),
+        Style::Colored => format!("{}{}{}", color, text, RESET)
+    }
+}
+
+/// The sources the spans of a diagnostic come from.
+#[derive(Copy, Clone)]
+enum Sources<'s> {
+    /// A single source.
+    Single(&'s str),
+
+    /// The files of a source map.
+    Map(&'s SourceMap)
+}
+
+impl<'s> Sources<'s> {
+    /// The text of a file, empty if the file is unknown.
+    fn text(&self, file: FileId) -> &'s str {
+        match *self {
+            Sources::Single(source) => source,
+            Sources::Map(source_map) => source_map.file(file).map_or("", |file| file.text.as_str())
+        }
+    }
+
+    /// The location of a span, i.e. `line:column` with the column in
+    /// `unit`, prefixed by the path of its file if any.
+    fn location(&self, span: &Span, unit: ColumnUnit) -> String {
+        match *self {
+            Sources::Map(source_map) => {
+                if let Some(file) = source_map.file(span.file) {
+                    let index = file.line_index();
+
+                    return Location {
+                        path  : &file.path,
+                        line  : index.line(span.offset),
+                        column: index.column(span.offset, unit)
+                    }.to_string();
+                }
+            },
+
+            Sources::Single(source) => {
+                let (_, before) = source_line(source, span.offset);
+
+                return format!("{}:{}", span.line(), line_index::width(before, unit) + 1);
+            }
+        }
+
+        format!("{}:{}", span.line(), span.column())
+    }
+}
+
+/// Find the line containing a byte offset of the source.
+///
+/// Return the line, without its line ending, and the part of the line
+/// before the offset.
+fn source_line(source: &str, offset: usize) -> (&str, &str) {
+    let offset = offset.min(source.len());
+    let start  = source[..offset].rfind('\n').map_or(0, |index| index + 1);
+    let end    = source[offset..].find('\n').map_or(source.len(), |index| offset + index);
+    let line   = source[start..end].trim_end_matches('\r');
+
+    (line, &source[start..offset.min(start + line.len())])
+}
+
+/// Render a diagnostic like a compiler does, e.g.:
+///
+/// ```text
+/// error[E0012]: unknown divert target
+///  --> 3:4
+///   |
+/// 3 | -> lodnon
+///   |    ^^^^^^ no knot with this name
+///   |
+///   = note: knots are declared with `== name ==`
+///   = help: did you mean `london`?
+/// ```
+///
+/// `source` is the whole input the spans of the diagnostic come from. The
+/// options are either a `Style` or `RenderOptions`. The column of a
+/// location is in the unit used to place the underlines: Unicode scalar
+/// values, or visual columns if the tabulations are expanded, see
+/// `RenderOptions::tab_width`.
+pub fn render<O: Into<RenderOptions>>(diagnostic: &Diagnostic, source: &str, options: O) -> String {
+    render_sources(diagnostic, Sources::Single(source), options.into())
+}
+
+/// Render a diagnostic whose spans come from the files of a source map, like
+/// `render`. Locations are prefixed by the path of their file, e.g.
+/// ` --> newspaper.ink:3:4`, and the labels in another file than the primary
+/// span are introduced by their location, e.g. ` ::: main.ink:1:4`.
+pub fn render_with_source_map<O: Into<RenderOptions>>(diagnostic: &Diagnostic, source_map: &SourceMap, options: O) -> String {
+    render_sources(diagnostic, Sources::Map(source_map), options.into())
+}
+
+/// Expand the tabulations of a text starting a line to spaces, with a tab
+/// stop every `tab_width` columns.
+fn expand_tabs(text: &str, tab_width: u32) -> String {
+    let tab_width    = tab_width.max(1);
+    let mut expanded = String::with_capacity(text.len());
+    let mut column   = 0;
+
+    for item in text.chars() {
+        if item == '\t' {
+            let spaces = tab_width - column % tab_width;
+
+            expanded.push_str(&" ".repeat(spaces as usize));
+            column += spaces;
+        } else {
+            expanded.push(item);
+            column += 1;
+        }
+    }
+
+    expanded
+}
+
+fn render_sources(diagnostic: &Diagnostic, sources: Sources, options: RenderOptions) -> String {
+    let style      = options.style;
+    let unit       = options.tab_width.map_or(ColumnUnit::Char, |tab_width| ColumnUnit::Visual { tab_width });
+    let mut output = String::new();
+
+    let severity_color = match diagnostic.severity {
+        Severity::Error   => RED,
+        Severity::Warning => YELLOW
+    };
+
+    let header = match diagnostic.code {
+        Some(code) => format!("{}[{}]", diagnostic.severity.name(), code),
+        None       => diagnostic.severity.name().to_string()
+    };
+
+    let _ = writeln!(
+        output,
+        "{}{}",
+        paint(style, severity_color, &header),
+        paint(style, BOLD, &format!(": {}", diagnostic.message))
+    );
+
+    // The primary span first, then the secondary labels, ordered by line. The
+    // labels of the file of the primary span come first.
+    let primary_file = diagnostic.span.file;
+    let mut annotations: Vec<(Span, Option<&str>, bool)> = vec![(diagnostic.span, diagnostic.label.as_deref(), true)];
+    annotations.extend(diagnostic.labels.iter().map(|label| (label.span, Some(label.message.as_str()), false)));
+    annotations.sort_by_key(|&(span, _, primary)| (span.file != primary_file, span.file, span.line(), !primary));
+
+    let width  = annotations.iter().map(|&(span, _, _)| span.line().to_string().len()).max().unwrap_or(1);
+    let margin = " ".repeat(width);
+    let bar    = paint(style, BLUE, "|");
+
+    let _ = writeln!(output, "{}{} {}", margin, paint(style, BLUE, "-->"), sources.location(&diagnostic.span, unit));
+    let _ = writeln!(output, "{} {}", margin, bar);
+
+    let mut previous_file = primary_file;
+    let mut previous_line = None;
+
+    for &(span, message, primary) in &annotations {
+        if span.file != previous_file {
+            let _ = writeln!(output, "{}{} {}", margin, paint(style, BLUE, ":::"), sources.location(&span, unit));
+            let _ = writeln!(output, "{} {}", margin, bar);
+
+            previous_file = span.file;
+            previous_line = None;
+        }
+
+        let (line, before) = source_line(sources.text(span.file), span.offset);
+        let line_number    = span.line();
+
+        if previous_line != Some(line_number) {
+            if let Some(previous_line) = previous_line {
+                if line_number > previous_line + 1 {
+                    let _ = writeln!(output, "{}", paint(style, BLUE, "..."));
+                }
+            }
+
+            let line = match options.tab_width {
+                Some(tab_width) => expand_tabs(line, tab_width),
+                None            => line.to_string()
+            };
+
+            let _ = writeln!(output, "{} {} {}", paint(style, BLUE, &format!("{:>width$}", line_number, width = width)), bar, line);
+            previous_line = Some(line_number);
+        }
+
+        let spanned = span.as_slice();
+        let spanned = &spanned[..spanned.find(['\r', '\n']).unwrap_or(spanned.len()).min(line.len() - before.len())];
+
+        let start  = line_index::width(before, unit);
+        let length = (line_index::width(&line[..before.len() + spanned.len()], unit) - start).max(1) as usize;
+
+        // Keep the tabulations so that the underline is aligned, unless
+        // they are expanded.
+        let padding: String = match options.tab_width {
+            Some(_) => " ".repeat(start as usize),
+            None    => before.chars().map(|item| if item == '\t' { '\t' } else { ' ' }).collect()
+        };
+
+        let (marker, color) = if primary { ("^", severity_color) } else { ("-", BLUE) };
+        let mut underline   = marker.repeat(length);
+
+        if let Some(message) = message {
+            underline.push(' ');
+            underline.push_str(message);
+        }
+
+        let _ = writeln!(output, "{} {} {}{}", margin, bar, padding, paint(style, color, &underline));
+    }
+
+    if !diagnostic.notes.is_empty() {
+        let _ = writeln!(output, "{} {}", margin, bar);
+
+        for note in &diagnostic.notes {
+            let _ = writeln!(output, "{} {} {}: {}", margin, paint(style, BLUE, "="), paint(style, BOLD, "note"), note);
+        }
+    }
+
+    for fix in &diagnostic.fixes {
+        let _ = writeln!(output, "{} {} {}: {}", margin, paint(style, BLUE, "="), paint(style, BOLD, "help"), fix.message);
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        render,
+        render_with_source_map,
+        RenderOptions,
+        Style
+    };
+    use diagnostic::Diagnostic;
+    use source_map::SourceMap;
+    use span::Span;
+
+    use nom::Slice;
+
+    #[test]
+    fn case_render_plain() {
+        let source     = "== london ==\nHello\n\n-> lodnon\n";
+        let diagnostic = Diagnostic::error(Span::new_at("lodnon", 23, 4, 4), "unknown divert target".to_string())
+            .with_code("E0012")
+            .with_label("no knot with this name".to_string())
+            .with_secondary_label(Span::new_at("london", 3, 1, 4), "a similar knot".to_string())
+            .with_note("knots are declared with `== name ==`".to_string())
+            .with_fix(Span::new_at("lodnon", 23, 4, 4), "london".to_string(), "did you mean `london`?".to_string());
+
+        assert_eq!(
+            render(&diagnostic, source, Style::Plain),
+            "error[E0012]: unknown divert target
+ --> 4:4
+  |
+1 | == london ==
+  |    ------ a similar knot
+...
+4 | -> lodnon
+  |    ^^^^^^ no knot with this name
+  |
+  = note: knots are declared with `== name ==`
+  = help: did you mean `london`?
+"
+        );
+    }
+
+    #[test]
+    fn case_render_plain_without_code_and_labels() {
+        let source     = "\tHello } world";
+        let diagnostic = Diagnostic::warning(Span::new_at("}", 7, 1, 8), "unexpected `}`".to_string());
+
+        assert_eq!(
+            render(&diagnostic, source, Style::Plain),
+            "warning: unexpected `}`
+ --> 1:8
+  |
+1 | \tHello } world
+  | \t      ^
+"
+        );
+    }
+
+    #[test]
+    fn case_render_with_tab_width() {
+        let source     = "\tHello\t} world";
+        let diagnostic = Diagnostic::warning(Span::new_at("}", 7, 1, 8), "unexpected `}`".to_string())
+            .with_secondary_label(Span::new_at("Hello\t", 1, 1, 2), "after this".to_string());
+
+        assert_eq!(
+            render(&diagnostic, source, RenderOptions::new(Style::Plain).with_tab_width(4)),
+            "warning: unexpected `}`
+ --> 1:13
+  |
+1 |     Hello   } world
+  |             ^
+  |     -------- after this
+"
+        );
+    }
+
+    #[test]
+    fn case_render_end_of_input() {
+        let source     = "{x";
+        let diagnostic = Diagnostic::error(Span::new_at("", 2, 1, 3), "expected `}`".to_string());
+
+        assert_eq!(
+            render(&diagnostic, source, Style::Plain),
+            "error: expected `}`
+ --> 1:3
+  |
+1 | {x
+  |   ^
+"
+        );
+    }
+
+    #[test]
+    fn case_render_with_source_map() {
+        let mut source_map = SourceMap::new();
+        let main           = source_map.add("main.ink", "-> london\n".to_string());
+        let newspaper      = source_map.add("newspaper.ink", "Hello\n\n-> lodnon\n".to_string());
+
+        let target     = source_map.span(newspaper).unwrap().slice(10..16);
+        let knot       = source_map.span(main).unwrap().slice(3..9);
+        let diagnostic = Diagnostic::error(target, "unknown divert target".to_string())
+            .with_label("not found".to_string())
+            .with_secondary_label(knot, "a similar target".to_string());
+
+        assert_eq!(
+            render_with_source_map(&diagnostic, &source_map, Style::Plain),
+            "error: unknown divert target
+ --> newspaper.ink:3:4
+  |
+3 | -> lodnon
+  |    ^^^^^^ not found
+ ::: main.ink:1:4
+  |
+1 | -> london
+  |    ------ a similar target
+"
+        );
+    }
+
+    #[test]
+    fn case_render_with_source_map_and_tab_width() {
+        let mut source_map = SourceMap::new();
+        let main           = source_map.add("main.ink", "\t-> london\n".to_string());
+
+        let knot       = source_map.span(main).unwrap().slice(4..10);
+        let diagnostic = Diagnostic::error(knot, "unknown divert target".to_string());
+
+        assert_eq!(
+            render_with_source_map(&diagnostic, &source_map, RenderOptions::new(Style::Plain).with_tab_width(8)),
+            "error: unknown divert target
+ --> main.ink:1:12
+  |
+1 |         -> london
+  |            ^^^^^^
+"
+        );
+        assert!(render_with_source_map(&diagnostic, &source_map, Style::Plain).contains(" --> main.ink:1:5\n"));
+    }
+
+    #[test]
+    fn case_render_colored() {
+        let source     = "Hello }";
+        let diagnostic = Diagnostic::error(Span::new_at("}", 6, 1, 7), "unexpected `}`".to_string());
+        let output     = render(&diagnostic, source, Style::Colored);
+
+        assert!(output.starts_with("\x1b[1;31merror\x1b[0m\x1b[1m: unexpected `}`\x1b[0m\n"));
+        assert!(output.contains("\x1b[1;31m^\x1b[0m"));
+    }
+}
@@ -0,0 +1,408 @@
+use std::error;
+use std::fmt;
+
+use ast::ast::Story;
+use ast::owned::{
+    IntoOwned,
+    OwnedSpan
+};
+use internal::{
+    Context,
+    Error,
+    ErrorKind,
+    ErrorKindExtension
+};
+use span::Span;
+use tokens;
+
+use nom::{
+    InputLength,
+    Slice
+};
+
+/// A token or a construct expected by the parser.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum Expected {
+    /// A particular token, e.g. `}`.
+    Token(&'static str),
+
+    /// A construct, e.g. an identifier.
+    Construct(&'static str)
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Expected::Token(token)         => write!(formatter, "`{}`", token),
+            Expected::Construct(construct) => write!(formatter, "{}", construct)
+        }
+    }
+}
+
+/// An error returned by the parser.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum ParseError<'a> {
+    /// The input does not match any of the expected tokens or constructs.
+    Unexpected {
+        /// The span where the parser has failed.
+        span: Span<'a>,
+
+        /// The expected tokens or constructs. It can be empty if the
+        /// parser does not know.
+        expected: Vec<Expected>
+    },
+
+    /// The input is recognized but is not valid, e.g. an integer that
+    /// overflows.
+    Invalid {
+        /// The span of the invalid input.
+        span: Span<'a>,
+
+        /// The reason.
+        kind: ErrorKindExtension
+    }
+}
+
+impl<'a> ParseError<'a> {
+    /// Convert an error returned by the rules on `input` into a parse error.
+    ///
+    /// An incomplete input is reported at the end of `input`.
+    pub fn from_nom(input: Span<'a>, error: Error<Span<'a>>) -> Self {
+        let (span, kind) = match error {
+            Error::Error(Context::Code(span, kind)) | Error::Failure(Context::Code(span, kind)) => (span, kind),
+            Error::Incomplete(_) => {
+                return ParseError::Unexpected {
+                    span    : input.slice(input.input_len()..),
+                    expected: vec![]
+                };
+            }
+        };
+
+        if let ErrorKind::Custom(code) = kind {
+            if let Some(extension) = ErrorKindExtension::from_code(code) {
+                return match expected(&extension) {
+                    Some(expected) => {
+                        ParseError::Unexpected {
+                            span,
+                            expected: vec![expected]
+                        }
+                    },
+
+                    None => {
+                        ParseError::Invalid {
+                            span,
+                            kind: extension
+                        }
+                    }
+                };
+            }
+        }
+
+        let expected = match kind {
+            ErrorKind::Digit    => vec![Expected::Construct("digit")],
+            ErrorKind::HexDigit => vec![Expected::Construct("hexadecimal digit")],
+            ErrorKind::OctDigit => vec![Expected::Construct("octal digit")],
+            ErrorKind::Eof      => vec![Expected::Construct("end of input")],
+            _                   => vec![]
+        };
+
+        ParseError::Unexpected {
+            span,
+            expected
+        }
+    }
+
+    /// The span where the parser has failed.
+    pub fn span(&self) -> Span<'a> {
+        match *self {
+            ParseError::Unexpected { span, .. } | ParseError::Invalid { span, .. } => span
+        }
+    }
+
+    /// The expected tokens or constructs.
+    pub fn expected(&self) -> &[Expected] {
+        match *self {
+            ParseError::Unexpected { ref expected, .. } => expected,
+            ParseError::Invalid { .. }                  => &[]
+        }
+    }
+
+    /// A human-readable message, without the position.
+    pub fn message(&self) -> String {
+        match *self {
+            ParseError::Unexpected { span, ref expected } => unexpected_message(span.as_slice(), expected),
+            ParseError::Invalid { ref kind, .. }          => invalid_message(kind).to_string()
+        }
+    }
+}
+
+/// The message of an unexpected input starting with `found`.
+fn unexpected_message(found: &str, expected: &[Expected]) -> String {
+    let found = match found.chars().next() {
+        Some('\n') | Some('\r') => "end of line".to_string(),
+        Some(item)              => format!("`{}`", item),
+        None                    => "end of input".to_string()
+    };
+
+    match expected.split_last() {
+        None => format!("unexpected {}", found),
+        Some((last, [])) => format!("expected {}, found {}", last, found),
+        Some((last, rest)) => {
+            let rest: Vec<String> = rest.iter().map(ToString::to_string).collect();
+
+            format!("expected {} or {}, found {}", rest.join(", "), last, found)
+        }
+    }
+}
+
+/// The token or the construct expected by an extended error kind, if it is
+/// not an invalid input.
+fn expected(kind: &ErrorKindExtension) -> Option<Expected> {
+    match *kind {
+        ErrorKindExtension::BinaryDigit                   => Some(Expected::Construct("binary digit")),
+        ErrorKindExtension::Identifier                    => Some(Expected::Construct("identifier")),
+        ErrorKindExtension::StringTooShort
+        | ErrorKindExtension::StringInvalidOpeningCharacter => Some(Expected::Construct("string")),
+        ErrorKindExtension::Expression                    => Some(Expected::Construct("expression")),
+        ErrorKindExtension::RightCurlyBracket             => Some(Expected::Token(tokens::RIGHT_CURLY_BRACKET)),
+        ErrorKindExtension::RightParenthesis              => Some(Expected::Token(tokens::RIGHT_PARENTHESIS)),
+        ErrorKindExtension::RightSquareBracket            => Some(Expected::Token(tokens::RIGHT_SQUARE_BRACKET)),
+        ErrorKindExtension::StringInvalidEncoding
+        | ErrorKindExtension::IntegerOverflow
+        | ErrorKindExtension::RealOverflow
+        | ErrorKindExtension::ExtendedSyntax              => None
+    }
+}
+
+/// The message of an extended error kind describing an invalid input.
+fn invalid_message(kind: &ErrorKindExtension) -> &str {
+    match *kind {
+        ErrorKindExtension::StringInvalidEncoding => "invalid string encoding, expected UTF-8",
+        ErrorKindExtension::IntegerOverflow       => "integer literal is too large",
+        ErrorKindExtension::RealOverflow          => "real literal is too large",
+        ErrorKindExtension::ExtendedSyntax        => "syntax only supported by the extended dialect",
+        _                                         => kind.description()
+    }
+}
+
+impl<'a> fmt::Display for ParseError<'a> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let span = self.span();
+
+        write!(formatter, "{}:{}: {}", span.line(), span.column(), self.message())
+    }
+}
+
+impl<'a> error::Error for ParseError<'a> {}
+
+impl<'a> IntoOwned for ParseError<'a> {
+    type Owned = OwnedParseError;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            ParseError::Unexpected { span, expected } => {
+                OwnedParseError::Unexpected {
+                    span: span.into_owned(),
+                    expected
+                }
+            },
+
+            ParseError::Invalid { span, kind } => {
+                OwnedParseError::Invalid {
+                    span: span.into_owned(),
+                    kind
+                }
+            }
+        }
+    }
+}
+
+/// An owned `ParseError`, independent of the lifetime of the source, see
+/// `ast::owned`. It has the same variants and the same message.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum OwnedParseError {
+    /// See `ParseError::Unexpected`.
+    Unexpected {
+        /// The span where the parser has failed.
+        span: OwnedSpan,
+
+        /// The expected tokens or constructs.
+        expected: Vec<Expected>
+    },
+
+    /// See `ParseError::Invalid`.
+    Invalid {
+        /// The span of the invalid input.
+        span: OwnedSpan,
+
+        /// The reason.
+        kind: ErrorKindExtension
+    }
+}
+
+impl OwnedParseError {
+    /// The span where the parser has failed.
+    pub fn span(&self) -> &OwnedSpan {
+        match *self {
+            OwnedParseError::Unexpected { ref span, .. } | OwnedParseError::Invalid { ref span, .. } => span
+        }
+    }
+
+    /// The expected tokens or constructs.
+    pub fn expected(&self) -> &[Expected] {
+        match *self {
+            OwnedParseError::Unexpected { ref expected, .. } => expected,
+            OwnedParseError::Invalid { .. }                  => &[]
+        }
+    }
+
+    /// A human-readable message, without the position, see
+    /// `ParseError::message`.
+    pub fn message(&self) -> String {
+        match *self {
+            OwnedParseError::Unexpected { ref span, ref expected } => unexpected_message(span.as_slice(), expected),
+            OwnedParseError::Invalid { ref kind, .. }              => invalid_message(kind).to_string()
+        }
+    }
+}
+
+impl fmt::Display for OwnedParseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let span = self.span();
+
+        write!(formatter, "{}:{}: {}", span.line, span.column, self.message())
+    }
+}
+
+impl error::Error for OwnedParseError {}
+
+/// Every token and construct that the parser can expect, to deserialize an
+/// `Expected` back into its static string.
+#[cfg(feature = "serde")]
+const EXPECTED: &[Expected] = &[
+    Expected::Construct("binary digit"),
+    Expected::Construct("digit"),
+    Expected::Construct("end of input"),
+    Expected::Construct("expression"),
+    Expected::Construct("hexadecimal digit"),
+    Expected::Construct("identifier"),
+    Expected::Construct("octal digit"),
+    Expected::Construct("string"),
+    Expected::Token(tokens::RIGHT_CURLY_BRACKET),
+    Expected::Token(tokens::RIGHT_PARENTHESIS),
+    Expected::Token(tokens::RIGHT_SQUARE_BRACKET)
+];
+
+/// An expected token or construct is deserialized from the schema of its
+/// derived `Serialize`, e.g. `{ "Token": "}" }`. It fails if the parser
+/// never expects it.
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for Expected {
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        #[derive(Deserialize)]
+        enum Name {
+            Token(String),
+            Construct(String)
+        }
+
+        let (token, name) = match Name::deserialize(deserializer)? {
+            Name::Token(name)     => (true, name),
+            Name::Construct(name) => (false, name)
+        };
+
+        EXPECTED
+            .iter()
+            .find(
+                |&&expected| match expected {
+                    Expected::Token(value)     => token && value == name,
+                    Expected::Construct(value) => !token && value == name
+                }
+            )
+            .cloned()
+            .ok_or_else(|| D::Error::custom("unknown expected token or construct"))
+    }
+}
+
+/// The result of parsing a whole story.
+pub type ParseResult<'a> = Result<Story<'a>, ParseError<'a>>;
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Expected,
+        ParseError
+    };
+    use internal::{
+        Context,
+        Error,
+        ErrorKind,
+        ErrorKindExtension,
+        Needed
+    };
+    use span::Span;
+
+    #[test]
+    fn case_from_nom_error() {
+        let input = Span::new("== }");
+        let span  = Span::new_at("}", 3, 1, 4);
+        let error = ParseError::from_nom(input, Error::Error(Context::Code(span, ErrorKind::Custom(ErrorKindExtension::Identifier as u32))));
+
+        assert_eq!(error, ParseError::Unexpected { span, expected: vec![Expected::Construct("identifier")] });
+        assert_eq!(error.message(), "expected identifier, found `}`");
+        assert_eq!(error.to_string(), "1:4: expected identifier, found `}`");
+    }
+
+    #[test]
+    fn case_from_nom_failure() {
+        let input = Span::new("0x2a");
+        let span  = Span::new_at("0x", 0, 1, 1);
+        let error = ParseError::from_nom(input, Error::Failure(Context::Code(span, ErrorKind::Custom(ErrorKindExtension::ExtendedSyntax as u32))));
+
+        assert_eq!(error, ParseError::Invalid { span, kind: ErrorKindExtension::ExtendedSyntax });
+        assert_eq!(error.expected(), &[]);
+        assert_eq!(error.to_string(), "1:1: syntax only supported by the extended dialect");
+    }
+
+    #[test]
+    fn case_from_nom_incomplete() {
+        let input = Span::new("foo");
+        let error = ParseError::from_nom(input, Error::Incomplete(Needed::Size(1)));
+
+        assert_eq!(error.span(), Span::new_at("", 3, 1, 4));
+        assert_eq!(error.message(), "unexpected end of input");
+    }
+
+    #[test]
+    fn case_message_many_expected() {
+        let error = ParseError::Unexpected {
+            span    : Span::new("\n"),
+            expected: vec![Expected::Token("}"), Expected::Token("-"), Expected::Construct("end of input")]
+        };
+
+        assert_eq!(error.message(), "expected `}`, `-` or end of input, found end of line");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn case_deserialize_expected() {
+        use super::{
+            expected,
+            EXPECTED
+        };
+        use serde_json;
+
+        for kind in (0..).map(ErrorKindExtension::from_code).take_while(Option::is_some) {
+            if let Some(expected) = expected(&kind.unwrap()) {
+                assert!(EXPECTED.contains(&expected), "{} cannot be deserialized", expected);
+            }
+        }
+
+        assert_eq!(serde_json::from_str::<Expected>(r#"{ "Token": "}" }"#).unwrap(), Expected::Token("}"));
+        assert!(serde_json::from_str::<Expected>(r#"{ "Token": "!" }"#).is_err());
+    }
+}
@@ -13,7 +13,8 @@ pub use nom::Context;
 /// Contain information on needed data if a parser_old returned `Incomplete`.
 pub use nom::Needed;
 
-#[derive(Debug,PartialEq,Eq,Hash,Clone)]
+#[derive(Debug,PartialEq,Eq,Hash,Clone,Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ErrorKindExtension {
     BinaryDigit,
 
@@ -27,18 +28,87 @@ pub enum ErrorKindExtension {
     StringInvalidEncoding,
 
     // Invalid identifier
-    Identifier
+    Identifier,
+
+    /// The integer literal does not fit in the integer type.
+    IntegerOverflow,
+
+    /// The real literal is too large for the real type.
+    RealOverflow,
+
+    /// The syntax is only accepted by the extended dialect.
+    ExtendedSyntax,
+
+    /// An expression is expected, e.g. after `~ x =`.
+    Expression,
+
+    /// A right curly bracket is expected to close an interpolation or a
+    /// block.
+    RightCurlyBracket,
+
+    /// A right parenthesis is expected to close a group.
+    RightParenthesis,
+
+    /// A right square bracket is expected to close the choice-only content
+    /// of a choice.
+    RightSquareBracket
 
 }
 
 impl ErrorKindExtension {
+    /// Get the error kind from the code of `ErrorKind::Custom`.
+    pub fn from_code(code: u32) -> Option<Self> {
+        [
+            ErrorKindExtension::BinaryDigit,
+            ErrorKindExtension::StringTooShort,
+            ErrorKindExtension::StringInvalidOpeningCharacter,
+            ErrorKindExtension::StringInvalidEncoding,
+            ErrorKindExtension::Identifier,
+            ErrorKindExtension::IntegerOverflow,
+            ErrorKindExtension::RealOverflow,
+            ErrorKindExtension::ExtendedSyntax,
+            ErrorKindExtension::Expression,
+            ErrorKindExtension::RightCurlyBracket,
+            ErrorKindExtension::RightParenthesis,
+            ErrorKindExtension::RightSquareBracket
+        ]
+            .iter()
+            .find(|&&kind| kind as u32 == code)
+            .cloned()
+    }
+
     pub fn description(&self) -> &str {
         match *self {
             ErrorKindExtension::BinaryDigit                         => "Binary digit",
             ErrorKindExtension::StringTooShort                      => "String too short",
             ErrorKindExtension::StringInvalidOpeningCharacter       => "String invalid opening character",
             ErrorKindExtension::StringInvalidEncoding               => "String invalid encoding",
-            ErrorKindExtension::Identifier                          => "Invalid identifier"
+            ErrorKindExtension::Identifier                          => "Invalid identifier",
+            ErrorKindExtension::IntegerOverflow                     => "Integer overflow",
+            ErrorKindExtension::RealOverflow                        => "Real overflow",
+            ErrorKindExtension::ExtendedSyntax                      => "Syntax not supported by Ink, only by the extended dialect",
+            ErrorKindExtension::Expression                          => "Expression expected",
+            ErrorKindExtension::RightCurlyBracket                   => "Right curly bracket expected",
+            ErrorKindExtension::RightParenthesis                    => "Right parenthesis expected",
+            ErrorKindExtension::RightSquareBracket                  => "Right square bracket expected"
+        }
+    }
+
+    /// Get the stable diagnostic code, see `diagnostic::code::REGISTRY`.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            ErrorKindExtension::BinaryDigit
+            | ErrorKindExtension::StringTooShort
+            | ErrorKindExtension::StringInvalidOpeningCharacter
+            | ErrorKindExtension::Identifier
+            | ErrorKindExtension::Expression
+            | ErrorKindExtension::RightCurlyBracket
+            | ErrorKindExtension::RightParenthesis
+            | ErrorKindExtension::RightSquareBracket                => "INK0001",
+            ErrorKindExtension::StringInvalidEncoding               => "INK0002",
+            ErrorKindExtension::IntegerOverflow                     => "INK0003",
+            ErrorKindExtension::ExtendedSyntax                      => "INK0004",
+            ErrorKindExtension::RealOverflow                        => "INK0009"
         }
     }
 }
@@ -6,20 +6,247 @@ extern crate nom;
 
 extern crate memchr;
 
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+
+#[cfg(all(test, feature = "serde"))]
+#[macro_use]
+extern crate serde_json;
+
 #[macro_use]
 pub mod macros;
 
-mod ast;
-mod tokens;
-mod rules;
+pub mod ast;
+pub mod cst;
+pub mod diagnostic;
+pub mod error;
+pub mod tokens;
+pub mod rules;
 mod internal;
-mod span;
+pub mod line_index;
+pub mod options;
+pub mod source_map;
+pub mod span;
+mod state;
 
 pub use self::internal::*;
 
+use ast::ast::{
+    Story,
+    Weave
+};
+use diagnostic::Diagnostic;
+use diagnostic::code::Suppressions;
+use error::{
+    ParseError,
+    ParseResult
+};
+use options::ParserOptions;
+use source_map::{
+    FileId,
+    SourceMap
+};
+use span::Span;
+
+use nom::Slice;
+
+/// Parse a whole story.
+///
+/// The lines of the input are indexed once, and the spans of the story find
+/// their lines in this index while they are parsed.
+pub fn parse<'a>(input: &'a str, options: ParserOptions) -> ParseResult<'a> {
+    parse_span(Span::new(input), options)
+}
+
+/// Parse a whole story from a file of a source map. The spans of the story
+/// carry the identifier of the file.
+///
+/// # Panics
+///
+/// Panics if the file does not belong to the source map.
+pub fn parse_file<'m>(source_map: &'m SourceMap, file: FileId, options: ParserOptions) -> ParseResult<'m> {
+    parse_span(file_span(source_map, file), options)
+}
+
+fn file_span(source_map: &SourceMap, file: FileId) -> Span<'_> {
+    source_map.span(file).expect("The file does not belong to the source map.")
+}
+
+/// Parse the story spanned by `span`, with `options` for the duration of
+/// the parse.
+fn parse_span(span: Span, options: ParserOptions) -> ParseResult {
+    state::with_state(span.as_slice(), options, || story(span))
+}
+
+fn story(span: Span) -> ParseResult {
+    rules::story::story(span)
+        .map(|(_, story)| story)
+        .map_err(|error| ParseError::from_nom(span, error))
+}
+
+/// Parse a whole story, recovering from malformed lines.
+///
+/// Return the story, where each malformed line is a `Line::Error`, and the
+/// diagnostics of these lines followed by the unresolved names and the loose
+/// ends of the story (see `diagnostic::names` and `diagnostic::loose_ends`).
+/// The warnings suppressed by the story, with `// ink-allow:` comments, are
+/// removed.
+pub fn parse_with_diagnostics<'a>(input: &'a str, options: ParserOptions) -> (Story<'a>, Vec<Diagnostic<'a>>) {
+    parse_span_with_diagnostics(Span::new(input), options)
+}
+
+/// Parse a whole story from a file of a source map, recovering from
+/// malformed lines, like `parse_with_diagnostics`. The spans of the story
+/// and of the diagnostics carry the identifier of the file.
+///
+/// # Panics
+///
+/// Panics if the file does not belong to the source map.
+pub fn parse_file_with_diagnostics<'m>(source_map: &'m SourceMap, file: FileId, options: ParserOptions) -> (Story<'m>, Vec<Diagnostic<'m>>) {
+    parse_span_with_diagnostics(file_span(source_map, file), options)
+}
+
+fn parse_span_with_diagnostics(span: Span, options: ParserOptions) -> (Story, Vec<Diagnostic>) {
+    let options = ParserOptions {
+        recover: true,
+        ..options
+    };
+
+    state::with_state(span.as_slice(), options, || story_with_diagnostics(span))
+}
+
+fn story_with_diagnostics(span: Span) -> (Story, Vec<Diagnostic>) {
+    match story(span) {
+        Ok(story) => {
+            let mut diagnostics = diagnostic::collect(&story);
+            diagnostics.extend(diagnostic::names::unresolved_names(&story));
+            diagnostics.extend(diagnostic::loose_ends::loose_ends(&story));
+
+            let diagnostics = Suppressions::from_source(span.as_slice()).filter(diagnostics);
+
+            (story, diagnostics)
+        },
+
+        Err(error) => {
+            let story = Story {
+                body : Weave {
+                    lines: Vec::new(),
+                    span : span.slice(..0)
+                },
+                knots: Vec::new(),
+                span
+            };
+
+            (story, vec![Diagnostic::from(error)])
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::{
+        parse,
+        parse_file_with_diagnostics,
+        parse_with_diagnostics
+    };
+    use source_map::SourceMap;
+    use diagnostic::Diagnostic;
+    use error::{
+        Expected,
+        ParseError
+    };
+    use options::{
+        Dialect,
+        ParserOptions
+    };
+    use span::Span;
+
+    use nom::Slice;
+
     #[test]
     fn it_works() {
     }
+
+    #[test]
+    fn case_parse() {
+        assert!(parse("Hello\n== london ==\nWorld\n", ParserOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn case_parse_positions() {
+        let story = parse("Hello\n== london ==\n= first_class\n\tWorld -> END\n", ParserOptions::default()).unwrap();
+        let knot  = &story.knots[0];
+        let body  = knot.stitches[0].body.span;
+
+        assert_eq!((knot.name.line(), knot.name.column()), (2, 4));
+        assert_eq!((knot.stitches[0].name.line(), knot.stitches[0].name.column()), (3, 3));
+        assert_eq!((body.line(), body.column()), (4, 2));
+
+        let end = body.slice(body.as_slice().find("END").unwrap()..);
+
+        assert_eq!((end.line(), end.column()), (4, 11));
+    }
+
+    #[test]
+    fn case_invalid_parse() {
+        let output = Err(ParseError::Unexpected {
+            span    : Span::new_at("}\n", 6, 2, 1),
+            expected: vec![Expected::Construct("end of input")]
+        });
+
+        assert_eq!(parse("Hello\n}\n", ParserOptions::default()), output);
+    }
+
+    #[test]
+    fn case_invalid_parse_expected_tokens() {
+        let interpolation = parse("{x\n", ParserOptions::default()).unwrap_err();
+        let declaration   = parse("~ x = \n", ParserOptions::default()).unwrap_err();
+
+        assert_eq!(interpolation.expected(), &[Expected::Token("}")]);
+        assert_eq!(interpolation.to_string(), "1:3: expected `}`, found end of line");
+        assert_eq!(declaration.expected(), &[Expected::Construct("expression")]);
+        assert_eq!(declaration.to_string(), "1:7: expected expression, found end of line");
+
+        let operand = parse("~ x = (a + )\n", ParserOptions::default()).unwrap_err();
+
+        assert_eq!(operand.to_string(), "1:12: expected expression, found `)`");
+    }
+
+    #[test]
+    fn case_parse_file_with_diagnostics() {
+        let mut source_map = SourceMap::new();
+        source_map.add("main.ink", "-> END\n".to_string());
+        let newspaper      = source_map.add("newspaper.ink", "News }\n-> END\n".to_string());
+
+        let (_, diagnostics) = parse_file_with_diagnostics(&source_map, newspaper, ParserOptions::default());
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].span.file, newspaper);
+        assert_eq!(source_map.locate(&diagnostics[0].span).unwrap().to_string(), "newspaper.ink:1:6");
+    }
+
+    #[test]
+    fn case_parse_with_diagnostics_suppressed_warning() {
+        let (_, diagnostics) = parse_with_diagnostics("// ink-allow: INK0006\nHello\n", ParserOptions::default());
+
+        assert_eq!(diagnostics, vec![]);
+    }
+
+    #[test]
+    fn case_parse_with_diagnostics() {
+        let (story, diagnostics) = parse_with_diagnostics("Hello\n~ x = 0x2a\n}\nWorld\n", ParserOptions::new(Dialect::Strict));
+
+        assert_eq!(story.body.lines.len(), 4);
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic::error(Span::new_at("0x", 12, 2, 7), "syntax only supported by the extended dialect".to_string()).with_code("INK0004"),
+                Diagnostic::error(Span::new_at("}", 17, 3, 1), "unexpected `}`".to_string()).with_code("INK0001"),
+                Diagnostic::warning(Span::new_at("World", 19, 4, 1), "the story may reach the end of the root content without `-> END` or `-> DONE`".to_string())
+                    .with_code("INK0006")
+                    .with_label("the flow runs out here".to_string())
+            ]
+        );
+    }
 }
@@ -0,0 +1,280 @@
+use std::borrow::Cow;
+
+use span::{
+    Position,
+    Span
+};
+
+/// The unit in which columns are counted.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ColumnUnit {
+    /// Bytes of the UTF-8 encoding.
+    Byte,
+
+    /// Unicode scalar values, i.e. Rust `char`s. This is the unit of
+    /// `Span::column`.
+    Char,
+
+    /// Code units of the UTF-16 encoding, as expected by the Language
+    /// Server Protocol.
+    Utf16,
+
+    /// Columns of a display where a tabulation moves to the next multiple
+    /// of `tab_width` columns, and any other character is one column wide.
+    Visual {
+        tab_width: u32
+    }
+}
+
+/// An index of the lines of a source, converting byte offsets to lines and
+/// columns, and back, on demand.
+///
+/// Lines and columns start at 1.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LineIndex<'a> {
+    /// The indexed source.
+    source: &'a str,
+
+    /// The byte offset of the start of each line.
+    line_starts: Cow<'a, [usize]>
+}
+
+impl<'a> LineIndex<'a> {
+    /// Index the lines of a source. A line ends after a `\n`.
+    pub fn new(source: &'a str) -> Self {
+        LineIndex {
+            source,
+            line_starts: Cow::Owned(line_starts(source))
+        }
+    }
+
+    /// Create an index from the starts of the lines of a source, computed
+    /// by `line_starts`.
+    pub fn from_line_starts(source: &'a str, line_starts: &'a [usize]) -> Self {
+        debug_assert!(line_starts.first() == Some(&0), "The first line must start at offset 0.");
+
+        LineIndex {
+            source,
+            line_starts: Cow::Borrowed(line_starts)
+        }
+    }
+
+    /// Get a span covering the whole source, whose lines and columns are
+    /// resolved through this index.
+    pub fn span(&self) -> Span<'_> {
+        Span::new_indexed(self.source, &self.line_starts)
+    }
+
+    /// The number of lines.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// The line of a byte offset.
+    ///
+    /// An offset past the end of the source is on the last line.
+    pub fn line(&self, offset: usize) -> u32 {
+        match self.line_starts.binary_search(&offset) {
+            Ok(index)  => index as u32 + 1,
+            Err(index) => index as u32
+        }
+    }
+
+    /// The byte offset of the start of a line, if the line exists.
+    pub fn line_start(&self, line: u32) -> Option<usize> {
+        (line as usize).checked_sub(1).and_then(|index| self.line_starts.get(index).cloned())
+    }
+
+    /// The column of a byte offset, counted in a unit.
+    ///
+    /// The offset must be on a character boundary of the source.
+    pub fn column(&self, offset: usize, unit: ColumnUnit) -> u32 {
+        let offset = offset.min(self.source.len());
+        let start  = self.line_starts[self.line(offset) as usize - 1];
+
+        width(&self.source[start..offset], unit) + 1
+    }
+
+    /// The position of a byte offset, with a column counted in a unit.
+    pub fn position(&self, offset: usize, unit: ColumnUnit) -> Position {
+        Position {
+            offset,
+            line  : self.line(offset),
+            column: self.column(offset, unit)
+        }
+    }
+
+    /// The position of the start of a span of the indexed source, with a
+    /// column counted in a unit.
+    ///
+    /// This is the same as `Span::line` and `Span::column` for
+    /// `ColumnUnit::Char`, but the line is found by a binary search even if
+    /// the span was not created by `span`.
+    pub fn locate(&self, span: &Span, unit: ColumnUnit) -> Position {
+        self.position(span.offset, unit)
+    }
+
+    /// The byte offset of a line and of a column counted in a unit, if the
+    /// line exists.
+    ///
+    /// A column past the end of the line is clamped to the end of the line,
+    /// before its line ending. A column inside a character, e.g. between the
+    /// two UTF-16 code units of a surrogate pair, is moved to the start of
+    /// the character.
+    pub fn offset(&self, line: u32, column: u32, unit: ColumnUnit) -> Option<usize> {
+        let start = self.line_start(line)?;
+        let end   = self.line_starts.get(line as usize).map_or(self.source.len(), |&end| end);
+        let text  = self.source[start..end].trim_end_matches(['\r', '\n']);
+
+        let target       = column.saturating_sub(1);
+        let mut measured = 0;
+
+        for (index, item) in text.char_indices() {
+            let next = measured + measure_char(item, measured, unit);
+
+            if target < next {
+                return Some(start + index);
+            }
+
+            measured = next;
+        }
+
+        Some(start + text.len())
+    }
+}
+
+/// The byte offsets of the starts of the lines of a source. A line ends
+/// after a `\n`.
+pub fn line_starts(source: &str) -> Vec<usize> {
+    let mut line_starts = vec![0];
+    line_starts.extend(source.match_indices('\n').map(|(index, _)| index + 1));
+
+    line_starts
+}
+
+/// The length of a text starting a line, counted in a unit.
+///
+/// The text must start a line for `ColumnUnit::Visual`, since the width of
+/// a tabulation depends on the column it is at.
+pub fn width(text: &str, unit: ColumnUnit) -> u32 {
+    match unit {
+        ColumnUnit::Byte          => text.len() as u32,
+        ColumnUnit::Char          => text.chars().count() as u32,
+        ColumnUnit::Utf16         => text.chars().map(char::len_utf16).sum::<usize>() as u32,
+        ColumnUnit::Visual { .. } => text.chars().fold(0, |column, item| column + measure_char(item, column, unit))
+    }
+}
+
+/// The length of a character at a column (starting at 0), counted in a
+/// unit.
+fn measure_char(item: char, column: u32, unit: ColumnUnit) -> u32 {
+    match unit {
+        ColumnUnit::Byte  => item.len_utf8() as u32,
+        ColumnUnit::Char  => 1,
+        ColumnUnit::Utf16 => item.len_utf16() as u32,
+
+        ColumnUnit::Visual { tab_width } => {
+            let tab_width = tab_width.max(1);
+
+            if item == '\t' { tab_width - column % tab_width } else { 1 }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        line_starts,
+        width,
+        ColumnUnit,
+        LineIndex
+    };
+    use span::Span;
+
+    use nom::Slice;
+
+    #[test]
+    fn case_line_index_lines() {
+        let index = LineIndex::new("foo\nbar\r\n\nbaz");
+
+        assert_eq!(index.line_count(), 4);
+        assert_eq!(index.line(0), 1);
+        assert_eq!(index.line(3), 1);
+        assert_eq!(index.line(4), 2);
+        assert_eq!(index.line(9), 3);
+        assert_eq!(index.line(10), 4);
+        assert_eq!(index.line(42), 4);
+        assert_eq!(index.line_start(2), Some(4));
+        assert_eq!(index.line_start(0), None);
+        assert_eq!(index.line_start(5), None);
+    }
+
+    #[test]
+    fn case_line_index_columns() {
+        // `é` is 2 bytes and 1 UTF-16 code unit, `𝄞` is 4 bytes and 2 UTF-16
+        // code units.
+        let index  = LineIndex::new("x\né𝄞 = 1");
+        let offset = 2 + "é𝄞 ".len();
+
+        assert_eq!(index.column(offset, ColumnUnit::Byte), 8);
+        assert_eq!(index.column(offset, ColumnUnit::Char), 4);
+        assert_eq!(index.column(offset, ColumnUnit::Utf16), 5);
+        assert_eq!(index.position(offset, ColumnUnit::Utf16).line, 2);
+    }
+
+    #[test]
+    fn case_line_index_offsets() {
+        let index = LineIndex::new("x\né𝄞 = 1\r\ny");
+
+        assert_eq!(index.offset(2, 5, ColumnUnit::Utf16), Some(2 + "é𝄞 ".len()));
+        assert_eq!(index.offset(2, 4, ColumnUnit::Char), Some(2 + "é𝄞 ".len()));
+        assert_eq!(index.offset(2, 8, ColumnUnit::Byte), Some(2 + "é𝄞 ".len()));
+        assert_eq!(index.offset(2, 3, ColumnUnit::Utf16), Some(2 + "é".len()));
+        assert_eq!(index.offset(2, 42, ColumnUnit::Char), Some(2 + "é𝄞 = 1".len()));
+        assert_eq!(index.offset(3, 1, ColumnUnit::Char), Some(14));
+        assert_eq!(index.offset(4, 1, ColumnUnit::Char), None);
+    }
+
+    #[test]
+    fn case_line_index_visual_columns() {
+        let index  = LineIndex::new("x\n\tab\tc = 1");
+        let visual = ColumnUnit::Visual { tab_width: 4 };
+
+        assert_eq!(index.column(2, visual), 1);
+        assert_eq!(index.column(3, visual), 5);
+        assert_eq!(index.column(5, visual), 7);
+        assert_eq!(index.column(6, visual), 9);
+        assert_eq!(index.column(6, ColumnUnit::Char), 5);
+        assert_eq!(index.column(6, ColumnUnit::Visual { tab_width: 8 }), 17);
+        assert_eq!(index.offset(2, 3, visual), Some(2));
+        assert_eq!(index.offset(2, 5, visual), Some(3));
+        assert_eq!(index.offset(2, 8, visual), Some(5));
+        assert_eq!(index.offset(2, 9, visual), Some(6));
+        assert_eq!(width("\tab\t", visual), 8);
+    }
+
+    #[test]
+    fn case_line_index_agrees_with_span() {
+        let source = "héllo\n  wörld = 1";
+        let index  = LineIndex::new(source);
+        let span   = Span::new(source).slice(source.find('=').unwrap()..);
+
+        assert_eq!(span.line(), index.line(span.offset));
+        assert_eq!(span.column(), index.column(span.offset, ColumnUnit::Char));
+        assert_eq!(span.column(), 9);
+        assert_eq!(index.locate(&span, ColumnUnit::Char), span.start());
+        assert_eq!(index.span().slice(span.offset..), span);
+        assert_eq!(index.span().slice(span.offset..).start(), span.start());
+    }
+
+    #[test]
+    fn case_line_index_from_line_starts() {
+        let source      = "foo\nbar\r\n\nbaz";
+        let line_starts = line_starts(source);
+        let index       = LineIndex::from_line_starts(source, &line_starts);
+
+        assert_eq!(line_starts, vec![0, 4, 9, 10]);
+        assert_eq!(index, LineIndex::new(source));
+        assert_eq!(index.position(11, ColumnUnit::Char).column, 2);
+    }
+}
@@ -7,7 +7,7 @@ macro_rules! skip_many0(
   ($i:expr, $submac:ident!( $($args:tt)* )) => (
     {
       use ::std::result::Result::*;
-      use nom::{Err,AtEof,ErrorKind};
+      use nom::{Err,AtEof,ErrorKind,InputLength};
 
       let ret;
       let mut input = $i.clone();
@@ -19,7 +19,7 @@ macro_rules! skip_many0(
             // loop trip must always consume (otherwise infinite loops)
             if i == input {
 
-              if i.at_eof() {
+              if i.at_eof() || i.input_len() == 0 {
                 ret = Ok((input, ()));
               } else {
                 ret = Err(Err::Error(error_position!(input, ErrorKind::Many0)));
@@ -44,7 +44,7 @@ macro_rules! skip_many0(
     }
   );
   ($i:expr, $f:expr) => (
-    skip_many0!($i, call!($f));
+    skip_many0!($i, call!($f))
   );
 );
 
@@ -64,7 +64,7 @@ macro_rules! first(
     );
 
     ($input:expr, $f:expr) => (
-        first!($input, call!($f));
+        first!($input, call!($f))
     );
 );
 
@@ -88,7 +88,33 @@ macro_rules! incomplete (
         }
     );
     ($i:expr, $f:expr) => (
-        incomplete!($i, call!($f));
+        incomplete!($i, call!($f))
+    );
+);
+
+/// `expect!(ErrorKindExtension, I -> Result<I, O>) => I -> Result<I, O>`
+/// turns an error or an incomplete input of the child parser into a
+/// failure at the current position, with the extended error kind, so that
+/// no alternative is tried and the error tells what was expected. A failure
+/// of the child parser is kept since it is more precise.
+#[macro_export]
+macro_rules! expect (
+    ($i:expr, $kind:expr, $submac:ident!( $($args:tt)* )) => (
+        {
+            use ::std::result::Result::*;
+            use nom::{Err,ErrorKind};
+
+            let i_ = $i.clone();
+            match $submac!(i_, $($args)*) {
+                Err(Err::Error(_)) | Err(Err::Incomplete(_)) => {
+                    Err(Err::Failure(error_position!($i, ErrorKind::Custom($kind as u32))))
+                },
+                rest => rest
+            }
+        }
+    );
+    ($i:expr, $kind:expr, $f:expr) => (
+        expect!($i, $kind, call!($f))
     );
 );
 
@@ -0,0 +1,51 @@
+/// The dialect of the language accepted by the parser.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum Dialect {
+    /// Only the syntax accepted by the official Ink compiler.
+    Strict,
+
+    /// The Ink syntax with C-style extras: binary (`0b`), hexadecimal
+    /// (`0x`) and octal (`0…`) integers, bitwise operators
+    /// (`~ & | ^ << >>`), and increment and decrement operators (`x++`,
+    /// `x--`) inside expressions.
+    #[default]
+    Extended
+}
+
+/// The options of the parser.
+///
+/// The options are given to `parse`, and every rule reads them from the
+/// state of the parse, so that the spans do not carry them.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub struct ParserOptions {
+    /// The dialect of the language.
+    pub dialect: Dialect,
+
+    /// Whether the parser recovers from a malformed line. When it does,
+    /// the line is replaced by a `Line::Error` and the parser resumes at
+    /// the next line, otherwise the parser stops with an error.
+    pub recover: bool
+}
+
+impl ParserOptions {
+    /// Create options for a particular dialect.
+    pub fn new(dialect: Dialect) -> Self {
+        ParserOptions {
+            dialect,
+            recover: false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Dialect,
+        ParserOptions
+    };
+
+    #[test]
+    fn case_default_options() {
+        assert_eq!(ParserOptions::default(), ParserOptions::new(Dialect::Extended));
+    }
+}
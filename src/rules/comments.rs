@@ -1,5 +1,12 @@
 use super::super::tokens;
 use super::super::span::Span;
+use super::super::ast::ast::Annotation;
+use super::identifier::is_identifier;
+
+use nom::{
+    IResult,
+    Slice
+};
 
 named_attr!(
     #[doc="
@@ -13,36 +20,88 @@ named_attr!(
     )
 );
 
-named!(
-    comment_delimited<Span, Span>,
+named_attr!(
+    #[doc="
+        Recognize a delimited block comment (`/* … */`).
+    "],
+    pub comment_delimited<Span, Span>,
     preceded!(
         tag!(tokens::BLOCK_COMMENT_OPEN),
-        complete!(take_until_and_consume_s!(tokens::BLOCK_COMMENT_CLOSE))
+        complete!(take_until_and_consume!(tokens::BLOCK_COMMENT_CLOSE))
     )
 );
 
-named!(
-    comment_single_line<Span, Span>,
+named_attr!(
+    #[doc="
+        Recognize a single line comment (`// …`), including its line ending.
+    "],
+    pub comment_single_line<Span, Span>,
     preceded!(
         tag!(tokens::INLINE_COMMENT),
         incomplete!(take_until_endline_and_consume!())
     )
 );
 
+#[inline]
+fn is_blank(chr: char) -> bool {
+    chr == ' ' || chr == '\t'
+}
+
+/// Recognize a `TODO` annotation, either as a line (`TODO: …`) or as a
+/// single line comment (`// TODO …`). The line ending is not consumed.
+pub fn todo(input: Span) -> IResult<Span, Annotation> {
+    use nom::{
+        Err,
+        ErrorKind
+    };
+
+    let slice = input.as_slice();
+
+    let tail = match slice.strip_prefix(tokens::INLINE_COMMENT) {
+        Some(comment) => {
+            comment
+                .trim_start_matches(is_blank)
+                .strip_prefix(tokens::TODO)
+                .filter(|tail| !tail.starts_with(is_identifier))
+        },
+
+        None => {
+            slice
+                .strip_prefix(tokens::TODO)
+                .filter(|tail| tail.starts_with(tokens::COLON))
+        }
+    };
+
+    let length = tail.map(|tail| slice.len() - tail.strip_prefix(tokens::COLON).unwrap_or(tail).len());
+
+    match length {
+        Some(length) => {
+            let message = &slice[length..];
+            let start   = length + message.len() - message.trim_start_matches(is_blank).len();
+            let end     = slice[start..].find(['\r', '\n']).map_or(slice.len(), |end| start + end);
+
+            Ok((input.slice(end..), Annotation::Todo(input.slice(start..end))))
+        },
+
+        None => Err(Err::Error(error_position!(input, ErrorKind::Tag)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
         comment,
         comment_delimited,
-        comment_single_line
+        comment_single_line,
+        todo
     };
+    use super::super::super::ast::ast::Annotation;
     use super::super::super::internal::{
         Context,
         Error,
         ErrorKind,
     };
     use super::super::super::span::Span;
-    use nom::Needed;
 
     #[test]
     fn case_comment_single_line_double_slash_empty() {
@@ -133,4 +192,49 @@ mod tests {
         assert_eq!(comment(input), Err(Error::Error(Context::Code(Span::new_at("/*foobar", 0, 1, 1), ErrorKind::Alt))));
     }
 
+    #[test]
+    fn case_todo() {
+        let input  = Span::new("TODO: describe the room\nfoo");
+        let output = Ok((Span::new_at("\nfoo", 23, 1, 24), Annotation::Todo(Span::new_at("describe the room", 6, 1, 7))));
+
+        assert_eq!(todo(input), output);
+    }
+
+    #[test]
+    fn case_todo_comment() {
+        let input  = Span::new("// TODO describe the room\r\nfoo");
+        let output = Ok((Span::new_at("\r\nfoo", 25, 1, 26), Annotation::Todo(Span::new_at("describe the room", 8, 1, 9))));
+
+        assert_eq!(todo(input), output);
+    }
+
+    #[test]
+    fn case_todo_comment_with_colon() {
+        let input  = Span::new("//TODO: describe the room");
+        let output = Ok((Span::new_at("", 25, 1, 26), Annotation::Todo(Span::new_at("describe the room", 8, 1, 9))));
+
+        assert_eq!(todo(input), output);
+    }
+
+    #[test]
+    fn case_todo_empty() {
+        let input  = Span::new("TODO:\n");
+        let output = Ok((Span::new_at("\n", 5, 1, 6), Annotation::Todo(Span::new_at("", 5, 1, 6))));
+
+        assert_eq!(todo(input), output);
+    }
+
+    #[test]
+    fn case_invalid_todo_without_colon() {
+        let input = Span::new("TODO describe the room");
+
+        assert_eq!(todo(input), Err(Error::Error(Context::Code(input, ErrorKind::Tag))));
+    }
+
+    #[test]
+    fn case_invalid_todo_comment_longer_word() {
+        let input = Span::new("// TODOS are fun");
+
+        assert_eq!(todo(input), Err(Error::Error(Context::Code(input, ErrorKind::Tag))));
+    }
 }
\ No newline at end of file
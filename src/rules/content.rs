@@ -0,0 +1,491 @@
+use span::Span;
+use tokens;
+
+use ast::ast::{
+    Content,
+    ContentPart,
+    Divert,
+    Token
+};
+use internal::ErrorKindExtension;
+
+use rules::expressions::operations::operation;
+use rules::identifier::is_identifier;
+
+use nom::{
+    IResult,
+    Offset,
+    Slice
+};
+
+/// Recognize a line of content.
+/// A line of content is a sequence of texts and interpolations, e.g.
+/// `Hello {name}!`. The trailing newline is not consumed.
+pub fn content(input: Span) -> IResult<Span, Content> {
+    parts(input, content_part)
+}
+
+named!(
+    content_part<Span, ContentPart>,
+    alt_complete!(
+        interpolation
+        | divert
+        | text
+    )
+);
+
+/// Recognize one or more parts of content.
+///
+/// Unlike `many1!`, the failure of the first part is returned as is, so that
+/// a malformed interpolation or divert starting the content is reported
+/// where it is malformed.
+fn parts(input: Span, part: fn(Span) -> IResult<Span, ContentPart>) -> IResult<Span, Content> {
+    let (input, first) = part(input)?;
+    let (input, mut parts) = many0!(input, call!(part))?;
+
+    parts.insert(0, first);
+
+    Ok((input, Content(parts)))
+}
+
+/// Recognize an interpolation, i.e. an expression surrounded by curly
+/// brackets whose value is printed, e.g. `{x + 1}`.
+pub fn interpolation(input: Span) -> IResult<Span, ContentPart> {
+    let (next_input, expression) = delimited!(
+        input,
+        tag!(tokens::LEFT_CURLY_BRACKET),
+        first!(expect!(ErrorKindExtension::Expression, operation)),
+        first!(expect!(ErrorKindExtension::RightCurlyBracket, tag!(tokens::RIGHT_CURLY_BRACKET)))
+    )?;
+    let length = input.offset(&next_input);
+
+    Ok((next_input, ContentPart::Interpolation(Token::new(expression, input.slice(..length)))))
+}
+
+/// Recognize a divert, e.g. `-> london`, `-> london.first_class`, `-> END`
+/// or `-> DONE`.
+///
+/// A malformed target is a failure: the arrow is never a text.
+pub fn divert(input: Span) -> IResult<Span, ContentPart> {
+    use nom::{
+        Err,
+        ErrorKind
+    };
+
+    let slice = input.as_slice();
+    let tail  = match slice.strip_prefix(tokens::DIVERT) {
+        Some(tail) if !slice.starts_with(tokens::TUNNEL_END) => tail,
+        _                                                    => return Err(Err::Error(error_position!(input, ErrorKind::Tag)))
+    };
+
+    let mut end  = slice.len() - tail.trim_start_matches([' ', '\t']).len();
+    let mut path = Vec::new();
+
+    loop {
+        let segment = &slice[end..];
+        let length  = segment.find(|item| !is_identifier(item)).unwrap_or(segment.len());
+
+        if length == 0 || segment.starts_with(char::is_numeric) {
+            return Err(Err::Failure(error_position!(input.slice(end..), ErrorKind::Custom(ErrorKindExtension::Identifier as u32))));
+        }
+
+        path.push(input.slice(end..end + length));
+        end += length;
+
+        if !slice[end..].starts_with(tokens::DOT) {
+            break;
+        }
+
+        end += tokens::DOT.len();
+    }
+
+    let target = match path.as_slice() {
+        [name] if name.as_slice() == tokens::END  => Divert::End(*name),
+        [name] if name.as_slice() == tokens::DONE => Divert::Done(*name),
+        _                                         => Divert::Target(path)
+    };
+
+    Ok((input.slice(end..), ContentPart::Divert(Token::new(target, input.slice(..end)))))
+}
+
+/// Recognize the content of a choice.
+/// It is like a line of content, except that square brackets delimit
+/// the choice-only content, e.g. `Hello [back] right back to you!`.
+pub fn choice_content(input: Span) -> IResult<Span, Content> {
+    parts(input, choice_content_part)
+}
+
+named!(
+    choice_content_part<Span, ContentPart>,
+    alt_complete!(
+        interpolation
+        | divert
+        | choice_text
+    )
+);
+
+#[inline]
+pub fn is_text(chr: char) -> bool {
+    chr != '{' && chr != '}' && chr != '\n' && chr != '\r'
+}
+
+#[inline]
+pub fn is_choice_text(chr: char) -> bool {
+    is_text(chr) && chr != '[' && chr != ']'
+}
+
+/// Recognize a run of text, up to the next interpolation, divert, comment or
+/// the end of the line.
+pub fn text(input: Span) -> IResult<Span, ContentPart> {
+    text_while(input, is_text)
+}
+
+/// Recognize a run of text of a choice, up to the next interpolation, divert,
+/// square bracket, comment or the end of the line.
+pub fn choice_text(input: Span) -> IResult<Span, ContentPart> {
+    text_while(input, is_choice_text)
+}
+
+fn text_while(input: Span, predicate: fn(char) -> bool) -> IResult<Span, ContentPart> {
+    use nom::{
+        Err,
+        ErrorKind
+    };
+
+    let slice = input.as_slice();
+    let mut length = slice.len();
+    let mut iterator = slice.char_indices().peekable();
+
+    while let Some((index, item)) = iterator.next() {
+        let is_comment = item == '/' && matches!(iterator.peek(), Some(&(_, '/')) | Some(&(_, '*')));
+        let is_divert  = item == '-' && matches!(iterator.peek(), Some(&(_, '>')));
+
+        if !predicate(item) || is_comment || is_divert {
+            length = index;
+            break;
+        }
+    }
+
+    if length == 0 {
+        return Err(Err::Error(error_position!(input, ErrorKind::IsNot)));
+    }
+
+    Ok((input.slice(length..), ContentPart::Text(input.slice(..length))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        content,
+        choice_content,
+        divert,
+        interpolation,
+        text
+    };
+
+    use ast::ast::{
+        BinaryOperator,
+        Content,
+        ContentPart,
+        Divert,
+        Expression,
+        Literal,
+        NAryOperation,
+        Token,
+        UnaryOperator,
+        Variable
+    };
+
+    use internal::{
+        Context,
+        Error,
+        ErrorKind,
+        ErrorKindExtension
+    };
+
+    use span::Span;
+
+    macro_rules! nullary {
+        (variable!($name:expr)) => (
+            NAryOperation::Nullary {
+                operand: Box::new(variable!($name)),
+                span   : $name
+            }
+        );
+
+        ($expression:expr, $span:expr) => (
+            NAryOperation::Nullary {
+                operand: Box::new($expression),
+                span   : $span
+            }
+        )
+    }
+
+    macro_rules! variable {
+        ($name:expr) => (
+            Expression::Variable(Variable($name))
+        )
+    }
+
+    #[test]
+    fn case_text() {
+        let input  = Span::new("Hello world\n");
+        let output = Ok((
+            Span::new_at("\n", 11, 1, 12),
+            ContentPart::Text(Span::new_at("Hello world", 0, 1, 1))
+        ));
+
+        assert_eq!(text(input), output);
+    }
+
+    #[test]
+    fn case_text_without_ending() {
+        let input  = Span::new("Hello world");
+        let output = Ok((
+            Span::new_at("", 11, 1, 12),
+            ContentPart::Text(input)
+        ));
+
+        assert_eq!(text(input), output);
+    }
+
+    #[test]
+    fn case_invalid_text_empty() {
+        let input  = Span::new("");
+        let output = Err(Error::Error(Context::Code(input, ErrorKind::IsNot)));
+
+        assert_eq!(text(input), output);
+    }
+
+    #[test]
+    fn case_divert() {
+        let input  = Span::new("-> london\n");
+        let output = Ok((
+            Span::new_at("\n", 9, 1, 10),
+            ContentPart::Divert(Token::new(Divert::Target(vec![Span::new_at("london", 3, 1, 4)]), Span::new("-> london")))
+        ));
+
+        assert_eq!(divert(input), output);
+    }
+
+    #[test]
+    fn case_divert_stitch_without_ending() {
+        let input  = Span::new("->london.first_class");
+        let output = Ok((
+            Span::new_at("", 20, 1, 21),
+            ContentPart::Divert(
+                Token::new(
+                    Divert::Target(vec![Span::new_at("london", 2, 1, 3), Span::new_at("first_class", 9, 1, 10)]),
+                    Span::new("->london.first_class")
+                )
+            )
+        ));
+
+        assert_eq!(divert(input), output);
+    }
+
+    #[test]
+    fn case_divert_end_and_done() {
+        assert_eq!(divert(Span::new("-> END\n")), Ok((Span::new_at("\n", 6, 1, 7), ContentPart::Divert(Token::new(Divert::End(Span::new_at("END", 3, 1, 4)), Span::new("-> END"))))));
+        assert_eq!(divert(Span::new("-> DONE\n")), Ok((Span::new_at("\n", 7, 1, 8), ContentPart::Divert(Token::new(Divert::Done(Span::new_at("DONE", 3, 1, 4)), Span::new("-> DONE"))))));
+    }
+
+    #[test]
+    fn case_invalid_divert_target() {
+        let input  = Span::new("-> 1st\n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at("1st\n", 3, 1, 4), ErrorKind::Custom(ErrorKindExtension::Identifier as u32))));
+
+        assert_eq!(divert(input), output);
+    }
+
+    #[test]
+    fn case_invalid_divert_tunnel_end() {
+        let input = Span::new("->->\n");
+
+        assert_eq!(divert(input), Err(Error::Error(Context::Code(input, ErrorKind::Tag))));
+    }
+
+    #[test]
+    fn case_content_with_divert() {
+        let input  = Span::new("Hello -> END\n");
+        let output = Ok((
+            Span::new_at("\n", 12, 1, 13),
+            Content(vec![
+                ContentPart::Text(Span::new_at("Hello ", 0, 1, 1)),
+                ContentPart::Divert(Token::new(Divert::End(Span::new_at("END", 9, 1, 10)), Span::new_at("-> END", 6, 1, 7)))
+            ])
+        ));
+
+        assert_eq!(content(input), output);
+    }
+
+    #[test]
+    fn case_interpolation_variable() {
+        let input  = Span::new("{x}");
+        let output = Ok((
+            Span::new_at("", 3, 1, 4),
+            ContentPart::Interpolation(
+                Token::new(
+                    Expression::NAryOperation(nullary!(variable!(Span::new_at("x", 1, 1, 2)))),
+                    Span::new("{x}")
+                )
+            )
+        ));
+
+        assert_eq!(interpolation(input), output);
+    }
+
+    #[test]
+    fn case_interpolation_expression_with_whitespaces() {
+        let input  = Span::new("{  x + 1  }");
+        let output = Ok((
+            Span::new_at("", 11, 1, 12),
+            ContentPart::Interpolation(
+                Token::new(
+                    Expression::NAryOperation(
+                        NAryOperation::Binary {
+                            operator     : Token::new(BinaryOperator::Addition, Span::new_at("+", 5, 1, 6)),
+                            left_operand : Box::new(nullary!(variable!(Span::new_at("x", 3, 1, 4)))),
+                            right_operand: Box::new(nullary!(Expression::Literal(Literal::Integer(Token::new(1, Span::new_at("1", 7, 1, 8)))), Span::new_at("1", 7, 1, 8))),
+                            span         : Span::new_at("x + 1", 3, 1, 4)
+                        }
+                    ),
+                    Span::new("{  x + 1  }")
+                )
+            )
+        ));
+
+        assert_eq!(interpolation(input), output);
+    }
+
+    #[test]
+    fn case_interpolation_negative_number() {
+        let input  = Span::new("{x > -3}");
+        let output = Ok((
+            Span::new_at("", 8, 1, 9),
+            ContentPart::Interpolation(
+                Token::new(
+                    Expression::NAryOperation(
+                        NAryOperation::Binary {
+                            operator     : Token::new(BinaryOperator::GreaterThan, Span::new_at(">", 3, 1, 4)),
+                            left_operand : Box::new(nullary!(variable!(Span::new_at("x", 1, 1, 2)))),
+                            right_operand: Box::new(
+                                NAryOperation::Unary {
+                                    operator: Token::new(UnaryOperator::Minus, Span::new_at("-", 5, 1, 6)),
+                                    operand : Box::new(nullary!(Expression::Literal(Literal::Integer(Token::new(3, Span::new_at("3", 6, 1, 7)))), Span::new_at("3", 6, 1, 7))),
+                                    span    : Span::new_at("-3", 5, 1, 6)
+                                }
+                            ),
+                            span         : Span::new_at("x > -3", 1, 1, 2)
+                        }
+                    ),
+                    Span::new("{x > -3}")
+                )
+            )
+        ));
+
+        assert_eq!(interpolation(input), output);
+    }
+
+    #[test]
+    fn case_invalid_interpolation_not_closed() {
+        let input  = Span::new("{x\n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at("\n", 2, 1, 3), ErrorKind::Custom(ErrorKindExtension::RightCurlyBracket as u32))));
+
+        assert_eq!(interpolation(input), output);
+    }
+
+    #[test]
+    fn case_invalid_interpolation_empty() {
+        let input  = Span::new("{ }\n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at("}\n", 2, 1, 3), ErrorKind::Custom(ErrorKindExtension::Expression as u32))));
+
+        assert_eq!(interpolation(input), output);
+    }
+
+    #[test]
+    fn case_invalid_content_starting_with_malformed_divert() {
+        let input  = Span::new("-> \n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at("\n", 3, 1, 4), ErrorKind::Custom(ErrorKindExtension::Identifier as u32))));
+
+        assert_eq!(content(input), output);
+    }
+
+    #[test]
+    fn case_content() {
+        let input  = Span::new("Hello {name}, you are {age} years old.\nNext");
+        let output = Ok((
+            Span::new_at("\nNext", 38, 1, 39),
+            Content(vec![
+                ContentPart::Text(Span::new_at("Hello ", 0, 1, 1)),
+                ContentPart::Interpolation(
+                    Token::new(
+                        Expression::NAryOperation(nullary!(variable!(Span::new_at("name", 7, 1, 8)))),
+                        Span::new_at("{name}", 6, 1, 7)
+                    )
+                ),
+                ContentPart::Text(Span::new_at(", you are ", 12, 1, 13)),
+                ContentPart::Interpolation(
+                    Token::new(
+                        Expression::NAryOperation(nullary!(variable!(Span::new_at("age", 23, 1, 24)))),
+                        Span::new_at("{age}", 22, 1, 23)
+                    )
+                ),
+                ContentPart::Text(Span::new_at(" years old.", 27, 1, 28))
+            ])
+        ));
+
+        assert_eq!(content(input), output);
+    }
+
+    #[test]
+    fn case_content_only_interpolation() {
+        let input  = Span::new("{x}\n");
+        let output = Ok((
+            Span::new_at("\n", 3, 1, 4),
+            Content(vec![
+                ContentPart::Interpolation(
+                    Token::new(
+                        Expression::NAryOperation(nullary!(variable!(Span::new_at("x", 1, 1, 2)))),
+                        Span::new("{x}")
+                    )
+                )
+            ])
+        ));
+
+        assert_eq!(content(input), output);
+    }
+
+    #[test]
+    fn case_text_until_comment() {
+        let input  = Span::new("Hello // world\n");
+        let output = Ok((
+            Span::new_at("// world\n", 6, 1, 7),
+            ContentPart::Text(Span::new_at("Hello ", 0, 1, 1))
+        ));
+
+        assert_eq!(text(input), output);
+    }
+
+    #[test]
+    fn case_text_with_slash() {
+        let input  = Span::new("and/or\n");
+        let output = Ok((
+            Span::new_at("\n", 6, 1, 7),
+            ContentPart::Text(Span::new_at("and/or", 0, 1, 1))
+        ));
+
+        assert_eq!(text(input), output);
+    }
+
+    #[test]
+    fn case_choice_content() {
+        let input  = Span::new("Hello [back] right back\n");
+        let output = Ok((
+            Span::new_at("[back] right back\n", 6, 1, 7),
+            Content(vec![ContentPart::Text(Span::new_at("Hello ", 0, 1, 1))])
+        ));
+
+        assert_eq!(choice_content(input), output);
+    }
+}
@@ -0,0 +1,60 @@
+use span::Span;
+use state;
+use internal::ErrorKindExtension;
+use options::Dialect;
+
+use nom::IResult;
+
+/// Accept a syntax of the extended dialect, spanned by `syntax`.
+///
+/// With the strict dialect, this is a failure located at `syntax`, so
+/// that the parsers do not try to read the syntax differently.
+pub fn extended_syntax<'a>(input: Span<'a>, syntax: Span<'a>) -> IResult<Span<'a>, ()> {
+    use nom::{
+        Err,
+        ErrorKind
+    };
+
+    match state::options().dialect {
+        Dialect::Extended => Ok((input, ())),
+        Dialect::Strict   => {
+            Err(Err::Failure(error_position!(
+                syntax,
+                ErrorKind::Custom(ErrorKindExtension::ExtendedSyntax as u32)
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::extended_syntax;
+    use internal::{
+        Context,
+        Error,
+        ErrorKind,
+        ErrorKindExtension
+    };
+    use options::{
+        Dialect,
+        ParserOptions
+    };
+    use span::Span;
+    use state;
+
+    #[test]
+    fn case_extended_syntax_in_extended_dialect() {
+        let input  = Span::new("0x2a");
+        let output = Ok((input, ()));
+
+        assert_eq!(extended_syntax(input, input), output);
+    }
+
+    #[test]
+    fn case_invalid_extended_syntax_in_strict_dialect() {
+        let input  = Span::new("0x2a");
+        let output = Err(Error::Failure(Context::Code(input, ErrorKind::Custom(ErrorKindExtension::ExtendedSyntax as u32))));
+
+        assert_eq!(state::with_state(input.as_slice(), ParserOptions::new(Dialect::Strict), || extended_syntax(input, input)), output);
+    }
+}
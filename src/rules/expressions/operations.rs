@@ -4,9 +4,146 @@ use ast::ast::{
     NAryOperation,
     BinaryOperator,
     UnaryOperator,
-    Expression
+    Expression,
+    Token
 };
+use internal::ErrorKindExtension;
+use options::Dialect;
 use super::expression;
+use rules::dialect::extended_syntax;
+use rules::identifier::is_identifier;
+use rules::number::minimum_integer;
+use rules::skip::skip_within_line;
+
+use nom::{
+    Err,
+    ErrorKind,
+    IResult,
+    Offset,
+    Slice
+};
+
+/// The associativity of a binary operator.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Associativity {
+    /// `x op y op z` is `(x op y) op z`.
+    Left,
+
+    /// `x op y op z` is `x op (y op z)`.
+    Right
+}
+
+/// The position of a unary operator relatively to its operand.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Fixity {
+    /// The operator is before its operand, e.g. `!x`.
+    Prefix,
+
+    /// The operator is after its operand, e.g. `x++`.
+    Postfix
+}
+
+/// A binary operator of the operator table.
+#[derive(Debug)]
+pub struct BinaryOperatorDefinition {
+    /// The token of the operator.
+    pub token: &'static str,
+
+    /// The operator.
+    pub operator: BinaryOperator,
+
+    /// The precedence of the operator. An operator binds tighter than the
+    /// operators with a lower precedence.
+    pub precedence: u8,
+
+    /// The associativity of the operator.
+    pub associativity: Associativity,
+
+    /// The dialect from which the operator is accepted.
+    pub dialect: Dialect
+}
+
+/// A unary operator of the operator table.
+#[derive(Debug)]
+pub struct UnaryOperatorDefinition {
+    /// The token of the operator.
+    pub token: &'static str,
+
+    /// The operator.
+    pub operator: UnaryOperator,
+
+    /// The position of the operator relatively to its operand.
+    pub fixity: Fixity,
+
+    /// The dialect from which the operator is accepted.
+    pub dialect: Dialect
+}
+
+const fn binary(token: &'static str, operator: BinaryOperator, precedence: u8, associativity: Associativity, dialect: Dialect) -> BinaryOperatorDefinition {
+    BinaryOperatorDefinition {
+        token,
+        operator,
+        precedence,
+        associativity,
+        dialect
+    }
+}
+
+const fn unary(token: &'static str, operator: UnaryOperator, fixity: Fixity, dialect: Dialect) -> UnaryOperatorDefinition {
+    UnaryOperatorDefinition {
+        token,
+        operator,
+        fixity,
+        dialect
+    }
+}
+
+/// The binary operators, from the lowest to the highest precedence, with
+/// their associativity, e.g. `x - y - z` is `(x - y) - z`.
+///
+/// As in Ink, `^` is the list intersection, and it binds like `has`.
+///
+/// Keywords, e.g. `and`, are recognized only if they are not followed by an
+/// identifier character.
+pub const BINARY_OPERATORS: &[BinaryOperatorDefinition] = &[
+    binary(tokens::BOOLEAN_OR,               BinaryOperator::LogicalOr,            1,  Associativity::Left,  Dialect::Strict),
+    binary(tokens::OR,                       BinaryOperator::LogicalOr,            1,  Associativity::Left,  Dialect::Strict),
+    binary(tokens::BOOLEAN_AND,              BinaryOperator::LogicalAnd,           2,  Associativity::Left,  Dialect::Strict),
+    binary(tokens::AND,                      BinaryOperator::LogicalAnd,           2,  Associativity::Left,  Dialect::Strict),
+    binary(tokens::BITWISE_OR,               BinaryOperator::BitwiseOr,            3,  Associativity::Left,  Dialect::Extended),
+    binary(tokens::BITWISE_AND,              BinaryOperator::BitwiseAnd,           4,  Associativity::Left,  Dialect::Extended),
+    binary(tokens::EQUAL,                    BinaryOperator::Equal,                5,  Associativity::Left,  Dialect::Strict),
+    binary(tokens::NOT_EQUAL,                BinaryOperator::NotEqual,             5,  Associativity::Left,  Dialect::Strict),
+    binary(tokens::LESS_THAN,                BinaryOperator::LessThan,             6,  Associativity::Left,  Dialect::Strict),
+    binary(tokens::LESS_THAN_OR_EQUAL_TO,    BinaryOperator::LessThanOrEqualTo,    6,  Associativity::Left,  Dialect::Strict),
+    binary(tokens::GREATER_THAN,             BinaryOperator::GreaterThan,          6,  Associativity::Left,  Dialect::Strict),
+    binary(tokens::GREATER_THAN_OR_EQUAL_TO, BinaryOperator::GreaterThanOrEqualTo, 6,  Associativity::Left,  Dialect::Strict),
+    binary(tokens::CONTAINS,                 BinaryOperator::Has,                  7,  Associativity::Left,  Dialect::Strict),
+    binary(tokens::HAS,                      BinaryOperator::Has,                  7,  Associativity::Left,  Dialect::Strict),
+    binary(tokens::NOT_CONTAINS,             BinaryOperator::Hasnt,                7,  Associativity::Left,  Dialect::Strict),
+    binary(tokens::HASNT,                    BinaryOperator::Hasnt,                7,  Associativity::Left,  Dialect::Strict),
+    binary(tokens::INTERSECT,                BinaryOperator::Intersection,         7,  Associativity::Left,  Dialect::Strict),
+    binary(tokens::BITWISE_LEFT_SHIFT,       BinaryOperator::BitwiseShiftLeft,     8,  Associativity::Left,  Dialect::Extended),
+    binary(tokens::BITWISE_RIGHT_SHIFT,      BinaryOperator::BitwiseShiftRight,    8,  Associativity::Left,  Dialect::Extended),
+    binary(tokens::ADD,                      BinaryOperator::Addition,             9,  Associativity::Left,  Dialect::Strict),
+    binary(tokens::SUBSTRACT,                BinaryOperator::Subtraction,          9,  Associativity::Left,  Dialect::Strict),
+    binary(tokens::MULTIPLY,                 BinaryOperator::Multiplication,       10, Associativity::Left,  Dialect::Strict),
+    binary(tokens::DIVIDE,                   BinaryOperator::Division,             10, Associativity::Left,  Dialect::Strict),
+    binary(tokens::MODULO,                   BinaryOperator::Modulo,               10, Associativity::Left,  Dialect::Strict),
+    binary(tokens::MOD,                      BinaryOperator::Modulo,               10, Associativity::Left,  Dialect::Strict)
+];
+
+/// The unary operators. They bind tighter than the binary operators, and
+/// postfix operators bind tighter than prefix operators.
+pub const UNARY_OPERATORS: &[UnaryOperatorDefinition] = &[
+    unary(tokens::BOOLEAN_NOT, UnaryOperator::Negate,            Fixity::Prefix,  Dialect::Strict),
+    unary(tokens::NOT,         UnaryOperator::Negate,            Fixity::Prefix,  Dialect::Strict),
+    unary(tokens::SUBSTRACT,   UnaryOperator::Minus,             Fixity::Prefix,  Dialect::Strict),
+    unary(tokens::ADD,         UnaryOperator::Plus,              Fixity::Prefix,  Dialect::Extended),
+    unary(tokens::BITWISE_NOT, UnaryOperator::BitwiseComplement, Fixity::Prefix,  Dialect::Extended),
+    unary(tokens::INCREMENT,   UnaryOperator::Increment,         Fixity::Postfix, Dialect::Extended),
+    unary(tokens::DECREMENT,   UnaryOperator::Decrement,         Fixity::Postfix, Dialect::Extended)
+];
 
 named_attr!(
     #[doc="
@@ -14,7 +151,7 @@ named_attr!(
     "],
     pub operation<Span, Expression>,
     map_res!(
-        logical,
+        call!(binary_operation, 0),
         nary_expression_mapper
     )
 );
@@ -24,294 +161,195 @@ fn nary_expression_mapper<'a>(nary_operation: NAryOperation<'a>) -> Result<Expre
     Ok(Expression::NAryOperation(nary_operation))
 }
 
-macro_rules! left_to_right_binary_operation {
-    (
-        $parser_name:ident:
-        $operand:ident with
-        $operator_token:ident as $operator_representation:ident
-    ) => (
-        named!(
-            $parser_name<Span, NAryOperation>,
-            do_parse!(
-                left_operand: $operand >>
-                result: fold_many0!(
-                    preceded!(
-                        first!(tag!(tokens::$operator_token)),
-                        first!($operand)
-                    ),
-                    left_operand,
-                    |accumulator, right_operand| {
-                        NAryOperation::Binary {
-                            operator     : BinaryOperator::$operator_representation,
-                            left_operand : Box::new(accumulator),
-                            right_operand: Box::new(right_operand)
-                        }
-                    }
-                ) >>
-                (result)
-            )
-        );
-    );
-
-    (
-        $parser_name:ident:
-        $operand:ident with
-        ($($operator_token:ident as $operator_representation:ident),*)
-    ) => (
-        named!(
-            $parser_name<Span, NAryOperation>,
-            do_parse!(
-                left_operand: $operand >>
-                result: fold_many0!(
-                    do_parse!(
-                        operator: first!(
-                            alt_complete!(
-                                $(
-                                    tag!(tokens::$operator_token) => {
-                                        |_| { BinaryOperator::$operator_representation }
-                                    }
-                                )|*
-                            )
-                        ) >>
-                        right_operand: first!($operand) >>
-                        (operator, right_operand)
-                    ),
-                    left_operand,
-                    |accumulator, (operator, right_operand)| {
-                        NAryOperation::Binary {
-                            operator     : operator,
-                            left_operand : Box::new(accumulator),
-                            right_operand: Box::new(right_operand)
-                        }
-                    }
-                ) >>
-                (result)
-            )
-        );
-    )
+/// Find the longest token of `definitions` starting the input. A keyword
+/// token must not be followed by an identifier character.
+///
+/// Return the definition and the span of the token.
+fn operator_token<'a, T, I>(
+    input: Span<'a>,
+    definitions: I,
+    token: fn(&T) -> &'static str
+) -> Option<(&'static T, Span<'a>)>
+    where I: Iterator<Item = &'static T>
+{
+    let slice = input.as_slice();
+
+    definitions
+        .filter(
+            |definition| {
+                let token = token(definition);
+
+                match slice.strip_prefix(token) {
+                    Some(tail) => {
+                        !token.starts_with(is_identifier) || !tail.starts_with(is_identifier)
+                    },
+                    None => false
+                }
+            }
+        )
+        .max_by_key(|definition| token(definition).len())
+        .map(|definition| (definition, input.slice(..token(definition).len())))
 }
 
-macro_rules! left_to_right_unary_operation {
-    (
-        $parser_name:ident:
-        $operand:ident with
-        $operator_token:ident as $operator_representation:ident
-    ) => (
-        named!(
-            $parser_name<Span, NAryOperation>,
-            do_parse!(
-                left_operand: $operand >>
-                unary_operator: opt!(tag!(tokens::$operator_token)) >>
-                (
-                    match (unary_operator) {
-                        Some(operator) => {
-                            NAryOperation::Unary {
-                                operator : operator,
-                                operand  : Box::new(left_operand)
-                            }
-                        },
-                        _ => left_operand
-                    }
-                )
-            )
-        );
-    );
-
-    (
-        $parser_name:ident:
-        $operand:ident with
-        ($($operator_token:ident as $operator_representation:ident),*)
-    ) => (
-        named!(
-            $parser_name<Span, NAryOperation>,
-            do_parse!(
-                left_operand: $operand >>
-                unary_operator: opt!(alt_complete!(
-                        $(
-                            tag!(tokens::$operator_token) => {
-                                |_| { UnaryOperator::$operator_representation }
-                            }
-                        )|*
-                    )
-                ) >>
-                (
-                    match (unary_operator) {
-                        Some(operator) => {
-                            NAryOperation::Unary {
-                                operator : operator,
-                                operand  : Box::new(left_operand)
-                            }
-                        },
-                        _ => left_operand
-                    }
-                )
-            )
-        );
+/// Find the longest unary operator with a particular fixity starting the input.
+fn unary_operator_token(input: Span, fixity: Fixity) -> Option<(&'static UnaryOperatorDefinition, Span)> {
+    operator_token(
+        input,
+        UNARY_OPERATORS.iter().filter(|definition| definition.fixity == fixity),
+        |definition| definition.token
     )
 }
 
-macro_rules! right_to_left_unary_operation {
-    (
-        $parser_name:ident:
-        $operand:ident with
-        $operator_token:ident as $operator_representation:ident
-    ) => (
-        named!(
-            $parser_name<Span, NAryOperation>,
-            alt_complete!(
-                do_parse!(
-                    tag!(tokens::$operator_token) >>
-                    operand: $parser_name >>
-                    (
-                        NAryOperation::Unary {
-                            operator : BinaryOperator::$operator_representation,
-                            operand  : operand
-                        }
-                    )
-                )
-                | $operand
-            )
-        );
-    );
-
-    (
-        $parser_name:ident:
-        $operand:ident with
-        ($($operator_token:ident as $operator_representation:ident),*)
-    ) => (
-        named!(
-            $parser_name<Span, NAryOperation>,
-            alt_complete!(
-                do_parse!(
-                    operator: alt_complete!(
-                        $(
-                            tag!(tokens::$operator_token) => {
-                                |_| { UnaryOperator::$operator_representation }
-                            }
-                        )|*
-                    ) >>
-                    operand: $parser_name >>
-                    (
-                        NAryOperation::Unary {
-                            operator : operator,
-                            operand  : Box::new(operand)
-                        }
-                    )
-                )
-                | $operand
-            )
-        );
-    )
+/// Recognize an operation whose binary operators have a precedence greater
+/// than or equal to `precedence`, by precedence climbing.
+fn binary_operation(input: Span, precedence: u8) -> IResult<Span, NAryOperation> {
+    climb(input, BINARY_OPERATORS, precedence)
 }
 
-left_to_right_binary_operation!(
-    logical:
-    bitwise with
-    (
-        BOOLEAN_OR      as LogicalOr,
-        BOOLEAN_AND     as LogicalAnd
-    )
-);
+/// Climb the precedences of the binary operators of `operators`, starting
+/// from `precedence`.
+fn climb<'a>(input: Span<'a>, operators: &'static [BinaryOperatorDefinition], precedence: u8) -> IResult<Span<'a>, NAryOperation<'a>> {
+    let start = input;
+    let (mut input, mut left_operand) = unary_operation(input)?;
+
+    loop {
+        // A single line comment ends the line, hence the operation, and the
+        // minus sign of a divert is not an operator, e.g. in `{x -> knot}`.
+        let (operator_input, _) = skip_within_line(input)?;
+
+        if operator_input.as_slice().starts_with(tokens::INLINE_COMMENT) || operator_input.as_slice().starts_with(tokens::DIVERT) {
+            break;
+        }
+
+        let (definition, operator) = match operator_token(operator_input, operators.iter(), |definition| definition.token) {
+            Some((definition, operator)) if definition.precedence >= precedence => (definition, operator),
+            _ => break
+        };
+
+        // The right operand of a left-associative operator binds tighter
+        // than the operator.
+        let right_precedence = match definition.associativity {
+            Associativity::Left  => definition.precedence + 1,
+            Associativity::Right => definition.precedence
+        };
+
+        let (next_input, right_operand) = match first!(
+            operator_input.slice(operator.as_slice().len()..),
+            call!(climb, operators, right_precedence)
+        ) {
+            Ok(result) => result,
+
+            // The right operand is missing, e.g. in `(a + )`.
+            Err(Err::Error(_)) => {
+                let (operand_input, _) = skip_within_line(operator_input.slice(operator.as_slice().len()..))?;
+
+                return Err(Err::Failure(error_position!(
+                    operand_input,
+                    ErrorKind::Custom(ErrorKindExtension::Expression as u32)
+                )));
+            },
+
+            Err(error) => return Err(error)
+        };
+
+        if definition.dialect == Dialect::Extended {
+            extended_syntax(next_input, operator)?;
+        }
+
+        left_operand = NAryOperation::Binary {
+            operator     : Token::new(definition.operator, operator),
+            left_operand : Box::new(left_operand),
+            right_operand: Box::new(right_operand),
+            span         : start.slice(..start.offset(&next_input))
+        };
+        input = next_input;
+    }
 
-left_to_right_binary_operation!(
-    bitwise:
-    equality with
-    (
-        BITWISE_OR      as BitwiseOr,
-        BITWISE_XOR     as BitwiseXor,
-        BITWISE_AND     as BitwiseAnd
-    )
-);
+    Ok((input, left_operand))
+}
 
-left_to_right_binary_operation!(
-    equality:
-    relational with
-    (
-        EQUAL         as Equal,
-        NOT_EQUAL     as NotEqual
-    )
-);
+/// Recognize an operand with its prefix and postfix unary operators.
+///
+/// Prefix operators are right-to-left, e.g. `-!x` is `-(!x)`, and postfix
+/// operators are left-to-right, e.g. `x++--` is `(x++)--`. Postfix
+/// operators bind tighter, e.g. `-x++` is `-(x++)`.
+fn unary_operation(input: Span) -> IResult<Span, NAryOperation> {
+    // The minus sign of the smallest integer belongs to the literal.
+    let prefix = match minimum_integer(input) {
+        Ok(_)  => None,
+        Err(_) => unary_operator_token(input, Fixity::Prefix)
+    };
 
-left_to_right_binary_operation!(
-    relational:
-    shift with
-    (
-        LESS_THAN_OR_EQUAL_TO    as LessThanOrEqualTo,
-        GREATER_THAN_OR_EQUAL_TO as GreaterThanOrEqualTo,
-        LESS_THAN                as LessThan,
-        GREATER_THAN             as GreaterThan
-    )
-);
+    if let Some((definition, operator)) = prefix {
+        match first!(input.slice(operator.as_slice().len()..), unary_operation) {
+            Ok((next_input, operand)) => {
+                if definition.dialect == Dialect::Extended {
+                    extended_syntax(next_input, operator)?;
+                }
+
+                return Ok((
+                    next_input,
+                    NAryOperation::Unary {
+                        operator: Token::new(definition.operator, operator),
+                        operand : Box::new(operand),
+                        span    : input.slice(..input.offset(&next_input))
+                    }
+                ));
+            },
 
-left_to_right_binary_operation!(
-    shift:
-    additive with
-    (
-        BITWISE_LEFT_SHIFT  as BitwiseShiftLeft,
-        BITWISE_RIGHT_SHIFT as BitwiseShiftRight
-    )
-);
+            Err(Err::Error(_)) => {},
 
-left_to_right_binary_operation!(
-    additive:
-    multiplicative with
-    (
-        ADD         as Addition,
-        SUBSTRACT   as Subtraction
-    )
-);
+            Err(error) => return Err(error)
+        }
+    }
 
-left_to_right_binary_operation!(
-    multiplicative:
-    unary_not with
-    (
-        MULTIPLY as Multiplication,
-        DIVIDE   as Division,
-        MODULO   as Modulo
-    )
-);
+    let start = input;
+    let (mut input, mut operand) = leaf(input)?;
 
-right_to_left_unary_operation!(
-    unary_not:
-    increment_and_decrement with
-    (
-        BITWISE_NOT as BitwiseComplement,
-        BOOLEAN_NOT as Negate
-    )
-);
+    while let Some((definition, operator)) = unary_operator_token(input, Fixity::Postfix) {
+        input = input.slice(operator.as_slice().len()..);
 
-left_to_right_unary_operation!(
-    increment_and_decrement:
-    nullary_operation with
-    (
-        INCREMENT as Increment,
-        DECREMENT as Decrement
-    )
-);
+        if definition.dialect == Dialect::Extended {
+            extended_syntax(input, operator)?;
+        }
 
-named!(
-    nullary_operation<Span, NAryOperation>,
-    call!(leaf)
-);
+        operand = NAryOperation::Unary {
+            operator: Token::new(definition.operator, operator),
+            operand : Box::new(operand),
+            span    : start.slice(..start.offset(&input))
+        };
+    }
+
+    Ok((input, operand))
+}
 
-named!(
-    leaf<Span, NAryOperation>,
-    alt_complete!(
-        map_res!(
+/// Recognize an expression, or an operation surrounded by parentheses.
+///
+/// The span of a parenthesized operation covers its parentheses.
+fn leaf(input: Span) -> IResult<Span, NAryOperation> {
+    let (next_input, mut operation) = alt_complete!(
+        input,
+        map!(
             expression,
-            |expression| -> Result<NAryOperation, ()> {
-                Ok(NAryOperation::Nullary(Box::new(expression)))
-            }
+            |operand| NAryOperation::Nullary { operand: Box::new(operand), span: input }
         )
         | preceded!(
             tag!(tokens::LEFT_PARENTHESIS),
             terminated!(
-                first!(logical),
-                first!(tag!(tokens::RIGHT_PARENTHESIS))
+                first!(expect!(ErrorKindExtension::Expression, call!(binary_operation, 0))),
+                first!(expect!(ErrorKindExtension::RightParenthesis, tag!(tokens::RIGHT_PARENTHESIS)))
             )
         )
-    )
-);
+    )?;
+
+    match operation {
+        NAryOperation::Nullary { ref mut span, .. } |
+        NAryOperation::Unary { ref mut span, .. }   |
+        NAryOperation::Binary { ref mut span, .. }  => *span = input.slice(..input.offset(&next_input))
+    }
+
+    Ok((next_input, operation))
+}
 
 #[cfg(test)]
 mod tests {
@@ -321,32 +359,73 @@ mod tests {
         Expression,
         Literal,
         NAryOperation,
+        Spanned,
         Token,
         Variable
     };
-    use span::Span;
+    use internal::{
+        Context,
+        Error,
+        ErrorKind,
+        ErrorKindExtension
+    };
+    use options::{
+        Dialect,
+        ParserOptions
+    };
+    use span::{
+        Position,
+        SourceRange,
+        Span
+    };
+    use state;
+
+    macro_rules! strict {
+        ($parser:ident($input:expr)) => (
+            state::with_state($input.as_slice(), ParserOptions::new(Dialect::Strict), || $parser($input))
+        )
+    }
+
+    macro_rules! extended_syntax {
+        ($span:expr) => (
+            Err(Error::Failure(Context::Code($span, ErrorKind::Custom(ErrorKindExtension::ExtendedSyntax as u32))))
+        )
+    }
 
     macro_rules! nullary {
-        ($expression:expr) => (
-            NAryOperation::Nullary(Box::new($expression))
+        ($kind:ident!($value:expr, $span:expr)) => (
+            nullary!($kind!($value, $span), $span)
+        );
+
+        (variable!($name:expr)) => (
+            nullary!(variable!($name), $name)
+        );
+
+        ($expression:expr, $span:expr) => (
+            NAryOperation::Nullary {
+                operand: Box::new($expression),
+                span   : $span
+            }
         )
     }
 
     macro_rules! unary {
-        ($operator:ident, $operand:expr) => (
+        ($operator:ident, $operator_span:expr, $operand:expr, $span:expr) => (
             NAryOperation::Unary {
-                operator : UnaryOperator::$operator,
-                operand  : Box::new($operand)
+                operator : Token::new(UnaryOperator::$operator, $operator_span),
+                operand  : Box::new($operand),
+                span     : $span
             }
         )
     }
 
     macro_rules! binary {
-        ($operator:ident, $left_operand:expr, $right_operand:expr) => (
+        ($operator:ident, $operator_span:expr, $left_operand:expr, $right_operand:expr, $span:expr) => (
             NAryOperation::Binary {
-                operator     : BinaryOperator::$operator,
+                operator     : Token::new(BinaryOperator::$operator, $operator_span),
                 left_operand : Box::new($left_operand),
-                right_operand: Box::new($right_operand)
+                right_operand: Box::new($right_operand),
+                span         : $span
             }
         )
     }
@@ -363,6 +442,66 @@ mod tests {
         )
     }
 
+    #[test]
+    fn case_binary_range() {
+        let input  = "(x + 12) * y\n";
+        let output = SourceRange::new(
+            Position { offset: 0, line: 1, column: 1 },
+            Position { offset: 12, line: 1, column: 13 }
+        );
+
+        match operation(Span::new(input)) {
+            Ok((_, expression)) => {
+                assert_eq!(expression.range(), output);
+                assert_eq!(&input[expression.range().byte_range()], "(x + 12) * y");
+
+                match expression {
+                    Expression::NAryOperation(NAryOperation::Binary { left_operand, right_operand, .. }) => {
+                        assert_eq!(&input[left_operand.range().byte_range()], "(x + 12)");
+                        assert_eq!(&input[right_operand.range().byte_range()], "y");
+                        assert!(output.contains(&left_operand.range()));
+                    },
+                    _ => panic!("expected a binary operation")
+                }
+            },
+            _ => panic!("expected an operation")
+        }
+    }
+
+    #[test]
+    fn case_binary_range_nested_parentheses() {
+        let input = "a * ((b) - -(c))\n";
+
+        match operation(Span::new(input)) {
+            Ok((_, Expression::NAryOperation(NAryOperation::Binary { right_operand, .. }))) => {
+                assert_eq!(&input[right_operand.range().byte_range()], "((b) - -(c))");
+
+                match *right_operand {
+                    NAryOperation::Binary { ref left_operand, ref right_operand, .. } => {
+                        assert_eq!(&input[left_operand.range().byte_range()], "(b)");
+                        assert_eq!(&input[right_operand.range().byte_range()], "-(c)");
+                    },
+                    _ => panic!("expected a binary operation")
+                }
+            },
+            _ => panic!("expected a binary operation")
+        }
+    }
+
+    #[test]
+    fn case_unary_range() {
+        let input = "-x++ * not y\n";
+
+        match operation(Span::new(input)) {
+            Ok((_, Expression::NAryOperation(NAryOperation::Binary { operator, left_operand, right_operand, .. }))) => {
+                assert_eq!(operator.span, Span::new_at("*", 5, 1, 6));
+                assert_eq!(&input[left_operand.range().byte_range()], "-x++");
+                assert_eq!(&input[right_operand.range().byte_range()], "not y");
+            },
+            _ => panic!("expected a binary operation")
+        }
+    }
+
     #[test]
     fn case_binary_logical_or() {
         let input  = Span::new("1 || 2 || 3\n");
@@ -370,13 +509,15 @@ mod tests {
             Span::new_at("\n", 11, 1, 12),
             Expression::NAryOperation(
                 binary!(
-                    LogicalOr,
+                    LogicalOr, Span::new_at("||", 7, 1, 8),
                     binary!(
-                        LogicalOr,
+                        LogicalOr, Span::new_at("||", 2, 1, 3),
                         nullary!(integer!(1, Span::new("1"))),
-                        nullary!(integer!(2, Span::new_at("2", 5, 1, 6)))
+                        nullary!(integer!(2, Span::new_at("2", 5, 1, 6))),
+                        Span::new("1 || 2")
                     ),
-                    nullary!(integer!(3, Span::new_at("3", 10, 1, 11)))
+                    nullary!(integer!(3, Span::new_at("3", 10, 1, 11))),
+                    Span::new("1 || 2 || 3")
                 )
             )
         ));
@@ -391,13 +532,15 @@ mod tests {
             Span::new_at("\n", 11, 1, 12),
             Expression::NAryOperation(
                 binary!(
-                    LogicalAnd,
+                    LogicalAnd, Span::new_at("&&", 7, 1, 8),
                     binary!(
-                        LogicalAnd,
+                        LogicalAnd, Span::new_at("&&", 2, 1, 3),
                         nullary!(integer!(1, Span::new("1"))),
-                        nullary!(integer!(2, Span::new_at("2", 5, 1, 6)))
+                        nullary!(integer!(2, Span::new_at("2", 5, 1, 6))),
+                        Span::new("1 && 2")
                     ),
-                    nullary!(integer!(3, Span::new_at("3", 10, 1, 11)))
+                    nullary!(integer!(3, Span::new_at("3", 10, 1, 11))),
+                    Span::new("1 && 2 && 3")
                 )
             )
         ));
@@ -412,13 +555,15 @@ mod tests {
             Span::new_at("\n", 9, 1, 10),
             Expression::NAryOperation(
                 binary!(
-                    BitwiseOr,
+                    BitwiseOr, Span::new_at("|", 6, 1, 7),
                     binary!(
-                        BitwiseOr,
+                        BitwiseOr, Span::new_at("|", 2, 1, 3),
                         nullary!(integer!(1, Span::new("1"))),
-                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5)))
+                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5))),
+                        Span::new("1 | 2")
                     ),
-                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9)))
+                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9))),
+                    Span::new("1 | 2 | 3")
                 )
             )
         ));
@@ -428,24 +573,56 @@ mod tests {
 
 
     #[test]
-    fn case_binary_bitwise_xor() {
-        let input  = Span::new("1 ^ 2 ^ 3\n");
+    fn case_binary_intersection() {
+        let input  = Span::new("a ? b ^ c + d\n");
         let output = Ok((
-            Span::new_at("\n", 9, 1, 10),
+            Span::new_at("\n", 13, 1, 14),
             Expression::NAryOperation(
                 binary!(
-                    BitwiseXor,
+                    Intersection, Span::new_at("^", 6, 1, 7),
                     binary!(
-                        BitwiseXor,
-                        nullary!(integer!(1, Span::new("1"))),
-                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5)))
+                        Has, Span::new_at("?", 2, 1, 3),
+                        nullary!(variable!(Span::new("a"))),
+                        nullary!(variable!(Span::new_at("b", 4, 1, 5))),
+                        Span::new("a ? b")
+                    ),
+                    binary!(
+                        Addition, Span::new_at("+", 10, 1, 11),
+                        nullary!(variable!(Span::new_at("c", 8, 1, 9))),
+                        nullary!(variable!(Span::new_at("d", 12, 1, 13))),
+                        Span::new_at("c + d", 8, 1, 9)
                     ),
-                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9)))
+                    Span::new("a ? b ^ c + d")
                 )
             )
         ));
 
-        assert_eq!(operation(input), output);
+        assert_eq!(strict!(operation(input)), output);
+    }
+
+    #[test]
+    fn case_binary_right_associativity() {
+        const OPERATORS: &[BinaryOperatorDefinition] = &[
+            binary(tokens::SUBSTRACT, BinaryOperator::Subtraction, 1, Associativity::Right, Dialect::Strict)
+        ];
+
+        let input  = Span::new("1 - 2 - 3\n");
+        let output = Ok((
+            Span::new_at("\n", 9, 1, 10),
+            binary!(
+                Subtraction, Span::new_at("-", 2, 1, 3),
+                nullary!(integer!(1, Span::new("1"))),
+                binary!(
+                    Subtraction, Span::new_at("-", 6, 1, 7),
+                    nullary!(integer!(2, Span::new_at("2", 4, 1, 5))),
+                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9))),
+                    Span::new_at("2 - 3", 4, 1, 5)
+                ),
+                Span::new("1 - 2 - 3")
+            )
+        ));
+
+        assert_eq!(climb(input, OPERATORS, 0), output);
     }
 
     #[test]
@@ -455,13 +632,15 @@ mod tests {
             Span::new_at("\n", 9, 1, 10),
             Expression::NAryOperation(
                 binary!(
-                    BitwiseAnd,
+                    BitwiseAnd, Span::new_at("&", 6, 1, 7),
                     binary!(
-                        BitwiseAnd,
+                        BitwiseAnd, Span::new_at("&", 2, 1, 3),
                         nullary!(integer!(1, Span::new("1"))),
-                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5)))
+                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5))),
+                        Span::new("1 & 2")
                     ),
-                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9)))
+                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9))),
+                    Span::new("1 & 2 & 3")
                 )
             )
         ));
@@ -476,13 +655,15 @@ mod tests {
             Span::new_at("\n", 11, 1, 12),
             Expression::NAryOperation(
                 binary!(
-                    Equal,
+                    Equal, Span::new_at("==", 7, 1, 8),
                     binary!(
-                        Equal,
+                        Equal, Span::new_at("==", 2, 1, 3),
                         nullary!(integer!(1, Span::new("1"))),
-                        nullary!(integer!(2, Span::new_at("2", 5, 1, 6)))
+                        nullary!(integer!(2, Span::new_at("2", 5, 1, 6))),
+                        Span::new("1 == 2")
                     ),
-                    nullary!(integer!(3, Span::new_at("3", 10, 1, 11)))
+                    nullary!(integer!(3, Span::new_at("3", 10, 1, 11))),
+                    Span::new("1 == 2 == 3")
                 )
             )
         ));
@@ -497,13 +678,15 @@ mod tests {
             Span::new_at("\n", 11, 1, 12),
             Expression::NAryOperation(
                 binary!(
-                    NotEqual,
+                    NotEqual, Span::new_at("!=", 7, 1, 8),
                     binary!(
-                        NotEqual,
+                        NotEqual, Span::new_at("!=", 2, 1, 3),
                         nullary!(integer!(1, Span::new("1"))),
-                        nullary!(integer!(2, Span::new_at("2", 5, 1, 6)))
+                        nullary!(integer!(2, Span::new_at("2", 5, 1, 6))),
+                        Span::new("1 != 2")
                     ),
-                    nullary!(integer!(3, Span::new_at("3", 10, 1, 11)))
+                    nullary!(integer!(3, Span::new_at("3", 10, 1, 11))),
+                    Span::new("1 != 2 != 3")
                 )
             )
         ));
@@ -518,13 +701,15 @@ mod tests {
             Span::new_at("\n", 11, 1, 12),
             Expression::NAryOperation(
                 binary!(
-                    LessThanOrEqualTo,
+                    LessThanOrEqualTo, Span::new_at("<=", 7, 1, 8),
                     binary!(
-                        LessThanOrEqualTo,
+                        LessThanOrEqualTo, Span::new_at("<=", 2, 1, 3),
                         nullary!(integer!(1, Span::new("1"))),
-                        nullary!(integer!(2, Span::new_at("2", 5, 1, 6)))
+                        nullary!(integer!(2, Span::new_at("2", 5, 1, 6))),
+                        Span::new("1 <= 2")
                     ),
-                    nullary!(integer!(3, Span::new_at("3", 10, 1, 11)))
+                    nullary!(integer!(3, Span::new_at("3", 10, 1, 11))),
+                    Span::new("1 <= 2 <= 3")
                 )
             )
         ));
@@ -539,13 +724,15 @@ mod tests {
             Span::new_at("\n", 11, 1, 12),
             Expression::NAryOperation(
                 binary!(
-                    GreaterThanOrEqualTo,
+                    GreaterThanOrEqualTo, Span::new_at(">=", 7, 1, 8),
                     binary!(
-                        GreaterThanOrEqualTo,
+                        GreaterThanOrEqualTo, Span::new_at(">=", 2, 1, 3),
                         nullary!(integer!(1, Span::new("1"))),
-                        nullary!(integer!(2, Span::new_at("2", 5, 1, 6)))
+                        nullary!(integer!(2, Span::new_at("2", 5, 1, 6))),
+                        Span::new("1 >= 2")
                     ),
-                    nullary!(integer!(3, Span::new_at("3", 10, 1, 11)))
+                    nullary!(integer!(3, Span::new_at("3", 10, 1, 11))),
+                    Span::new("1 >= 2 >= 3")
                 )
             )
         ));
@@ -560,13 +747,15 @@ mod tests {
             Span::new_at("\n", 9, 1, 10),
             Expression::NAryOperation(
                 binary!(
-                    LessThan,
+                    LessThan, Span::new_at("<", 6, 1, 7),
                     binary!(
-                        LessThan,
+                        LessThan, Span::new_at("<", 2, 1, 3),
                         nullary!(integer!(1, Span::new("1"))),
-                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5)))
+                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5))),
+                        Span::new("1 < 2")
                     ),
-                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9)))
+                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9))),
+                    Span::new("1 < 2 < 3")
                 )
             )
         ));
@@ -581,13 +770,15 @@ mod tests {
             Span::new_at("\n", 9, 1, 10),
             Expression::NAryOperation(
                 binary!(
-                    GreaterThan,
+                    GreaterThan, Span::new_at(">", 6, 1, 7),
                     binary!(
-                        GreaterThan,
+                        GreaterThan, Span::new_at(">", 2, 1, 3),
                         nullary!(integer!(1, Span::new("1"))),
-                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5)))
+                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5))),
+                        Span::new("1 > 2")
                     ),
-                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9)))
+                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9))),
+                    Span::new("1 > 2 > 3")
                 )
             )
         ));
@@ -602,13 +793,15 @@ mod tests {
             Span::new_at("\n", 11, 1, 12),
             Expression::NAryOperation(
                 binary!(
-                    BitwiseShiftLeft,
+                    BitwiseShiftLeft, Span::new_at("<<", 7, 1, 8),
                     binary!(
-                        BitwiseShiftLeft,
+                        BitwiseShiftLeft, Span::new_at("<<", 2, 1, 3),
                         nullary!(integer!(1, Span::new("1"))),
-                        nullary!(integer!(2, Span::new_at("2", 5, 1, 6)))
+                        nullary!(integer!(2, Span::new_at("2", 5, 1, 6))),
+                        Span::new("1 << 2")
                     ),
-                    nullary!(integer!(3, Span::new_at("3", 10, 1, 11)))
+                    nullary!(integer!(3, Span::new_at("3", 10, 1, 11))),
+                    Span::new("1 << 2 << 3")
                 )
             )
         ));
@@ -623,13 +816,15 @@ mod tests {
             Span::new_at("\n", 11, 1, 12),
             Expression::NAryOperation(
                 binary!(
-                    BitwiseShiftRight,
+                    BitwiseShiftRight, Span::new_at(">>", 7, 1, 8),
                     binary!(
-                        BitwiseShiftRight,
+                        BitwiseShiftRight, Span::new_at(">>", 2, 1, 3),
                         nullary!(integer!(1, Span::new("1"))),
-                        nullary!(integer!(2, Span::new_at("2", 5, 1, 6)))
+                        nullary!(integer!(2, Span::new_at("2", 5, 1, 6))),
+                        Span::new("1 >> 2")
                     ),
-                    nullary!(integer!(3, Span::new_at("3", 10, 1, 11)))
+                    nullary!(integer!(3, Span::new_at("3", 10, 1, 11))),
+                    Span::new("1 >> 2 >> 3")
                 )
             )
         ));
@@ -644,13 +839,15 @@ mod tests {
             Span::new_at("\n", 9, 1, 10),
             Expression::NAryOperation(
                 binary!(
-                    Addition,
+                    Addition, Span::new_at("+", 6, 1, 7),
                     binary!(
-                        Addition,
+                        Addition, Span::new_at("+", 2, 1, 3),
                         nullary!(integer!(1, Span::new("1"))),
-                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5)))
+                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5))),
+                        Span::new("1 + 2")
                     ),
-                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9)))
+                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9))),
+                    Span::new("1 + 2 + 3")
                 )
             )
         ));
@@ -665,13 +862,15 @@ mod tests {
             Span::new_at("\n", 9, 1, 10),
             Expression::NAryOperation(
                 binary!(
-                    Subtraction,
+                    Subtraction, Span::new_at("-", 6, 1, 7),
                     binary!(
-                        Subtraction,
+                        Subtraction, Span::new_at("-", 2, 1, 3),
                         nullary!(integer!(1, Span::new("1"))),
-                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5)))
+                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5))),
+                        Span::new("1 - 2")
                     ),
-                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9)))
+                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9))),
+                    Span::new("1 - 2 - 3")
                 )
             )
         ));
@@ -686,13 +885,15 @@ mod tests {
             Span::new_at("\n", 9, 1, 10),
             Expression::NAryOperation(
                 binary!(
-                    Multiplication,
+                    Multiplication, Span::new_at("*", 6, 1, 7),
                     binary!(
-                        Multiplication,
+                        Multiplication, Span::new_at("*", 2, 1, 3),
                         nullary!(integer!(1, Span::new("1"))),
-                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5)))
+                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5))),
+                        Span::new("1 * 2")
                     ),
-                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9)))
+                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9))),
+                    Span::new("1 * 2 * 3")
                 )
             )
         ));
@@ -707,13 +908,15 @@ mod tests {
             Span::new_at("\n", 9, 1, 10),
             Expression::NAryOperation(
                 binary!(
-                    Division,
+                    Division, Span::new_at("/", 6, 1, 7),
                     binary!(
-                        Division,
+                        Division, Span::new_at("/", 2, 1, 3),
                         nullary!(integer!(1, Span::new("1"))),
-                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5)))
+                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5))),
+                        Span::new("1 / 2")
                     ),
-                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9)))
+                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9))),
+                    Span::new("1 / 2 / 3")
                 )
             )
         ));
@@ -728,13 +931,15 @@ mod tests {
             Span::new_at("\n", 9, 1, 10),
             Expression::NAryOperation(
                 binary!(
-                    Modulo,
+                    Modulo, Span::new_at("%", 6, 1, 7),
                     binary!(
-                        Modulo,
+                        Modulo, Span::new_at("%", 2, 1, 3),
                         nullary!(integer!(1, Span::new("1"))),
-                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5)))
+                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5))),
+                        Span::new("1 % 2")
                     ),
-                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9)))
+                    nullary!(integer!(3, Span::new_at("3", 8, 1, 9))),
+                    Span::new("1 % 2 % 3")
                 )
             )
         ));
@@ -749,13 +954,15 @@ mod tests {
             Span::new_at("\n", 9, 1, 10),
             Expression::NAryOperation(
                 binary!(
-                    Addition,
+                    Addition, Span::new_at("+", 2, 1, 3),
                     nullary!(integer!(1, Span::new_at("1", 0, 1, 1))),
                     binary!(
-                        Multiplication,
+                        Multiplication, Span::new_at("*", 6, 1, 7),
                         nullary!(integer!(2, Span::new_at("2", 4, 1, 5))),
-                        nullary!(integer!(3, Span::new_at("3", 8, 1, 9)))
-                    )
+                        nullary!(integer!(3, Span::new_at("3", 8, 1, 9))),
+                        Span::new_at("2 * 3", 4, 1, 5)
+                    ),
+                    Span::new("1 + 2 * 3")
                 )
             )
         ));
@@ -770,17 +977,20 @@ mod tests {
             Span::new_at("\n", 16, 1, 17),
             Expression::NAryOperation(
                 binary!(
-                    LogicalOr,
+                    LogicalOr, Span::new_at("||", 7, 1, 8),
                     binary!(
-                        Equal,
+                        Equal, Span::new_at("==", 2, 1, 3),
                         nullary!(integer!(1, Span::new_at("1", 0, 1, 1))),
-                        nullary!(integer!(2, Span::new_at("2", 5, 1, 6)))
+                        nullary!(integer!(2, Span::new_at("2", 5, 1, 6))),
+                        Span::new("1 == 2")
                     ),
                     binary!(
-                        NotEqual,
+                        NotEqual, Span::new_at("!=", 12, 1, 13),
                         nullary!(integer!(3, Span::new_at("3", 10, 1, 11))),
-                        nullary!(integer!(4, Span::new_at("4", 15, 1, 16)))
-                    )
+                        nullary!(integer!(4, Span::new_at("4", 15, 1, 16))),
+                        Span::new_at("3 != 4", 10, 1, 11)
+                    ),
+                    Span::new("1 == 2 || 3 != 4")
                 )
             )
         ));
@@ -795,17 +1005,20 @@ mod tests {
             Span::new_at("\n", 14, 1, 15),
             Expression::NAryOperation(
                 binary!(
-                    Equal,
+                    Equal, Span::new_at("==", 6, 1, 7),
                     binary!(
-                        Addition,
+                        Addition, Span::new_at("+", 2, 1, 3),
                         nullary!(integer!(1, Span::new_at("1", 0, 1, 1))),
-                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5)))
+                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5))),
+                        Span::new("1 + 2")
                     ),
                     binary!(
-                        Subtraction,
+                        Subtraction, Span::new_at("-", 11, 1, 12),
                         nullary!(integer!(3, Span::new_at("3", 9, 1, 10))),
-                        nullary!(integer!(4, Span::new_at("4", 13, 1, 14)))
-                    )
+                        nullary!(integer!(4, Span::new_at("4", 13, 1, 14))),
+                        Span::new_at("3 - 4", 9, 1, 10)
+                    ),
+                    Span::new("1 + 2 == 3 - 4")
                 )
             )
         ));
@@ -820,13 +1033,15 @@ mod tests {
             Span::new_at("\n", 13, 1, 14),
             Expression::NAryOperation(
                 binary!(
-                    Addition,
+                    Addition, Span::new_at("+", 4, 1, 5),
                     nullary!(integer!(1, Span::new_at("1", 0, 1, 1))),
                     binary!(
-                        Multiplication,
+                        Multiplication, Span::new_at("*", 8, 1, 9),
                         nullary!(integer!(2, Span::new_at("2", 6, 1, 7))),
-                        nullary!(integer!(3, Span::new_at("3", 12, 1, 13)))
-                    )
+                        nullary!(integer!(3, Span::new_at("3", 12, 1, 13))),
+                        Span::new_at("2 *   3", 6, 1, 7)
+                    ),
+                    Span::new("1   + 2 *   3")
                 )
             )
         ));
@@ -841,13 +1056,15 @@ mod tests {
             Span::new_at("\n", 9, 1, 10),
             Expression::NAryOperation(
                 binary!(
-                    Addition,
+                    Addition, Span::new_at("+", 2, 1, 3),
                     nullary!(integer!(1, Span::new_at("1", 0, 1, 1))),
                     binary!(
-                        Multiplication,
+                        Multiplication, Span::new_at("*", 6, 1, 7),
                         nullary!(variable!(Span::new_at("x", 4, 1, 5))),
-                        nullary!(variable!(Span::new_at("y",  8, 1, 9)))
-                    )
+                        nullary!(variable!(Span::new_at("y",  8, 1, 9))),
+                        Span::new_at("x * y", 4, 1, 5)
+                    ),
+                    Span::new("1 + x * y")
                 )
             )
         ));
@@ -862,13 +1079,202 @@ mod tests {
             Span::new_at("\n", 19, 1, 20),
             Expression::NAryOperation(
                 binary!(
-                    Multiplication,
+                    Multiplication, Span::new_at("*", 10, 1, 11),
                     binary!(
-                        Addition,
+                        Addition, Span::new_at("+", 5, 1, 6),
                         nullary!(integer!(1, Span::new_at("1", 3, 1, 4))),
-                        nullary!(integer!(2, Span::new_at("2",  7, 1, 8)))
+                        nullary!(integer!(2, Span::new_at("2",  7, 1, 8))),
+                        Span::new_at("(1 + 2)", 2, 1, 3)
+                    ),
+                    nullary!(integer!(3, Span::new_at("3", 14, 1, 15)), Span::new_at("((3))", 12, 1, 13)),
+                    Span::new("(((1 + 2) * ((3))))")
+                )
+            )
+        ));
+
+        assert_eq!(operation(input), output);
+    }
+
+    #[test]
+    fn case_binary_keyword_aliases() {
+        let input  = Span::new("x and y or z mod 2\n");
+        let output = Ok((
+            Span::new_at("\n", 18, 1, 19),
+            Expression::NAryOperation(
+                binary!(
+                    LogicalOr, Span::new_at("or", 8, 1, 9),
+                    binary!(
+                        LogicalAnd, Span::new_at("and", 2, 1, 3),
+                        nullary!(variable!(Span::new_at("x", 0, 1, 1))),
+                        nullary!(variable!(Span::new_at("y", 6, 1, 7))),
+                        Span::new("x and y")
+                    ),
+                    binary!(
+                        Modulo, Span::new_at("mod", 13, 1, 14),
+                        nullary!(variable!(Span::new_at("z", 11, 1, 12))),
+                        nullary!(integer!(2, Span::new_at("2", 17, 1, 18))),
+                        Span::new_at("z mod 2", 11, 1, 12)
+                    ),
+                    Span::new("x and y or z mod 2")
+                )
+            )
+        ));
+
+        assert_eq!(operation(input), output);
+    }
+
+    #[test]
+    fn case_binary_keyword_prefix_of_identifier() {
+        let input  = Span::new("x andy\n");
+        let output = Ok((
+            Span::new_at(" andy\n", 1, 1, 2),
+            Expression::NAryOperation(nullary!(variable!(Span::new_at("x", 0, 1, 1))))
+        ));
+
+        assert_eq!(operation(input), output);
+    }
+
+    #[test]
+    fn case_binary_has() {
+        let input  = Span::new("a ? b has c\n");
+        let output = Ok((
+            Span::new_at("\n", 11, 1, 12),
+            Expression::NAryOperation(
+                binary!(
+                    Has, Span::new_at("has", 6, 1, 7),
+                    binary!(
+                        Has, Span::new_at("?", 2, 1, 3),
+                        nullary!(variable!(Span::new_at("a", 0, 1, 1))),
+                        nullary!(variable!(Span::new_at("b", 4, 1, 5))),
+                        Span::new("a ? b")
+                    ),
+                    nullary!(variable!(Span::new_at("c", 10, 1, 11))),
+                    Span::new("a ? b has c")
+                )
+            )
+        ));
+
+        assert_eq!(operation(input), output);
+    }
+
+    #[test]
+    fn case_binary_hasnt() {
+        let input  = Span::new("a !? b hasnt c\n");
+        let output = Ok((
+            Span::new_at("\n", 14, 1, 15),
+            Expression::NAryOperation(
+                binary!(
+                    Hasnt, Span::new_at("hasnt", 7, 1, 8),
+                    binary!(
+                        Hasnt, Span::new_at("!?", 2, 1, 3),
+                        nullary!(variable!(Span::new_at("a", 0, 1, 1))),
+                        nullary!(variable!(Span::new_at("b", 5, 1, 6))),
+                        Span::new("a !? b")
+                    ),
+                    nullary!(variable!(Span::new_at("c", 13, 1, 14))),
+                    Span::new("a !? b hasnt c")
+                )
+            )
+        ));
+
+        assert_eq!(operation(input), output);
+    }
+
+    #[test]
+    fn case_binary_precedence_has() {
+        let input  = Span::new("a has b + 1 == c\n");
+        let output = Ok((
+            Span::new_at("\n", 16, 1, 17),
+            Expression::NAryOperation(
+                binary!(
+                    Equal, Span::new_at("==", 12, 1, 13),
+                    binary!(
+                        Has, Span::new_at("has", 2, 1, 3),
+                        nullary!(variable!(Span::new_at("a", 0, 1, 1))),
+                        binary!(
+                            Addition, Span::new_at("+", 8, 1, 9),
+                            nullary!(variable!(Span::new_at("b", 6, 1, 7))),
+                            nullary!(integer!(1, Span::new_at("1", 10, 1, 11))),
+                            Span::new_at("b + 1", 6, 1, 7)
+                        ),
+                        Span::new("a has b + 1")
+                    ),
+                    nullary!(variable!(Span::new_at("c", 15, 1, 16))),
+                    Span::new("a has b + 1 == c")
+                )
+            )
+        ));
+
+        assert_eq!(operation(input), output);
+    }
+
+    #[test]
+    fn case_binary_precedence_logical() {
+        let input  = Span::new("a || b && c\n");
+        let output = Ok((
+            Span::new_at("\n", 11, 1, 12),
+            Expression::NAryOperation(
+                binary!(
+                    LogicalOr, Span::new_at("||", 2, 1, 3),
+                    nullary!(variable!(Span::new_at("a", 0, 1, 1))),
+                    binary!(
+                        LogicalAnd, Span::new_at("&&", 7, 1, 8),
+                        nullary!(variable!(Span::new_at("b", 5, 1, 6))),
+                        nullary!(variable!(Span::new_at("c", 10, 1, 11))),
+                        Span::new_at("b && c", 5, 1, 6)
+                    ),
+                    Span::new("a || b && c")
+                )
+            )
+        ));
+
+        assert_eq!(operation(input), output);
+    }
+
+    #[test]
+    fn case_binary_precedence_bitwise() {
+        let input  = Span::new("1 | 2 & 3 == 4\n");
+        let output = Ok((
+            Span::new_at("\n", 14, 1, 15),
+            Expression::NAryOperation(
+                binary!(
+                    BitwiseOr, Span::new_at("|", 2, 1, 3),
+                    nullary!(integer!(1, Span::new("1"))),
+                    binary!(
+                        BitwiseAnd, Span::new_at("&", 6, 1, 7),
+                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5))),
+                        binary!(
+                            Equal, Span::new_at("==", 10, 1, 11),
+                            nullary!(integer!(3, Span::new_at("3", 8, 1, 9))),
+                            nullary!(integer!(4, Span::new_at("4", 13, 1, 14))),
+                            Span::new_at("3 == 4", 8, 1, 9)
+                        ),
+                        Span::new_at("2 & 3 == 4", 4, 1, 5)
+                    ),
+                    Span::new("1 | 2 & 3 == 4")
+                )
+            )
+        ));
+
+        assert_eq!(operation(input), output);
+    }
+
+    #[test]
+    fn case_binary_precedence_bitwise_and_logical() {
+        let input  = Span::new("1 & 2 && 3\n");
+        let output = Ok((
+            Span::new_at("\n", 10, 1, 11),
+            Expression::NAryOperation(
+                binary!(
+                    LogicalAnd, Span::new_at("&&", 6, 1, 7),
+                    binary!(
+                        BitwiseAnd, Span::new_at("&", 2, 1, 3),
+                        nullary!(integer!(1, Span::new("1"))),
+                        nullary!(integer!(2, Span::new_at("2", 4, 1, 5))),
+                        Span::new("1 & 2")
                     ),
-                    nullary!(integer!(3, Span::new_at("3", 14, 1, 15)))
+                    nullary!(integer!(3, Span::new_at("3", 9, 1, 10))),
+                    Span::new("1 & 2 && 3")
                 )
             )
         ));
@@ -883,8 +1289,9 @@ mod tests {
             Span::new_at("\n", 3, 1, 4),
             Expression::NAryOperation(
                 unary!(
-                    Increment,
-                    nullary!(integer!(1, Span::new_at("1", 0, 1, 1)))
+                    Increment, Span::new_at("++", 1, 1, 2),
+                    nullary!(integer!(1, Span::new_at("1", 0, 1, 1))),
+                    Span::new("1++")
                 )
             )
         ));
@@ -899,11 +1306,13 @@ mod tests {
             Span::new_at("\n", 7, 1, 8),
             Expression::NAryOperation(
                 unary!(
-                    Increment,
+                    Increment, Span::new_at("++", 5, 1, 6),
                     unary!(
-                        Increment,
-                        nullary!(integer!(1, Span::new_at("1", 1, 1, 2)))
-                    )
+                        Increment, Span::new_at("++", 2, 1, 3),
+                        nullary!(integer!(1, Span::new_at("1", 1, 1, 2))),
+                        Span::new("(1++)")
+                    ),
+                    Span::new("(1++)++")
                 )
             )
         ));
@@ -918,8 +1327,9 @@ mod tests {
             Span::new_at("\n", 3, 1, 4),
             Expression::NAryOperation(
                 unary!(
-                    Decrement,
-                    nullary!(integer!(1, Span::new_at("1", 0, 1, 1)))
+                    Decrement, Span::new_at("--", 1, 1, 2),
+                    nullary!(integer!(1, Span::new_at("1", 0, 1, 1))),
+                    Span::new("1--")
                 )
             )
         ));
@@ -934,8 +1344,9 @@ mod tests {
             Span::new_at("\n", 2, 1, 3),
             Expression::NAryOperation(
                 unary!(
-                    BitwiseComplement,
-                    nullary!(variable!(Span::new_at("x", 1, 1, 2)))
+                    BitwiseComplement, Span::new_at("~", 0, 1, 1),
+                    nullary!(variable!(Span::new_at("x", 1, 1, 2))),
+                    Span::new("~x")
                 )
             )
         ));
@@ -950,8 +1361,26 @@ mod tests {
             Span::new_at("\n", 2, 1, 3),
             Expression::NAryOperation(
                 unary!(
-                    Negate,
-                    nullary!(variable!(Span::new_at("x", 1, 1, 2)))
+                    Negate, Span::new_at("!", 0, 1, 1),
+                    nullary!(variable!(Span::new_at("x", 1, 1, 2))),
+                    Span::new("!x")
+                )
+            )
+        ));
+
+        assert_eq!(operation(input), output);
+    }
+
+    #[test]
+    fn case_unary_negate_keyword() {
+        let input  = Span::new("not x\n");
+        let output = Ok((
+            Span::new_at("\n", 5, 1, 6),
+            Expression::NAryOperation(
+                unary!(
+                    Negate, Span::new_at("not", 0, 1, 1),
+                    nullary!(variable!(Span::new_at("x", 4, 1, 5))),
+                    Span::new("not x")
                 )
             )
         ));
@@ -959,6 +1388,17 @@ mod tests {
         assert_eq!(operation(input), output);
     }
 
+    #[test]
+    fn case_unary_negate_keyword_prefix_of_identifier() {
+        let input  = Span::new("nothing\n");
+        let output = Ok((
+            Span::new_at("\n", 7, 1, 8),
+            Expression::NAryOperation(nullary!(variable!(Span::new_at("nothing", 0, 1, 1))))
+        ));
+
+        assert_eq!(operation(input), output);
+    }
+
     #[test]
     fn case_unary_multiple_negate() {
         let input  = Span::new("!!!x\n");
@@ -966,14 +1406,17 @@ mod tests {
             Span::new_at("\n", 4, 1, 5),
             Expression::NAryOperation(
                 unary!(
-                    Negate,
+                    Negate, Span::new_at("!", 0, 1, 1),
                     unary!(
-                        Negate,
+                        Negate, Span::new_at("!", 1, 1, 2),
                         unary!(
-                            Negate,
-                            nullary!(variable!(Span::new_at("x", 3, 1, 4)))
-                        )
-                    )
+                            Negate, Span::new_at("!", 2, 1, 3),
+                            nullary!(variable!(Span::new_at("x", 3, 1, 4))),
+                            Span::new_at("!x", 2, 1, 3)
+                        ),
+                        Span::new_at("!!x", 1, 1, 2)
+                    ),
+                    Span::new("!!!x")
                 )
             )
         ));
@@ -988,18 +1431,340 @@ mod tests {
             Span::new_at("\n", 5, 1, 6),
             Expression::NAryOperation(
                 unary!(
-                    BitwiseComplement,
+                    BitwiseComplement, Span::new_at("~", 0, 1, 1),
                     unary!(
-                        Negate,
+                        Negate, Span::new_at("!", 1, 1, 2),
                         unary!(
-                            Increment,
-                            nullary!(variable!(Span::new_at("x", 2, 1, 3)))
-                        )
-                    )
+                            Increment, Span::new_at("++", 3, 1, 4),
+                            nullary!(variable!(Span::new_at("x", 2, 1, 3))),
+                            Span::new_at("x++", 2, 1, 3)
+                        ),
+                        Span::new_at("!x++", 1, 1, 2)
+                    ),
+                    Span::new("~!x++")
+                )
+            )
+        ));
+
+        assert_eq!(operation(input), output);
+    }
+    #[test]
+    fn case_strict_logical_and() {
+        let input  = Span::new("1 && 2\n");
+        let output = Ok((
+            Span::new_at("\n", 6, 1, 7),
+            Expression::NAryOperation(
+                binary!(
+                    LogicalAnd, Span::new_at("&&", 2, 1, 3),
+                    nullary!(integer!(1, Span::new("1"))),
+                    nullary!(integer!(2, Span::new_at("2", 5, 1, 6))),
+                    Span::new("1 && 2")
+                )
+            )
+        ));
+
+        assert_eq!(strict!(operation(input)), output);
+    }
+
+    #[test]
+    fn case_invalid_strict_binary_bitwise_and() {
+        let input  = Span::new("1 & 2\n");
+        let output = extended_syntax!(Span::new_at("&", 2, 1, 3));
+
+        assert_eq!(strict!(operation(input)), output);
+    }
+
+    #[test]
+    fn case_invalid_strict_binary_bitwise_shift() {
+        let input  = Span::new("1 + 2 << 3\n");
+        let output = extended_syntax!(Span::new_at("<<", 6, 1, 7));
+
+        assert_eq!(strict!(operation(input)), output);
+    }
+
+    #[test]
+    fn case_strict_unary_negate() {
+        let input  = Span::new("!x\n");
+        let output = Ok((
+            Span::new_at("\n", 2, 1, 3),
+            Expression::NAryOperation(
+                unary!(
+                    Negate, Span::new_at("!", 0, 1, 1),
+                    nullary!(variable!(Span::new_at("x", 1, 1, 2))),
+                    Span::new("!x")
+                )
+            )
+        ));
+
+        assert_eq!(strict!(operation(input)), output);
+    }
+
+    #[test]
+    fn case_invalid_strict_unary_bitwise_complement() {
+        let input  = Span::new("!~x\n");
+        let output = extended_syntax!(Span::new_at("~", 1, 1, 2));
+
+        assert_eq!(strict!(operation(input)), output);
+    }
+
+    #[test]
+    fn case_invalid_strict_unary_increment() {
+        let input  = Span::new("1 + x++\n");
+        let output = extended_syntax!(Span::new_at("++", 5, 1, 6));
+
+        assert_eq!(strict!(operation(input)), output);
+    }
+
+    #[test]
+    fn case_unary_minus() {
+        let input  = Span::new("-x\n");
+        let output = Ok((
+            Span::new_at("\n", 2, 1, 3),
+            Expression::NAryOperation(
+                unary!(
+                    Minus, Span::new_at("-", 0, 1, 1),
+                    nullary!(variable!(Span::new_at("x", 1, 1, 2))),
+                    Span::new("-x")
+                )
+            )
+        ));
+
+        assert_eq!(operation(input), output);
+    }
+
+    #[test]
+    fn case_unary_plus() {
+        let input  = Span::new("+x\n");
+        let output = Ok((
+            Span::new_at("\n", 2, 1, 3),
+            Expression::NAryOperation(
+                unary!(
+                    Plus, Span::new_at("+", 0, 1, 1),
+                    nullary!(variable!(Span::new_at("x", 1, 1, 2))),
+                    Span::new("+x")
                 )
             )
         ));
 
         assert_eq!(operation(input), output);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn case_unary_multiple_minus() {
+        let input  = Span::new("- -x\n");
+        let output = Ok((
+            Span::new_at("\n", 4, 1, 5),
+            Expression::NAryOperation(
+                unary!(
+                    Minus, Span::new_at("-", 0, 1, 1),
+                    unary!(
+                        Minus, Span::new_at("-", 2, 1, 3),
+                        nullary!(variable!(Span::new_at("x", 3, 1, 4))),
+                        Span::new_at("-x", 2, 1, 3)
+                    ),
+                    Span::new("- -x")
+                )
+            )
+        ));
+
+        assert_eq!(operation(input), output);
+    }
+
+    #[test]
+    fn case_unary_postfix_chain() {
+        let input  = Span::new("x++--\n");
+        let output = Ok((
+            Span::new_at("\n", 5, 1, 6),
+            Expression::NAryOperation(
+                unary!(
+                    Decrement, Span::new_at("--", 3, 1, 4),
+                    unary!(
+                        Increment, Span::new_at("++", 1, 1, 2),
+                        nullary!(variable!(Span::new_at("x", 0, 1, 1))),
+                        Span::new("x++")
+                    ),
+                    Span::new("x++--")
+                )
+            )
+        ));
+
+        assert_eq!(operation(input), output);
+    }
+
+    #[test]
+    fn case_unary_minus_and_postfix_precedence() {
+        let input  = Span::new("-x--\n");
+        let output = Ok((
+            Span::new_at("\n", 4, 1, 5),
+            Expression::NAryOperation(
+                unary!(
+                    Minus, Span::new_at("-", 0, 1, 1),
+                    unary!(
+                        Decrement, Span::new_at("--", 2, 1, 3),
+                        nullary!(variable!(Span::new_at("x", 1, 1, 2))),
+                        Span::new_at("x--", 1, 1, 2)
+                    ),
+                    Span::new("-x--")
+                )
+            )
+        ));
+
+        assert_eq!(operation(input), output);
+    }
+
+    #[test]
+    fn case_binary_subtraction_of_negative() {
+        let input  = Span::new("1 - -2 * +3\n");
+        let output = Ok((
+            Span::new_at("\n", 11, 1, 12),
+            Expression::NAryOperation(
+                binary!(
+                    Subtraction, Span::new_at("-", 2, 1, 3),
+                    nullary!(integer!(1, Span::new("1"))),
+                    binary!(
+                        Multiplication, Span::new_at("*", 7, 1, 8),
+                        unary!(
+                            Minus, Span::new_at("-", 4, 1, 5),
+                            nullary!(integer!(2, Span::new_at("2", 5, 1, 6))),
+                            Span::new_at("-2", 4, 1, 5)
+                        ),
+                        unary!(
+                            Plus, Span::new_at("+", 9, 1, 10),
+                            nullary!(integer!(3, Span::new_at("3", 10, 1, 11))),
+                            Span::new_at("+3", 9, 1, 10)
+                        ),
+                        Span::new_at("-2 * +3", 4, 1, 5)
+                    ),
+                    Span::new("1 - -2 * +3")
+                )
+            )
+        ));
+
+        assert_eq!(operation(input), output);
+    }
+
+    #[test]
+    #[cfg(feature = "ink-numbers")]
+    fn case_binary_subtraction_of_minimum_integer() {
+        let input  = Span::new("1 - -2147483648\n");
+        let output = Ok((
+            Span::new_at("\n", 15, 1, 16),
+            Expression::NAryOperation(
+                binary!(
+                    Subtraction, Span::new_at("-", 2, 1, 3),
+                    nullary!(integer!(1, Span::new("1"))),
+                    nullary!(integer!(i32::MIN, Span::new_at("-2147483648", 4, 1, 5))),
+                    Span::new("1 - -2147483648")
+                )
+            )
+        ));
+
+        assert_eq!(operation(input), output);
+    }
+
+    #[test]
+    #[cfg(feature = "ink-numbers")]
+    fn case_invalid_negation_overflow() {
+        let input  = Span::new("-2147483649\n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at("2147483649", 1, 1, 2), ErrorKind::Custom(ErrorKindExtension::IntegerOverflow as u32))));
+
+        assert_eq!(operation(input), output);
+    }
+
+    #[test]
+    fn case_binary_greater_than_negative() {
+        let input  = Span::new("x > -3\n");
+        let output = Ok((
+            Span::new_at("\n", 6, 1, 7),
+            Expression::NAryOperation(
+                binary!(
+                    GreaterThan, Span::new_at(">", 2, 1, 3),
+                    nullary!(variable!(Span::new_at("x", 0, 1, 1))),
+                    unary!(
+                        Minus, Span::new_at("-", 4, 1, 5),
+                        nullary!(integer!(3, Span::new_at("3", 5, 1, 6))),
+                        Span::new_at("-3", 4, 1, 5)
+                    ),
+                    Span::new("x > -3")
+                )
+            )
+        ));
+
+        assert_eq!(operation(input), output);
+    }
+
+    #[test]
+    fn case_unary_matrix() {
+        // The input, the operator, the offset of the operand `x`, and
+        // whether the operator is accepted by the strict dialect.
+        let matrix = [
+            ("!x",    UnaryOperator::Negate,            1, true),
+            ("not x", UnaryOperator::Negate,            4, true),
+            ("-x",    UnaryOperator::Minus,             1, true),
+            ("+x",    UnaryOperator::Plus,              1, false),
+            ("~x",    UnaryOperator::BitwiseComplement, 1, false),
+            ("x++",   UnaryOperator::Increment,         0, false),
+            ("x--",   UnaryOperator::Decrement,         0, false)
+        ];
+
+        for &(source, operator, offset, strict) in matrix.iter() {
+            let operator_span = match offset {
+                0 => Span::new_at(&source[1..], 1, 1, 2),
+                _ => Span::new_at(source[..offset].trim_end(), 0, 1, 1)
+            };
+
+            let input  = format!("{}\n", source);
+            let output = Ok((
+                Span::new_at("\n", source.len(), 1, source.len() as u32 + 1),
+                Expression::NAryOperation(
+                    NAryOperation::Unary {
+                        operator: Token::new(operator, operator_span),
+                        operand : Box::new(nullary!(variable!(Span::new_at("x", offset, 1, offset as u32 + 1)))),
+                        span    : Span::new(source)
+                    }
+                )
+            ));
+
+            assert_eq!(operation(Span::new(&input)), output, "extended `{}`", source);
+
+            if strict {
+                assert_eq!(strict!(operation(Span::new(&input))), output, "strict `{}`", source);
+            } else {
+                assert!(strict!(operation(Span::new(&input))).is_err(), "strict `{}`", source);
+            }
+        }
+    }
+
+    #[test]
+    fn case_invalid_strict_unary_plus() {
+        let input  = Span::new("+x\n");
+        let output = extended_syntax!(Span::new_at("+", 0, 1, 1));
+
+        assert_eq!(strict!(operation(input)), output);
+    }
+
+    #[test]
+    fn case_invalid_parenthesized_operation_not_closed() {
+        let input  = Span::new("(1 + 2 \n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at("\n", 7, 1, 8), ErrorKind::Custom(ErrorKindExtension::RightParenthesis as u32))));
+
+        assert_eq!(operation(input), output);
+    }
+
+    #[test]
+    fn case_invalid_binary_operation_missing_right_operand() {
+        let input  = Span::new("(a + )\n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at(")\n", 5, 1, 6), ErrorKind::Custom(ErrorKindExtension::Expression as u32))));
+
+        assert_eq!(operation(input), output);
+        assert_eq!(operation(Span::new("1 * \n")), Err(Error::Failure(Context::Code(Span::new_at("\n", 4, 1, 5), ErrorKind::Custom(ErrorKindExtension::Expression as u32)))));
+    }
+
+    #[test]
+    fn case_binary_operation_before_divert_or_comment() {
+        let input = Span::new("x -> knot\n");
+
+        assert_eq!(operation(input).map(|(rest, _)| rest), Ok(Span::new_at(" -> knot\n", 1, 1, 2)));
+        assert_eq!(operation(Span::new("x // y\n")).map(|(rest, _)| rest), Ok(Span::new_at(" // y\n", 1, 1, 2)));
+    }
+}
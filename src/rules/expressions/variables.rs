@@ -2,10 +2,7 @@ use rules::identifier::identifier;
 use span::Span;
 use tokens;
 
-use ast::ast::{
-    Variable,
-    Expression
-};
+use ast::ast::Variable;
 
 named_attr!(
     #[doc="
@@ -59,16 +56,7 @@ mod tests {
         global_constant
     };
 
-    use ast::ast::{
-        Variable,
-        Expression
-    };
-
-    use internal::{
-        Context,
-        Error,
-        ErrorKind
-    };
+    use ast::ast::Variable;
 
     use span::Span;
 
@@ -2,11 +2,7 @@ use span::Span;
 use internal::ErrorKindExtension;
 use tokens;
 
-use nom::{
-    IResult,
-    digit,
-    alphanumeric
-};
+use nom::IResult;
 
 #[inline]
 pub fn is_identifier(chr: char) -> bool {
@@ -27,7 +23,7 @@ pub fn parse_identifier(input: Span) -> IResult<Span, Span> {
     match input.position(|item| !is_identifier(item)) {
         Some(0) => Err(Err::Error(error_position!(input, ErrorKind::Custom(ErrorKindExtension::Identifier as u32)))),
         Some(n) => {
-            match input.as_slice().chars().next().and_then(|chr| Some(chr.is_numeric())) {
+            match input.as_slice().chars().next().map(|chr| chr.is_numeric()) {
                 Some(false) => { Ok((input.slice(n..), input.slice(..n))) },
                 _ => { Err(Err::Error(error_position!(input, ErrorKind::Custom(ErrorKindExtension::Identifier as u32)))) }
             }
@@ -66,11 +62,6 @@ named_attr!(
 mod tests {
     use super::identifier;
 
-    use ast::ast::{
-        Literal,
-        Token
-    };
-
     use internal::{
         Context,
         Error,
@@ -25,14 +25,7 @@ mod tests {
         Token
     };
 
-    use internal::{
-        Context,
-        Error,
-        ErrorKind
-    };
-
     use span::Span;
-    use internal::ErrorKindExtension;
 
     #[test]
     fn case_literal_boolean() {
@@ -50,7 +43,7 @@ mod tests {
 
         assert_eq!(literal(input), Ok((
             Span::new_at("\n", 19, 1, 20),
-            Literal::Integer(Token::new(78557i64, Span::new_at("10011001011011101", 2, 1, 3)))
+            Literal::Integer(Token::new(78557, Span::new_at("10011001011011101", 2, 1, 3)))
         )));
     }
 
@@ -60,7 +53,7 @@ mod tests {
 
         assert_eq!(literal(input), Ok((
             Span::new_at("\n", 7, 1, 8),
-            Literal::Integer(Token::new(78557i64, Span::new_at("132DD", 2, 1, 3)))
+            Literal::Integer(Token::new(78557, Span::new_at("132DD", 2, 1, 3)))
         )));
     }
 
@@ -70,7 +63,7 @@ mod tests {
 
         assert_eq!(literal(input), Ok((
             Span::new_at("\n", 7, 1, 8),
-            Literal::Integer(Token::new(78557i64, Span::new_at("231335", 1, 1, 2)))
+            Literal::Integer(Token::new(78557, Span::new_at("231335", 1, 1, 2)))
         )));
     }
 
@@ -80,14 +73,17 @@ mod tests {
 
         assert_eq!(literal(input), Ok((
             Span::new_at("\n", 5, 1, 6),
-            Literal::Integer(Token::new(78557i64, Span::new_at("78557", 0, 1, 1)))
+            Literal::Integer(Token::new(78557, Span::new_at("78557", 0, 1, 1)))
         )));
     }
 
     #[test]
     fn case_literal_real() {
         let input  = Span::new("1.6180339887498948482\n");
-        let value = ‎1.6180339887498948482f64;
+        #[cfg(not(feature = "ink-numbers"))]
+        let value = 1.618_033_988_749_895_f64;
+        #[cfg(feature = "ink-numbers")]
+        let value = 1.618_034_f32;
 
         assert_eq!(literal(input), Ok((
             Span::new_at("\n", 21, 1, 22),
@@ -1,9 +1,12 @@
-mod comments;
-mod identifier;
-mod number;
-mod literals;
-mod skip;
-mod string;
-mod whitespaces;
-mod expressions;
-mod statements;
\ No newline at end of file
+pub mod comments;
+pub mod content;
+pub mod dialect;
+pub mod identifier;
+pub mod number;
+pub mod literals;
+pub mod skip;
+pub mod string;
+pub mod whitespaces;
+pub mod expressions;
+pub mod statements;
+pub mod story;
\ No newline at end of file
@@ -1,19 +1,17 @@
 use ast::ast::{
+    Integer,
     Literal,
+    Real,
     Token
 };
 
 use span::Span;
 use internal::ErrorKindExtension;
+use rules::dialect::extended_syntax;
 use tokens;
 
 use std::result::Result as StdResult;
 
-use std::num::{
-    ParseFloatError,
-    ParseIntError
-};
-
 use std::str::{
     FromStr,
     ParseBoolError
@@ -49,7 +47,9 @@ named_attr!(
 named_attr!(
     #[doc="
         Recognize an integer.
-        An integer is either a binary, a decimal, an hexadecimal or an octal representation.
+        An integer is either a binary, a decimal, an hexadecimal or an octal representation,
+        or the smallest integer.
+        Only the decimal representation is accepted by the strict dialect.
     "],
     pub integer<Span, Literal>,
     alt_complete!(
@@ -57,6 +57,7 @@ named_attr!(
         | hexadecimal
         | decimal
         | octal
+        | minimum_integer
     )
 );
 
@@ -160,101 +161,98 @@ named_attr!(
         Recognize an integer with the binary notation.
     "],
     pub binary<Span, Literal>,
-    map_res!(
-        preceded!(
-            tag!("0b"),
-            call!(binary_digit)
-        ),
-        binary_mapper
+    do_parse!(
+        prefix: tag!("0b") >>
+        digits: call!(binary_digit) >>
+        call!(extended_syntax, prefix) >>
+        literal: call!(integer_literal, digits, 2) >>
+        (literal)
     )
 );
 
-#[inline]
-fn binary_mapper(span: Span) -> StdResult<Literal, ParseIntError> {
-    i64::from_str_radix(span.as_slice(), 2)
-        .and_then(
-            | binary| {
-                Ok(Literal::Integer(Token::new(binary, span)))
-            }
-        )
-}
-
 named_attr!(
     #[doc="
         Recognize an integer with the hexadecimal notation.
     "],
     pub hexadecimal<Span, Literal>,
-    map_res!(
-        preceded!(
-            tag!("0x"),
-            call!(hex_digit)
-        ),
-        hexadecimal_mapper
+    do_parse!(
+        prefix: tag!("0x") >>
+        digits: call!(hex_digit) >>
+        call!(extended_syntax, prefix) >>
+        literal: call!(integer_literal, digits, 16) >>
+        (literal)
     )
 );
 
-#[inline]
-fn hexadecimal_mapper(span: Span) -> StdResult<Literal, ParseIntError> {
-    i64::from_str_radix(span.as_slice(), 16)
-        .and_then(
-            | hex| {
-                Ok(Literal::Integer(Token::new(hex, span)))
-            }
-        )
-}
-
 named_attr!(
     #[doc="
         Recognize an integer with the octal notation.
     "],
     pub octal<Span, Literal>,
-    map_res!(
-        preceded!(
-            tag!("0"),
-            call!(oct_digit)
-        ),
-        octal_mapper
+    do_parse!(
+        prefix: tag!("0") >>
+        digits: call!(oct_digit) >>
+        call!(extended_syntax, prefix) >>
+        literal: call!(integer_literal, digits, 8) >>
+        (literal)
     )
 );
 
-#[inline]
-fn octal_mapper(span: Span) -> StdResult<Literal, ParseIntError> {
-    i64::from_str_radix(span.as_slice(), 8)
-        .and_then(
-            | octal| {
-                Ok(Literal::Integer(Token::new(octal, span)))
-            }
-        )
-}
-
 named_attr!(
     #[doc="
         Recognize an integer with the decimal notation.
     "],
     pub decimal<Span, Literal>,
-    map_res!(
-        call!(decimal_digit),
-        decimal_mapper
+    do_parse!(
+        digits: call!(decimal_digit) >>
+        literal: call!(integer_literal, digits, 10) >>
+        (literal)
     )
 );
 
-#[inline]
-fn decimal_mapper(span: Span) -> StdResult<Literal, ParseFloatError> {
-    i64::from_str(span.as_slice())
-        .and_then(
-            | decimal| {
-                Ok(Literal::Integer(Token::new(decimal, span)))
+/// Recognize the smallest integer, e.g. `-2147483648` with `ink-numbers`.
+///
+/// The magnitude of the smallest integer does not fit in `Integer`, so it
+/// cannot be the negation of a decimal: the minus sign belongs to the
+/// literal. Any other negative integer is a negation.
+pub fn minimum_integer(input: Span) -> IResult<Span, Literal> {
+    use nom::{
+        Err,
+        ErrorKind
+    };
+
+    let (next_input, integer) = recognize!(input, preceded!(tag!(tokens::SUBSTRACT), call!(decimal_digit)))?;
+
+    match Integer::from_str(integer.as_slice()) {
+        Ok(Integer::MIN) => Ok((next_input, Literal::Integer(Token::new(Integer::MIN, integer)))),
+        _                => Err(Err::Error(error_position!(input, ErrorKind::Digit)))
+    }
+}
+
+/// Convert the digits of an integer written in `radix` to an integer literal.
+///
+/// The digits are already validated, so the conversion can only fail if the
+/// integer does not fit in `Integer`. This is a failure rather than an error:
+/// the literal must not be re-read with another notation or as a real.
+fn integer_literal<'a>(input: Span<'a>, digits: Span<'a>, radix: u32) -> IResult<Span<'a>, Literal<'a>> {
+    use nom::{
+        Err,
+        ErrorKind
+    };
+
+    Integer::from_str_radix(digits.as_slice(), radix)
+        .map(
+            |integer| {
+                (input, Literal::Integer(Token::new(integer, digits)))
             }
-        ).or_else(
-            |_: ParseIntError| {
-                f64::from_str(span.as_slice())
-                    .and_then(
-                        | decimal | {
-                            Ok(Literal::Real(Token::new(decimal, span)))
-                        }
-                    )
+        ).map_err(
+            |_| {
+                Err::Failure(error_position!(
+                    digits,
+                    ErrorKind::Custom(ErrorKindExtension::IntegerOverflow as u32)
+                ))
             }
-    )
+        )
 }
 
 named_attr!(
@@ -262,20 +260,33 @@ named_attr!(
         Recognize a real number.
     "],
     pub real<Span, Literal>,
-    map_res!(
-        call!(real_digit),
-        real_mapper
+    do_parse!(
+        digits: call!(real_digit) >>
+        literal: call!(real_literal, digits) >>
+        (literal)
     )
 );
 
-#[inline]
-fn real_mapper(span: Span) -> StdResult<Literal, ParseFloatError> {
-    f64::from_str(span.as_slice())
-        .and_then(
-            | decimal | {
-                Ok(Literal::Real(Token::new(decimal, span)))
-            }
-        )
+/// Convert the digits of a real to a real literal.
+///
+/// The digits are already validated, so the conversion can only fail if the
+/// real is too large for `Real`, which would round it to infinity. Like an
+/// integer overflow, this is a failure rather than an error.
+fn real_literal<'a>(input: Span<'a>, digits: Span<'a>) -> IResult<Span<'a>, Literal<'a>> {
+    use nom::{
+        Err,
+        ErrorKind
+    };
+
+    match Real::from_str(digits.as_slice()) {
+        Ok(real) if real.is_finite() => Ok((input, Literal::Real(Token::new(real, digits)))),
+        _                            => {
+            Err(Err::Failure(error_position!(
+                digits,
+                ErrorKind::Custom(ErrorKindExtension::RealOverflow as u32)
+            )))
+        }
+    }
 }
 
 named_attr!(
@@ -294,9 +305,9 @@ named_attr!(
 #[inline]
 fn boolean_mapper(span: Span) -> StdResult<Literal, ParseBoolError> {
     bool::from_str(span.as_slice())
-        .and_then(
-            | boolean | {
-                Ok(Literal::Boolean(Token::new(boolean, span)))
+        .map(
+            |boolean| {
+                Literal::Boolean(Token::new(boolean, span))
             }
         )
 }
@@ -304,6 +315,7 @@ fn boolean_mapper(span: Span) -> StdResult<Literal, ParseBoolError> {
 #[cfg(test)]
 mod tests {
     use super::{
+        number,
         integer,
         binary,
         hexadecimal,
@@ -325,14 +337,31 @@ mod tests {
     };
 
     use span::Span;
+    use state;
     use internal::ErrorKindExtension;
+    use options::{
+        Dialect,
+        ParserOptions
+    };
+
+    macro_rules! strict {
+        ($parser:ident($input:expr)) => (
+            state::with_state($input.as_slice(), ParserOptions::new(Dialect::Strict), || $parser($input))
+        )
+    }
+
+    macro_rules! extended_syntax {
+        ($span:expr) => (
+            Err(Error::Failure(Context::Code($span, ErrorKind::Custom(ErrorKindExtension::ExtendedSyntax as u32))))
+        )
+    }
 
     #[test]
     fn case_binary() {
         let input  = Span::new("0b101010\n");
         let output = Ok((
             Span::new_at("\n", 8, 1, 9),
-            Literal::Integer(Token::new(42i64, Span::new_at("101010", 2, 1, 3)))
+            Literal::Integer(Token::new(42, Span::new_at("101010", 2, 1, 3)))
         ));
 
         assert_eq!(binary(input),  output);
@@ -340,11 +369,12 @@ mod tests {
     }
 
     #[test]
+    #[cfg(not(feature = "ink-numbers"))]
     fn case_binary_maximum_integer_value() {
         let input  = Span::new("0b111111111111111111111111111111111111111111111111111111111111111\n");
         let output = Ok((
             Span::new_at("\n", 65, 1, 66),
-            Literal::Integer(Token::new(::std::i64::MAX,
+            Literal::Integer(Token::new(i64::MAX,
                                         Span::new_at("111111111111111111111111111111111111111111111111111111111111111",
                                                      2, 1, 3)))
         ));
@@ -354,15 +384,25 @@ mod tests {
     }
 
     #[test]
-    fn case_invalid_binary_overflow() {
-        let input  = Span::new("0b1000000000000000000000000000000000000000000000000000000000000000\n");
+    #[cfg(feature = "ink-numbers")]
+    fn case_binary_maximum_integer_value() {
+        let input  = Span::new("0b1111111111111111111111111111111\n");
         let output = Ok((
-            Span::new_at("b1000000000000000000000000000000000000000000000000000000000000000\n", 1, 1, 2),
-            Literal::Integer(Token::new(0i64, Span::new("0")))
+            Span::new_at("\n", 33, 1, 34),
+            Literal::Integer(Token::new(i32::MAX, Span::new_at("1111111111111111111111111111111", 2, 1, 3)))
         ));
 
-        assert_eq!(binary(input), Err(Error::Error(Context::Code(input, ErrorKind::MapRes))));
-        assert_eq!(integer(input),  output);
+        assert_eq!(binary(input), output);
+        assert_eq!(integer(input), output);
+    }
+
+    #[test]
+    fn case_invalid_binary_overflow() {
+        let input  = Span::new("0b1000000000000000000000000000000000000000000000000000000000000000\n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at("1000000000000000000000000000000000000000000000000000000000000000", 2, 1, 3), ErrorKind::Custom(ErrorKindExtension::IntegerOverflow as u32))));
+
+        assert_eq!(binary(input), output);
+        assert_eq!(integer(input), output);
     }
 
     #[test]
@@ -395,7 +435,7 @@ mod tests {
         let input  = Span::new("0b120");
         let output = Ok((
             Span::new_at("20", 3, 1, 4),
-            Literal::Integer(Token::new(1i64, Span::new_at("1", 2, 1, 3)))
+            Literal::Integer(Token::new(1, Span::new_at("1", 2, 1, 3)))
         ));
 
         assert_eq!(binary(input),  output);
@@ -407,7 +447,7 @@ mod tests {
         let input  = Span::new("0x2a\n");
         let output = Ok((
             Span::new_at("\n", 4, 1, 5),
-            Literal::Integer(Token::new(42i64, Span::new_at("2a", 2, 1, 3)))
+            Literal::Integer(Token::new(42, Span::new_at("2a", 2, 1, 3)))
         ));
 
         assert_eq!(hexadecimal(input), output);
@@ -419,7 +459,7 @@ mod tests {
         let input  = Span::new("0x2A\n");
         let output = Ok((
             Span::new_at("\n", 4, 1, 5),
-            Literal::Integer(Token::new(42i64, Span::new_at("2A", 2, 1, 3)))
+            Literal::Integer(Token::new(42, Span::new_at("2A", 2, 1, 3)))
         ));
 
         assert_eq!(hexadecimal(input), output);
@@ -453,11 +493,12 @@ mod tests {
     }
 
     #[test]
+    #[cfg(not(feature = "ink-numbers"))]
     fn case_hexadecimal_maximum_integer_value() {
         let input  = Span::new("0x7fffffffffffffff\n");
         let output = Ok((
             Span::new_at("\n", 18, 1, 19),
-            Literal::Integer(Token::new(::std::i64::MAX, Span::new_at("7fffffffffffffff", 2, 1, 3)))
+            Literal::Integer(Token::new(i64::MAX, Span::new_at("7fffffffffffffff", 2, 1, 3)))
         ));
 
         assert_eq!(hexadecimal(input), output);
@@ -465,14 +506,24 @@ mod tests {
     }
 
     #[test]
-    fn case_invalid_hexadecimal_overflow() {
-        let input  = Span::new("0x8000000000000000\n");
+    #[cfg(feature = "ink-numbers")]
+    fn case_hexadecimal_maximum_integer_value() {
+        let input  = Span::new("0x7fffffff\n");
         let output = Ok((
-            Span::new_at("x8000000000000000\n", 1, 1, 2),
-            Literal::Integer(Token::new(0, Span::new_at("0", 0, 1, 1)))
+            Span::new_at("\n", 10, 1, 11),
+            Literal::Integer(Token::new(i32::MAX, Span::new_at("7fffffff", 2, 1, 3)))
         ));
 
-        assert_eq!(hexadecimal(input), Err(Error::Error(Context::Code(input, ErrorKind::MapRes))));
+        assert_eq!(hexadecimal(input), output);
+        assert_eq!(integer(input), output);
+    }
+
+    #[test]
+    fn case_invalid_hexadecimal_overflow() {
+        let input  = Span::new("0x8000000000000000\n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at("8000000000000000", 2, 1, 3), ErrorKind::Custom(ErrorKindExtension::IntegerOverflow as u32))));
+
+        assert_eq!(hexadecimal(input), output);
         assert_eq!(integer(input), output);
     }
 
@@ -481,7 +532,7 @@ mod tests {
         let input  = Span::new("052\n");
         let output = Ok((
             Span::new_at("\n", 3, 1, 4),
-            Literal::Integer(Token::new(42i64, Span::new_at("52", 1, 1, 2)))
+            Literal::Integer(Token::new(42, Span::new_at("52", 1, 1, 2)))
         ));
 
         assert_eq!(octal(input), output);
@@ -493,7 +544,7 @@ mod tests {
         let input  = Span::new("0\n");
         let output = Ok((
             Span::new_at("\n", 1, 1, 2),
-            Literal::Integer(Token::new(0i64, Span::new_at("0", 0, 1, 1)))
+            Literal::Integer(Token::new(0, Span::new_at("0", 0, 1, 1)))
         ));
 
         assert_eq!(octal(input), Err(Error::Error(Context::Code(Span::new_at("\n", 1, 1, 2),
@@ -502,11 +553,25 @@ mod tests {
     }
 
     #[test]
+    #[cfg(not(feature = "ink-numbers"))]
     fn case_octal_maximum_integer_value() {
         let input  = Span::new("0777777777777777777777\n");
         let output = Ok((
             Span::new_at("\n", 22, 1, 23),
-            Literal::Integer(Token::new(::std::i64::MAX, Span::new_at("777777777777777777777", 1, 1, 2)))
+            Literal::Integer(Token::new(i64::MAX, Span::new_at("777777777777777777777", 1, 1, 2)))
+        ));
+
+        assert_eq!(octal(input), output);
+        assert_eq!(integer(input), output);
+    }
+
+    #[test]
+    #[cfg(feature = "ink-numbers")]
+    fn case_octal_maximum_integer_value() {
+        let input  = Span::new("017777777777\n");
+        let output = Ok((
+            Span::new_at("\n", 12, 1, 13),
+            Literal::Integer(Token::new(i32::MAX, Span::new_at("17777777777", 1, 1, 2)))
         ));
 
         assert_eq!(octal(input), output);
@@ -516,9 +581,9 @@ mod tests {
     #[test]
     fn case_invalid_octal_overflow() {
         let input  = Span::new("01000000000000000000000\n");
-        let output = Err(Error::Error(Context::Code(input, ErrorKind::Alt)));
+        let output = Err(Error::Failure(Context::Code(Span::new_at("1000000000000000000000", 1, 1, 2), ErrorKind::Custom(ErrorKindExtension::IntegerOverflow as u32))));
 
-        assert_eq!(octal(input), Err(Error::Error(Context::Code(input, ErrorKind::MapRes))));
+        assert_eq!(octal(input), output);
         assert_eq!(integer(input), output);
     }
 
@@ -527,7 +592,7 @@ mod tests {
         let input  = Span::new("7\n");
         let output = Ok((
             Span::new_at("\n", 1, 1, 2),
-            Literal::Integer(Token::new(7i64, Span::new_at("7", 0, 1, 1)))
+            Literal::Integer(Token::new(7, Span::new_at("7", 0, 1, 1)))
         ));
 
         assert_eq!(octal(input), Err(Error::Error(Context::Code(input, ErrorKind::Tag))));
@@ -539,7 +604,7 @@ mod tests {
         let input  = Span::new("8\n");
         let output = Ok((
             Span::new_at("\n", 1, 1, 2),
-            Literal::Integer(Token::new(8i64, Span::new_at("8", 0, 1, 1)))
+            Literal::Integer(Token::new(8, Span::new_at("8", 0, 1, 1)))
         ));
 
         assert_eq!(octal(input), Err(Error::Error(Context::Code(input, ErrorKind::Tag))));
@@ -551,7 +616,7 @@ mod tests {
         let input  = Span::new("7\n");
         let output = Ok((
             Span::new_at("\n", 1, 1, 2),
-            Literal::Integer(Token::new(7i64, Span::new_at("7", 0, 1, 1)))
+            Literal::Integer(Token::new(7, Span::new_at("7", 0, 1, 1)))
         ));
 
         assert_eq!(decimal(input), output);
@@ -563,7 +628,7 @@ mod tests {
         let input  = Span::new("42\n");
         let output = Ok((
             Span::new_at("\n", 2, 1, 3),
-            Literal::Integer(Token::new(42i64, Span::new_at("42", 0, 1, 1)))
+            Literal::Integer(Token::new(42, Span::new_at("42", 0, 1, 1)))
         ));
 
         assert_eq!(decimal(input), output);
@@ -575,7 +640,7 @@ mod tests {
         let input  = Span::new("0\n");
         let output = Ok((
             Span::new_at("\n", 1, 1, 2),
-            Literal::Integer(Token::new(0i64, Span::new_at("0", 0, 1, 1)))
+            Literal::Integer(Token::new(0, Span::new_at("0", 0, 1, 1)))
         ));
 
         assert_eq!(decimal(input), output);
@@ -587,7 +652,7 @@ mod tests {
         let input  = Span::new("42+");
         let output = Ok((
             Span::new_at("+", 2, 1, 3),
-            Literal::Integer(Token::new(42i64, Span::new("42")))
+            Literal::Integer(Token::new(42, Span::new("42")))
         ));
 
         assert_eq!(decimal(input), output);
@@ -595,11 +660,12 @@ mod tests {
     }
 
     #[test]
+    #[cfg(not(feature = "ink-numbers"))]
     fn case_decimal_maximum_integer_value() {
         let input  = Span::new("9223372036854775807\n");
         let output = Ok((
             Span::new_at("\n", 19, 1, 20),
-            Literal::Integer(Token::new(::std::i64::MAX, Span::new_at("9223372036854775807", 0, 1, 1)))
+            Literal::Integer(Token::new(i64::MAX, Span::new_at("9223372036854775807", 0, 1, 1)))
         ));
 
         assert_eq!(decimal(input), output);
@@ -607,11 +673,12 @@ mod tests {
     }
 
     #[test]
-    fn case_decimal_overflow_to_real() {
-        let input  = Span::new("9223372036854775808\n");
+    #[cfg(feature = "ink-numbers")]
+    fn case_decimal_maximum_integer_value() {
+        let input  = Span::new("2147483647\n");
         let output = Ok((
-            Span::new_at("\n", 19, 1, 20),
-            Literal::Real(Token::new(9223372036854775808f64, Span::new_at("9223372036854775808", 0, 1, 1)))
+            Span::new_at("\n", 10, 1, 11),
+            Literal::Integer(Token::new(i32::MAX, Span::new_at("2147483647", 0, 1, 1)))
         ));
 
         assert_eq!(decimal(input), output);
@@ -619,35 +686,112 @@ mod tests {
     }
 
     #[test]
-    fn case_decimal_maximum_real_value() {
-        let input  = Span::new("179769313486231570000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000\n");
+    #[cfg(feature = "ink-numbers")]
+    fn case_invalid_decimal_overflow_ink_integer() {
+        let input  = Span::new("2147483648\n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at("2147483648", 0, 1, 1), ErrorKind::Custom(ErrorKindExtension::IntegerOverflow as u32))));
+
+        assert_eq!(decimal(input), output);
+        assert_eq!(integer(input), output);
+    }
+
+    #[test]
+    #[cfg(not(feature = "ink-numbers"))]
+    fn case_minimum_integer() {
+        let input  = Span::new("-9223372036854775808\n");
         let output = Ok((
-            Span::new_at("\n", 309, 1, 310),
-            Literal::Real(Token::new(::std::f64::MAX, Span::new_at("179769313486231570000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000", 0, 1, 1)))
+            Span::new_at("\n", 20, 1, 21),
+            Literal::Integer(Token::new(i64::MIN, Span::new_at("-9223372036854775808", 0, 1, 1)))
         ));
 
-        assert_eq!(decimal(input), output);
         assert_eq!(integer(input), output);
+        assert_eq!(number(input), output);
     }
 
     #[test]
-    fn case_invalid_decimal_overflow_to_infinity() {
-        let input  = Span::new("1797693134862315700000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000\n");
+    #[cfg(feature = "ink-numbers")]
+    fn case_minimum_integer_ink_integer() {
+        let input  = Span::new("-2147483648\n");
         let output = Ok((
-            Span::new_at("\n", 310, 1, 311),
-            Literal::Real(Token::new(::std::f64::INFINITY, Span::new_at("1797693134862315700000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000", 0, 1, 1)))
+            Span::new_at("\n", 11, 1, 12),
+            Literal::Integer(Token::new(i32::MIN, Span::new_at("-2147483648", 0, 1, 1)))
         ));
 
+        assert_eq!(integer(input), output);
+        assert_eq!(number(input), output);
+    }
+
+    #[test]
+    fn case_invalid_minimum_integer_not_minimum() {
+        let input = Span::new("-42\n");
+
+        assert!(integer(input).is_err());
+    }
+
+    #[test]
+    fn case_invalid_decimal_overflow() {
+        let input  = Span::new("9223372036854775808\n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at("9223372036854775808", 0, 1, 1), ErrorKind::Custom(ErrorKindExtension::IntegerOverflow as u32))));
+
+        assert_eq!(decimal(input), output);
+        assert_eq!(integer(input), output);
+    }
+
+    #[test]
+    fn case_invalid_decimal_overflow_not_read_as_real() {
+        let input  = Span::new("1797693134862315700000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000\n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at("1797693134862315700000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000", 0, 1, 1), ErrorKind::Custom(ErrorKindExtension::IntegerOverflow as u32))));
+
         assert_eq!(decimal(input), output);
         assert_eq!(integer(input), output);
+        assert_eq!(number(input), output);
+    }
+
+    #[test]
+    fn case_strict_decimal() {
+        let input  = Span::new("42\n");
+        let output = Ok((
+            Span::new_at("\n", 2, 1, 3),
+            Literal::Integer(Token::new(42, Span::new_at("42", 0, 1, 1)))
+        ));
+
+        assert_eq!(strict!(integer(input)), output);
+    }
+
+    #[test]
+    fn case_invalid_strict_binary() {
+        let input  = Span::new("0b101010\n");
+        let output = extended_syntax!(Span::new_at("0b", 0, 1, 1));
+
+        assert_eq!(strict!(binary(input)), output);
+        assert_eq!(strict!(integer(input)), output);
+    }
+
+    #[test]
+    fn case_invalid_strict_hexadecimal() {
+        let input  = Span::new("0x2a\n");
+        let output = extended_syntax!(Span::new_at("0x", 0, 1, 1));
+
+        assert_eq!(strict!(hexadecimal(input)), output);
+        assert_eq!(strict!(integer(input)), output);
+    }
+
+    #[test]
+    fn case_invalid_strict_octal() {
+        let input  = Span::new("052\n");
+        let output = extended_syntax!(Span::new_at("0", 0, 1, 1));
+
+        assert_eq!(strict!(octal(input)), output);
+        assert_eq!(strict!(integer(input)), output);
     }
 
     #[test]
+    #[cfg(not(feature = "ink-numbers"))]
     fn case_real() {
         let input  = Span::new("123.456e+78\n");
         let output = Ok((
             Span::new_at("\n", 11, 1, 12),
-            Literal::Real(Token::new(123.456e78f64, Span::new_at("123.456e+78", 0, 1, 1)))
+            Literal::Real(Token::new(123.456e78,  Span::new_at("123.456e+78", 0, 1, 1)))
         ));
 
         assert_eq!(real(input), output);
@@ -658,7 +802,7 @@ mod tests {
         let input  = Span::new("0.456\n");
         let output = Ok((
             Span::new_at("\n", 5, 1, 6),
-            Literal::Real(Token::new(0.456f64, Span::new_at("0.456", 0, 1, 1)))
+            Literal::Real(Token::new(0.456, Span::new_at("0.456", 0, 1, 1)))
         ));
 
         assert_eq!(real(input), output);
@@ -669,7 +813,7 @@ mod tests {
         let input  = Span::new("123.\n");
         let output = Ok((
             Span::new_at("\n", 4, 1, 5),
-            Literal::Real(Token::new(123.0f64, Span::new_at("123.", 0, 1, 1)))
+            Literal::Real(Token::new(123.0, Span::new_at("123.", 0, 1, 1)))
         ));
 
         assert_eq!(real(input), output);
@@ -680,73 +824,98 @@ mod tests {
         let input  = Span::new(".456\n");
         let output = Ok((
             Span::new_at("\n", 4, 1, 5),
-            Literal::Real(Token::new(0.456f64, Span::new_at(".456", 0, 1, 1)))
+            Literal::Real(Token::new(0.456, Span::new_at(".456", 0, 1, 1)))
         ));
 
         assert_eq!(real(input), output);
     }
 
     #[test]
+    #[cfg(not(feature = "ink-numbers"))]
     fn case_real_only_with_rational_and_exponent_part_with_lowercase_e() {
         let input  = Span::new("123.e78\n");
         let output = Ok((
             Span::new_at("\n", 7, 1, 8),
-            Literal::Real(Token::new(123e78f64, Span::new_at("123.e78", 0, 1, 1)))
+            Literal::Real(Token::new(123e78,  Span::new_at("123.e78", 0, 1, 1)))
         ));
 
         assert_eq!(real(input), output);
     }
 
     #[test]
+    #[cfg(feature = "ink-numbers")]
+    fn case_invalid_real_overflow_ink_real() {
+        let input  = Span::new("123.e78\n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at("123.e78", 0, 1, 1), ErrorKind::Custom(ErrorKindExtension::RealOverflow as u32))));
+
+        assert_eq!(real(input), output);
+        assert_eq!(number(input), output);
+    }
+
+    #[test]
+    fn case_invalid_real_overflow() {
+        let input  = Span::new("1e400\n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at("1e400", 0, 1, 1), ErrorKind::Custom(ErrorKindExtension::RealOverflow as u32))));
+
+        assert_eq!(real(input), output);
+        assert_eq!(number(input), output);
+    }
+
+    #[test]
+    #[cfg(not(feature = "ink-numbers"))]
     fn case_real_only_with_integer_rational_and_exponent_part() {
         let input  = Span::new("123e78\n");
         let output = Ok((
             Span::new_at("\n", 6, 1, 7),
-            Literal::Real(Token::new(123e78f64, Span::new_at("123e78", 0, 1, 1)))
+            Literal::Real(Token::new(123e78,  Span::new_at("123e78", 0, 1, 1)))
         ));
 
         assert_eq!(real(input), output);
     }
 
     #[test]
+    #[cfg(not(feature = "ink-numbers"))]
     fn case_real_only_with_rational_and_exponent_part_with_uppercase_e() {
         let input  = Span::new("123.E78\n");
         let output = Ok((
             Span::new_at("\n", 7, 1, 8),
-            Literal::Real(Token::new(123e78f64, Span::new_at("123.E78", 0, 1, 1)))
+            Literal::Real(Token::new(123e78,  Span::new_at("123.E78", 0, 1, 1)))
         ));
 
         assert_eq!(real(input), output);
     }
 
     #[test]
+    #[cfg(not(feature = "ink-numbers"))]
     fn case_real_only_with_rational_and_unsigned_exponent_part() {
         let input  = Span::new("123.e78\n");
         let output = Ok((
             Span::new_at("\n", 7, 1, 8),
-            Literal::Real(Token::new(123e78f64, Span::new_at("123.e78", 0, 1, 1)))
+            Literal::Real(Token::new(123e78,  Span::new_at("123.e78", 0, 1, 1)))
         ));
 
         assert_eq!(real(input), output);
     }
 
     #[test]
+    #[cfg(not(feature = "ink-numbers"))]
     fn case_real_only_with_rational_and_positive_exponent_part() {
         let input  = Span::new("123.e+78\n");
         let output = Ok((
             Span::new_at("\n", 8, 1, 9),
-            Literal::Real(Token::new(123e78f64, Span::new_at("123.e+78", 0, 1, 1)))
+            Literal::Real(Token::new(123e78,  Span::new_at("123.e+78", 0, 1, 1)))
         ));
 
         assert_eq!(real(input), output);
     }
 
     #[test]
+    #[cfg(not(feature = "ink-numbers"))]
     fn case_real_only_with_rational_and_negative_exponent_part() {
         let input  = Span::new("123.e-78\n");
         let output = Ok((
             Span::new_at("\n", 8, 1, 9),
-            Literal::Real(Token::new(123e-78f64, Span::new_at("123.e-78", 0, 1, 1)))
+            Literal::Real(Token::new(123e-78,  Span::new_at("123.e-78", 0, 1, 1)))
         ));
 
         assert_eq!(real(input), output);
@@ -757,7 +926,7 @@ mod tests {
         let input  = Span::new("123.e-0\n");
         let output = Ok((
             Span::new_at("\n", 7, 1, 8),
-            Literal::Real(Token::new(123f64, Span::new_at("123.e-0", 0, 1, 1)))
+            Literal::Real(Token::new(123.0, Span::new_at("123.e-0", 0, 1, 1)))
         ));
 
         assert_eq!(real(input), output);
@@ -768,7 +937,7 @@ mod tests {
         let input  = Span::new(".7e\n");
         let output = Ok((
             Span::new_at("e\n", 2, 1, 3),
-            Literal::Real(Token::new(0.7f64, Span::new(".7")))
+            Literal::Real(Token::new(0.7, Span::new(".7")))
         ));
 
         assert_eq!(real(input), output);
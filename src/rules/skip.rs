@@ -1,6 +1,20 @@
-use super::comments::comment;
-use super::whitespaces::whitespace;
+use super::comments::{
+    comment,
+    comment_delimited,
+    comment_single_line,
+    todo
+};
+use super::whitespaces::{
+    whitespace,
+    newline
+};
 use super::super::span::Span;
+use super::super::tokens;
+
+use nom::{
+    IResult,
+    InputLength
+};
 
 named_attr!(
     #[doc="
@@ -17,10 +31,159 @@ named_attr!(
     )
 );
 
+named_attr!(
+    #[doc="
+        Recognize the tokens to skip within a line, i.e. the whitespaces and
+        the delimited comments. Unlike `skip`, it stops before a single line
+        comment, which ends the line.
+    "],
+    pub skip_within_line<Span, ()>,
+    skip_many0!(
+        alt_complete!(
+            comment_delimited
+            | whitespace
+        )
+    )
+);
+
+named_attr!(
+    #[doc="
+        Recognize all tokens to skip, including line endings.
+        It is used to skip the blank lines between two lines. A `// TODO`
+        comment is not skipped since it is an annotation.
+    "],
+    pub skip_lines<Span, ()>,
+    skip_many0!(
+        alt_complete!(
+            preceded!(not!(todo), comment)
+            | whitespace
+            | newline
+        )
+    )
+);
+
+named_attr!(
+    #[doc="
+        Recognize the end of a line, i.e. the tokens to skip followed by a
+        line ending or by the end of the input.
+    "],
+    pub end_of_line<Span, ()>,
+    value!(
+        (),
+        preceded!(
+            skip_many0!(
+                alt_complete!(
+                    comment_delimited
+                    | whitespace
+                )
+            ),
+            alt_complete!(
+                newline
+                | comment_single_line
+                | end_of_input
+            )
+        )
+    )
+);
+
+/// Recognize the blank lines and the comments before a knot or stitch
+/// header, or before a declaration, like `skip_lines`, and return the
+/// comments above it.
+///
+/// A blank line separates the comments above it from the header, so they
+/// are skipped but not returned. The comments are the ones recognized by
+/// `comment`, i.e. without their delimiters.
+pub fn leading_comments(input: Span) -> IResult<Span, Vec<Span>> {
+    let mut input    = input;
+    let mut comments = Vec::new();
+    let mut blank    = true;
+
+    loop {
+        if let Some((next_input, _)) = consume(input, whitespace) {
+            input = next_input;
+        } else if let Some((next_input, _)) = consume(input, newline) {
+            if blank {
+                comments.clear();
+            }
+
+            blank = true;
+            input = next_input;
+        } else if let Some((next_input, comment)) = consume(input, |input| preceded!(input, not!(todo), comment)) {
+            // A single line comment consumes its line ending.
+            blank = input.as_slice().starts_with(tokens::INLINE_COMMENT);
+            input = next_input;
+            comments.push(comment);
+        } else {
+            break;
+        }
+    }
+
+    Ok((input, comments))
+}
+
+/// Recognize the end of a knot or stitch header, or of a declaration, like
+/// `end_of_line`, and return the comments following it on its line.
+pub fn trailing_comments(input: Span) -> IResult<Span, Vec<Span>> {
+    let mut input    = input;
+    let mut comments = Vec::new();
+
+    loop {
+        if let Some((next_input, _)) = consume(input, whitespace) {
+            input = next_input;
+        } else if let Some((next_input, comment)) = consume(input, comment_delimited) {
+            input = next_input;
+            comments.push(comment);
+        } else {
+            break;
+        }
+    }
+
+    let (input, comment) = alt_complete!(
+        input,
+        value!(None, newline)
+        | map!(comment_single_line, Some)
+        | value!(None, end_of_input)
+    )?;
+
+    comments.extend(comment);
+
+    Ok((input, comments))
+}
+
+/// Apply `parser`, and return its result only if it consumed some input.
+fn consume<'a, O, F>(input: Span<'a>, parser: F) -> Option<(Span<'a>, O)>
+    where F: Fn(Span<'a>) -> IResult<Span<'a>, O>
+{
+    match parser(input) {
+        Ok((next_input, output)) if next_input.input_len() < input.input_len() => Some((next_input, output)),
+        _ => None
+    }
+}
+
+/// Recognize the end of the input.
+pub fn end_of_input(input: Span) -> IResult<Span, Span> {
+    use nom::{
+        Err,
+        ErrorKind
+    };
+
+    if input.input_len() == 0 {
+        Ok((input, input))
+    } else {
+        Err(Err::Error(error_position!(input, ErrorKind::Eof)))
+    }
+}
 
 #[cfg(test)]
 mod tests {
-    use super::skip;
+    use super::{
+        skip,
+        skip_within_line,
+        skip_lines,
+        end_of_line,
+        leading_comments,
+        trailing_comments
+    };
     use super::super::super::span::Span;
 
     #[test]
@@ -54,4 +217,118 @@ mod tests {
 
         assert_eq!(skip(input), output);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn case_skip_within_line() {
+        let input  = Span::new(" /* foo */\t// bar\nhello");
+        let output = Ok((Span::new_at("// bar\nhello", 11, 1, 12), ()));
+
+        assert_eq!(skip_within_line(input), output);
+    }
+
+    #[test]
+    fn case_skip_lines() {
+        let input  = Span::new("  // foo\n\n\t/* bar */\r\n  hello");
+        let output = Ok((Span::new_at("hello", 24, 4, 3), ()));
+
+        assert_eq!(skip_lines(input), output);
+    }
+
+    #[test]
+    fn case_skip_lines_until_todo() {
+        let input  = Span::new("// foo\n  // TODO bar\n");
+        let output = Ok((Span::new_at("// TODO bar\n", 9, 2, 3), ()));
+
+        assert_eq!(skip_lines(input), output);
+    }
+
+    #[test]
+    fn case_end_of_line() {
+        let input  = Span::new("  /* foo */ \nhello");
+        let output = Ok((Span::new_at("hello", 13, 2, 1), ()));
+
+        assert_eq!(end_of_line(input), output);
+    }
+
+    #[test]
+    fn case_end_of_line_with_comment() {
+        let input  = Span::new("  // foo\nhello");
+        let output = Ok((Span::new_at("hello", 9, 2, 1), ()));
+
+        assert_eq!(end_of_line(input), output);
+    }
+
+    #[test]
+    fn case_end_of_line_end_of_input() {
+        let input  = Span::new("  ");
+        let output = Ok((Span::new_at("", 2, 1, 3), ()));
+
+        assert_eq!(end_of_line(input), output);
+    }
+
+    #[test]
+    fn case_invalid_end_of_line() {
+        let input  = Span::new("  hello");
+
+        assert!(end_of_line(input).is_err());
+    }
+
+    #[test]
+    fn case_leading_comments() {
+        let input  = Span::new("// foo\n\n  // bar\n/* baz */\n== knot ==");
+        let output = Ok((
+            Span::new_at("== knot ==", 27, 5, 1),
+            vec![
+                Span::new_at(" bar", 12, 3, 5),
+                Span::new_at(" baz ", 19, 4, 3)
+            ]
+        ));
+
+        assert_eq!(leading_comments(input), output);
+    }
+
+    #[test]
+    fn case_leading_comments_separated_by_a_blank_line() {
+        let input  = Span::new("// foo\n  \t\r\n== knot ==");
+        let output = Ok((Span::new_at("== knot ==", 12, 3, 1), vec![]));
+
+        assert_eq!(leading_comments(input), output);
+    }
+
+    #[test]
+    fn case_leading_comments_until_todo() {
+        let input  = Span::new("// foo\n// TODO bar\n");
+        let output = Ok((Span::new_at("// TODO bar\n", 7, 2, 1), vec![Span::new_at(" foo", 2, 1, 3)]));
+
+        assert_eq!(leading_comments(input), output);
+    }
+
+    #[test]
+    fn case_trailing_comments() {
+        let input  = Span::new(" /* foo */ // bar\r\nhello");
+        let output = Ok((
+            Span::new_at("hello", 19, 2, 1),
+            vec![
+                Span::new_at(" foo ", 3, 1, 4),
+                Span::new_at(" bar", 13, 1, 14)
+            ]
+        ));
+
+        assert_eq!(trailing_comments(input), output);
+    }
+
+    #[test]
+    fn case_trailing_comments_end_of_input() {
+        let input  = Span::new("  ");
+        let output = Ok((Span::new_at("", 2, 1, 3), vec![]));
+
+        assert_eq!(trailing_comments(input), output);
+    }
+
+    #[test]
+    fn case_invalid_trailing_comments() {
+        let input  = Span::new("  hello");
+
+        assert!(trailing_comments(input).is_err());
+    }
+}
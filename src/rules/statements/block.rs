@@ -0,0 +1,323 @@
+use span::Span;
+use tokens;
+
+use ast::ast::{
+    Conditional,
+    ConditionalBranch,
+    Expression,
+    Line,
+    Sequence,
+    SequenceKind,
+    Weave
+};
+
+use internal::ErrorKindExtension;
+use rules::expressions::operations::operation;
+use rules::skip::{
+    end_of_line,
+    skip,
+    skip_lines
+};
+use super::weave::weave_at;
+use super::{
+    position,
+    span_to
+};
+
+use nom::IResult;
+
+named_attr!(
+    #[doc="
+        Recognize a multiline block, i.e. a sequence or a conditional.
+    "],
+    pub block<Span, Line>,
+    alt_complete!(
+        sequence      => { Line::Sequence }
+        | conditional => { Line::Conditional }
+    )
+);
+
+named_attr!(
+    #[doc="
+        Recognize a multiline sequence, e.g.
+        `{ stopping: - I entered the casino. - I entered the casino again. }`.
+    "],
+    pub sequence<Span, Sequence>,
+    do_parse!(
+        start: position >>
+        tag!(tokens::LEFT_CURLY_BRACKET) >>
+        kind: first!(sequence_kind) >>
+        first!(tag!(tokens::COLON)) >>
+        end_of_line >>
+        branches: many0!(
+            complete!(
+                preceded!(
+                    branch_marker,
+                    branch_body
+                )
+            )
+        ) >>
+        skip_lines >>
+        end: expect!(ErrorKindExtension::RightCurlyBracket, tag!(tokens::RIGHT_CURLY_BRACKET)) >>
+        (
+            Sequence {
+                kind,
+                branches,
+                span: span_to(start, end)
+            }
+        )
+    )
+);
+
+named!(
+    sequence_kind<Span, SequenceKind>,
+    alt_complete!(
+        tag!(tokens::STOPPING)  => { |_| SequenceKind::Stopping }
+        | tag!(tokens::CYCLE)   => { |_| SequenceKind::Cycle }
+        | tag!(tokens::ONCE)    => { |_| SequenceKind::Once }
+        | tag!(tokens::SHUFFLE) => { |_| SequenceKind::Shuffle }
+    )
+);
+
+named_attr!(
+    #[doc="
+        Recognize a multiline conditional. The first branch is either on the
+        opening line, e.g. `{ x > 0: … - else: … }`, or every branch is
+        marked by a dash, e.g. `{ - x > 0: … - else: … }`.
+    "],
+    pub conditional<Span, Conditional>,
+    do_parse!(
+        start: position >>
+        tag!(tokens::LEFT_CURLY_BRACKET) >>
+        first_branch: alt_complete!(
+            value!(None, end_of_line)
+            | map!(first_conditional_branch, Some)
+        ) >>
+        branches: many0!(
+            complete!(
+                preceded!(
+                    branch_marker,
+                    conditional_branch
+                )
+            )
+        ) >>
+        skip_lines >>
+        end: expect!(ErrorKindExtension::RightCurlyBracket, tag!(tokens::RIGHT_CURLY_BRACKET)) >>
+        ({
+            let mut all_branches = Vec::new();
+            all_branches.extend(first_branch);
+            all_branches.extend(branches);
+
+            Conditional {
+                branches: all_branches,
+                span    : span_to(start, end)
+            }
+        })
+    )
+);
+
+named!(
+    first_conditional_branch<Span, ConditionalBranch>,
+    do_parse!(
+        skip >>
+        start: position >>
+        condition: operation >>
+        colon: first!(tag!(tokens::COLON)) >>
+        body: branch_body >>
+        (conditional_branch_from(start, Some(condition), colon, body))
+    )
+);
+
+named!(
+    conditional_branch<Span, ConditionalBranch>,
+    do_parse!(
+        skip >>
+        start: position >>
+        condition: alt_complete!(
+            value!(None, tag!(tokens::ELSE))
+            | map!(operation, Some)
+        ) >>
+        colon: first!(tag!(tokens::COLON)) >>
+        body: branch_body >>
+        (conditional_branch_from(start, condition, colon, body))
+    )
+);
+
+/// Build a conditional branch starting at `start`, and ending at the end of
+/// its body, or at its colon if the body is empty.
+fn conditional_branch_from<'a>(
+    start: Span<'a>,
+    condition: Option<Expression<'a>>,
+    colon: Span<'a>,
+    body: Weave<'a>
+) -> ConditionalBranch<'a> {
+    let span = if body.lines.is_empty() {
+        span_to(start, colon)
+    } else {
+        span_to(start, body.span)
+    };
+
+    ConditionalBranch {
+        condition,
+        body,
+        span
+    }
+}
+
+named!(
+    branch_marker<Span, Span>,
+    preceded!(
+        skip_lines,
+        terminated!(
+            tag!(tokens::GATHER),
+            not!(tag!(">"))
+        )
+    )
+);
+
+/// Recognize the body of a branch, up to the next branch or the end of the block.
+fn branch_body(input: Span) -> IResult<Span, Weave> {
+    weave_at(input, 0, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        conditional,
+        sequence
+    };
+    use internal::{
+        Context,
+        Error,
+        ErrorKind,
+        ErrorKindExtension
+    };
+    use span::Span;
+
+    use ast::ast::{
+        Conditional,
+        ConditionalBranch,
+        Content,
+        ContentPart,
+        Expression,
+        Line,
+        NAryOperation,
+        Sequence,
+        SequenceKind,
+        Variable,
+        Weave
+    };
+
+    macro_rules! text {
+        ($span:expr) => (
+            Line::Content(Content(vec![ContentPart::Text($span)]))
+        )
+    }
+
+    macro_rules! variable {
+        ($name:expr) => (
+            Expression::NAryOperation(NAryOperation::Nullary { operand: Box::new(Expression::Variable(Variable($name))), span: $name })
+        )
+    }
+
+    #[test]
+    fn case_sequence() {
+        let input  = Span::new("{ stopping:\n- A\n-\n  B\n}");
+        let output = Ok((
+            Span::new_at("", 23, 5, 2),
+            Sequence {
+                kind    : SequenceKind::Stopping,
+                branches: vec![
+                    Weave {
+                        lines: vec![text!(Span::new_at("A", 14, 2, 3))],
+                        span : Span::new_at("A", 14, 2, 3)
+                    },
+                    Weave {
+                        lines: vec![text!(Span::new_at("B", 20, 4, 3))],
+                        span : Span::new_at("B", 20, 4, 3)
+                    }
+                ],
+                span    : Span::new_at("{ stopping:\n- A\n-\n  B\n}", 0, 1, 1)
+            }
+        ));
+
+        assert_eq!(sequence(input), output);
+    }
+
+    #[test]
+    fn case_conditional_first_branch_on_opening_line() {
+        let input  = Span::new("{ x:\n  A\n- else:\n  B\n}");
+        let output = Ok((
+            Span::new_at("", 22, 5, 2),
+            Conditional {
+                branches: vec![
+                    ConditionalBranch {
+                        condition: Some(variable!(Span::new_at("x", 2, 1, 3))),
+                        body     : Weave {
+                            lines: vec![text!(Span::new_at("A", 7, 2, 3))],
+                            span : Span::new_at("A", 7, 2, 3)
+                        },
+                        span     : Span::new_at("x:\n  A", 2, 1, 3)
+                    },
+                    ConditionalBranch {
+                        condition: None,
+                        body     : Weave {
+                            lines: vec![text!(Span::new_at("B", 19, 4, 3))],
+                            span : Span::new_at("B", 19, 4, 3)
+                        },
+                        span     : Span::new_at("else:\n  B", 11, 3, 3)
+                    }
+                ],
+                span    : Span::new_at("{ x:\n  A\n- else:\n  B\n}", 0, 1, 1)
+            }
+        ));
+
+        assert_eq!(conditional(input), output);
+    }
+
+    #[test]
+    fn case_conditional_dashed_branches() {
+        let input  = Span::new("{\n- x: A\n- y: B\n}");
+        let output = Ok((
+            Span::new_at("", 17, 4, 2),
+            Conditional {
+                branches: vec![
+                    ConditionalBranch {
+                        condition: Some(variable!(Span::new_at("x", 4, 2, 3))),
+                        body     : Weave {
+                            lines: vec![text!(Span::new_at("A", 7, 2, 6))],
+                            span : Span::new_at("A", 7, 2, 6)
+                        },
+                        span     : Span::new_at("x: A", 4, 2, 3)
+                    },
+                    ConditionalBranch {
+                        condition: Some(variable!(Span::new_at("y", 11, 3, 3))),
+                        body     : Weave {
+                            lines: vec![text!(Span::new_at("B", 14, 3, 6))],
+                            span : Span::new_at("B", 14, 3, 6)
+                        },
+                        span     : Span::new_at("y: B", 11, 3, 3)
+                    }
+                ],
+                span    : Span::new_at("{\n- x: A\n- y: B\n}", 0, 1, 1)
+            }
+        ));
+
+        assert_eq!(conditional(input), output);
+    }
+
+    #[test]
+    fn case_invalid_conditional_not_closed() {
+        let input  = Span::new("{ x:\n  A\n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at("", 9, 3, 1), ErrorKind::Custom(ErrorKindExtension::RightCurlyBracket as u32))));
+
+        assert_eq!(conditional(input), output);
+    }
+
+    #[test]
+    fn case_invalid_conditional_interpolation() {
+        let input = Span::new("{x} is here\n");
+
+        assert!(conditional(input).is_err());
+    }
+}
@@ -1,53 +1,198 @@
 use span::Span;
 use tokens;
 
-use ast::ast::Statement;
+use ast::ast::{
+    Comments,
+    DeclarationKind,
+    Expression,
+    Integer,
+    ListItem,
+    Literal,
+    Statement,
+    Token,
+    Variable
+};
+use internal::ErrorKindExtension;
 use rules::expressions::variables::variable;
 use rules::expressions::operations::operation;
+use rules::identifier::identifier;
+use rules::number::decimal;
 
-named_attr!(
-    #[doc="
-        Recognize a declaration statement.
-    "],
-    pub declaration<Span, Statement>,
-    preceded!(
-        tag!(tokens::STATEMENT),
-        do_parse!(
-            variable: first!(variable) >>
-            first!(tag!(tokens::ASSIGN)) >>
-            expression: first!(operation) >>
-            (Statement::Declaration(variable, expression))
+use nom::{
+    IResult,
+    Offset,
+    Slice
+};
+
+/// Recognize a declaration statement, e.g. `~ x = 1`, a global variable,
+/// e.g. `VAR x = 1`, or a global constant, e.g. `CONST x = 1`.
+///
+/// The comments documenting the declaration are attached by `statement`.
+pub fn declaration(input: Span) -> IResult<Span, Statement> {
+    let (next_input, (kind, variable, value)) = declaration_parts(input)?;
+    let length = input.offset(&next_input);
+
+    Ok((
+        next_input,
+        Statement::Declaration {
+            variable,
+            value,
+            kind,
+            span    : input.slice(..length),
+            comments: Comments::default()
+        }
+    ))
+}
+
+named!(
+    declaration_parts<Span, (DeclarationKind, Variable, Expression)>,
+    do_parse!(
+        kind: alt_complete!(
+            value!(DeclarationKind::Statement, tag!(tokens::STATEMENT))
+            | value!(DeclarationKind::Global, terminated!(tag!(tokens::GLOBAL_VARIABLE), is_a!(" \t")))
+            | value!(DeclarationKind::Constant, terminated!(tag!(tokens::CONSTANT), is_a!(" \t")))
+        ) >>
+        variable: first!(variable) >>
+        first!(tag!(tokens::ASSIGN)) >>
+        expression: first!(expect!(ErrorKindExtension::Expression, operation)) >>
+        (kind, variable, expression)
+    )
+);
+
+/// Recognize a list declaration, e.g. `LIST l = a, (b = 2)`.
+///
+/// The comments documenting the list are attached by `statement`.
+pub fn list_declaration(input: Span) -> IResult<Span, Statement> {
+    let (next_input, (name, items)) = list_parts(input)?;
+    let length = input.offset(&next_input);
+
+    Ok((
+        next_input,
+        Statement::List {
+            name,
+            items,
+            span    : input.slice(..length),
+            comments: Comments::default()
+        }
+    ))
+}
+
+named!(
+    list_parts<Span, (Variable, Vec<ListItem>)>,
+    do_parse!(
+        terminated!(tag!(tokens::LIST), is_a!(" \t")) >>
+        name: first!(variable) >>
+        first!(tag!(tokens::ASSIGN)) >>
+        items: separated_nonempty_list_complete!(
+            first!(tag!(tokens::COMMA)),
+            first!(expect!(ErrorKindExtension::Identifier, list_item))
+        ) >>
+        (name, items)
+    )
+);
+
+/// Recognize an item of a list, e.g. `a`, `b = 2`, or `(c)` when it is in
+/// the initial value of the list.
+fn list_item(input: Span) -> IResult<Span, ListItem> {
+    let (next_input, (included, (name, value))) = alt_complete!(
+        input,
+        map!(
+            delimited!(
+                tag!(tokens::LEFT_PARENTHESIS),
+                first!(list_item_parts),
+                first!(expect!(ErrorKindExtension::RightParenthesis, tag!(tokens::RIGHT_PARENTHESIS)))
+            ),
+            |parts| (true, parts)
         )
+        | map!(list_item_parts, |parts| (false, parts))
+    )?;
+    let length = input.offset(&next_input);
+
+    Ok((
+        next_input,
+        ListItem {
+            name,
+            value,
+            included,
+            span: input.slice(..length)
+        }
+    ))
+}
+
+named!(
+    list_item_parts<Span, (Span, Option<Token<Integer>>)>,
+    do_parse!(
+        name: identifier >>
+        value: opt!(
+            preceded!(
+                first!(tag!(tokens::ASSIGN)),
+                first!(
+                    map_opt!(
+                        decimal,
+                        |literal| match literal {
+                            Literal::Integer(token) => Some(token),
+                            _                       => None
+                        }
+                    )
+                )
+            )
+        ) >>
+        (name, value)
     )
 );
 
 #[cfg(test)]
 mod tests {
-    use super::declaration;
+    use super::{
+        declaration,
+        list_declaration
+    };
+    use internal::{
+        Context,
+        Error,
+        ErrorKind,
+        ErrorKindExtension
+    };
     use span::Span;
 
     use ast::ast::{
+        Comments,
+        DeclarationKind,
+        ListItem,
         Token,
         Statement,
         Variable,
         Expression,
         Literal,
         NAryOperation,
-        BinaryOperator
+        BinaryOperator,
+        Spanned
     };
 
     macro_rules! nullary_operation {
-        ($expression:expr) => (
-            NAryOperation::Nullary(Box::new($expression))
+        ($kind:ident!($value:expr, $span:expr)) => (
+            nullary_operation!($kind!($value, $span), $span)
+        );
+
+        (variable!($name:expr)) => (
+            nullary_operation!(variable!($name), $name)
+        );
+
+        ($expression:expr, $span:expr) => (
+            NAryOperation::Nullary {
+                operand: Box::new($expression),
+                span   : $span
+            }
         )
     }
 
     macro_rules! binary_operation {
-        ($operator:ident, $left_operand:expr, $right_operand:expr) => (
+        ($operator:ident, $operator_span:expr, $left_operand:expr, $right_operand:expr, $span:expr) => (
             NAryOperation::Binary {
-                operator     : BinaryOperator::$operator,
+                operator     : Token::new(BinaryOperator::$operator, $operator_span),
                 left_operand : Box::new($left_operand),
-                right_operand: Box::new($right_operand)
+                right_operand: Box::new($right_operand),
+                span         : $span
             }
         )
     }
@@ -81,34 +226,142 @@ mod tests {
         let input  = Span::new("~ knows_about_wager = true\n");
         let output = Ok((
             Span::new_at("\n", 26, 1, 27),
-            Statement::Declaration (
-                Variable (Span::new_at("knows_about_wager", 2, 1, 3)),
-                Expression::NAryOperation(nullary_operation!(boolean!(true, Span::new_at("true", 22, 1, 23))))
-            )
+            Statement::Declaration {
+                variable: Variable(Span::new_at("knows_about_wager", 2, 1, 3)),
+                value   : Expression::NAryOperation(nullary_operation!(boolean!(true, Span::new_at("true", 22, 1, 23)))),
+                kind    : DeclarationKind::Statement,
+                span    : Span::new_at("~ knows_about_wager = true", 0, 1, 1),
+                comments: Comments::default()
+            }
         ));
 
         assert_eq!(declaration(input), output);
     }
 
+    #[test]
+    fn case_declaration_global_variable() {
+        let input  = Span::new("VAR score = 0\n");
+        let output = Ok((
+            Span::new_at("\n", 13, 1, 14),
+            Statement::Declaration {
+                variable: Variable(Span::new_at("score", 4, 1, 5)),
+                value   : Expression::NAryOperation(nullary_operation!(integer!(0, Span::new_at("0", 12, 1, 13)))),
+                kind    : DeclarationKind::Global,
+                span    : Span::new_at("VAR score = 0", 0, 1, 1),
+                comments: Comments::default()
+            }
+        ));
+
+        assert_eq!(declaration(input), output);
+    }
+
+    #[test]
+    fn case_declaration_global_constant() {
+        let input  = Span::new("CONST max = 10\n");
+        let output = Ok((
+            Span::new_at("\n", 14, 1, 15),
+            Statement::Declaration {
+                variable: Variable(Span::new_at("max", 6, 1, 7)),
+                value   : Expression::NAryOperation(nullary_operation!(integer!(10, Span::new_at("10", 12, 1, 13)))),
+                kind    : DeclarationKind::Constant,
+                span    : Span::new_at("CONST max = 10", 0, 1, 1),
+                comments: Comments::default()
+            }
+        ));
+
+        assert_eq!(declaration(input), output);
+    }
+
+    #[test]
+    fn case_list_declaration() {
+        let input  = Span::new("LIST kettle = cold, (boiling = 5), done = 7\n");
+        let output = Ok((
+            Span::new_at("\n", 43, 1, 44),
+            Statement::List {
+                name    : Variable(Span::new_at("kettle", 5, 1, 6)),
+                items   : vec![
+                    ListItem {
+                        name    : Span::new_at("cold", 14, 1, 15),
+                        value   : None,
+                        included: false,
+                        span    : Span::new_at("cold", 14, 1, 15)
+                    },
+                    ListItem {
+                        name    : Span::new_at("boiling", 21, 1, 22),
+                        value   : Some(Token::new(5, Span::new_at("5", 31, 1, 32))),
+                        included: true,
+                        span    : Span::new_at("(boiling = 5)", 20, 1, 21)
+                    },
+                    ListItem {
+                        name    : Span::new_at("done", 35, 1, 36),
+                        value   : Some(Token::new(7, Span::new_at("7", 42, 1, 43))),
+                        included: false,
+                        span    : Span::new_at("done = 7", 35, 1, 36)
+                    }
+                ],
+                span    : Span::new_at("LIST kettle = cold, (boiling = 5), done = 7", 0, 1, 1),
+                comments: Comments::default()
+            }
+        ));
+
+        assert_eq!(list_declaration(input), output);
+    }
+
+    #[test]
+    fn case_invalid_list_declaration_unclosed_item() {
+        let input  = Span::new("LIST l = (a\n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at("\n", 11, 1, 12), ErrorKind::Custom(ErrorKindExtension::RightParenthesis as u32))));
+
+        assert_eq!(list_declaration(input), output);
+    }
+
+    #[test]
+    fn case_invalid_declaration_global_variable_without_whitespace() {
+        assert!(declaration(Span::new("VARscore = 0\n")).is_err());
+    }
+
+    #[test]
+    fn case_invalid_declaration_missing_expression() {
+        let input  = Span::new("~ x = \n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at("\n", 6, 1, 7), ErrorKind::Custom(ErrorKindExtension::Expression as u32))));
+
+        assert_eq!(declaration(input), output);
+    }
+
+    #[test]
+    fn case_declaration_range() {
+        let input = "~ x = 1 + y  \n";
+
+        match declaration(Span::new(input)) {
+            Ok((_, statement)) => assert_eq!(&input[statement.range().byte_range()], "~ x = 1 + y"),
+            _                  => panic!("expected a declaration")
+        }
+    }
+
     #[test]
     fn case_declaration_expression() {
         let input  = Span::new("~ y = 2 * x * y\n");
         let output = Ok((
             Span::new_at("\n", 15, 1, 16),
-            Statement::Declaration (
-                Variable (Span::new_at("y", 2, 1, 3)),
-                Expression::NAryOperation(
+            Statement::Declaration {
+                variable: Variable(Span::new_at("y", 2, 1, 3)),
+                value   : Expression::NAryOperation(
                     binary_operation!(
-                        Multiplication,
+                        Multiplication, Span::new_at("*", 12, 1, 13),
                         binary_operation!(
-                            Multiplication,
+                            Multiplication, Span::new_at("*", 8, 1, 9),
                             nullary_operation!(integer!(2, Span::new_at("2", 6, 1, 7))),
-                            nullary_operation!(variable!(Span::new_at("x", 10, 1, 11)))
+                            nullary_operation!(variable!(Span::new_at("x", 10, 1, 11))),
+                            Span::new_at("2 * x", 6, 1, 7)
                         ),
-                        nullary_operation!(variable!(Span::new_at("y", 14, 1, 15)))
+                        nullary_operation!(variable!(Span::new_at("y", 14, 1, 15))),
+                        Span::new_at("2 * x * y", 6, 1, 7)
                     )
-                )
-            )
+                ),
+                kind    : DeclarationKind::Statement,
+                span    : Span::new_at("~ y = 2 * x * y", 0, 1, 1),
+                comments: Comments::default()
+            }
         ));
 
         assert_eq!(declaration(input), output);
@@ -119,20 +372,25 @@ mod tests {
         let input  = Span::new("~   y   =   2   *   x   *   y\n");
         let output = Ok((
             Span::new_at("\n", 29, 1, 30),
-            Statement::Declaration (
-                Variable (Span::new_at("y", 4, 1, 5)),
-                Expression::NAryOperation(
+            Statement::Declaration {
+                variable: Variable(Span::new_at("y", 4, 1, 5)),
+                value   : Expression::NAryOperation(
                     binary_operation!(
-                        Multiplication,
+                        Multiplication, Span::new_at("*", 24, 1, 25),
                         binary_operation!(
-                            Multiplication,
+                            Multiplication, Span::new_at("*", 16, 1, 17),
                             nullary_operation!(integer!(2, Span::new_at("2", 12, 1, 13))),
-                            nullary_operation!(variable!(Span::new_at("x", 20, 1, 21)))
+                            nullary_operation!(variable!(Span::new_at("x", 20, 1, 21))),
+                            Span::new_at("2   *   x", 12, 1, 13)
                         ),
-                        nullary_operation!(variable!(Span::new_at("y", 28, 1, 29)))
+                        nullary_operation!(variable!(Span::new_at("y", 28, 1, 29))),
+                        Span::new_at("2   *   x   *   y", 12, 1, 13)
                     )
-                )
-            )
+                ),
+                kind    : DeclarationKind::Statement,
+                span    : Span::new_at("~   y   =   2   *   x   *   y", 0, 1, 1),
+                comments: Comments::default()
+            }
         ));
 
         assert_eq!(declaration(input), output);
@@ -143,28 +401,35 @@ mod tests {
         let input  = Span::new("~ x = (x * x) - (y * y) + c\n");
         let output = Ok((
             Span::new_at("\n", 27, 1, 28),
-            Statement::Declaration (
-                Variable (Span::new_at("x", 2, 1, 3)),
-                Expression::NAryOperation(
+            Statement::Declaration {
+                variable: Variable(Span::new_at("x", 2, 1, 3)),
+                value   : Expression::NAryOperation(
                     binary_operation!(
-                        Addition,
+                        Addition, Span::new_at("+", 24, 1, 25),
                         binary_operation!(
-                            Subtraction,
+                            Subtraction, Span::new_at("-", 14, 1, 15),
                             binary_operation!(
-                                Multiplication,
+                                Multiplication, Span::new_at("*", 9, 1, 10),
                                 nullary_operation!(variable!(Span::new_at("x", 7, 1, 8))),
-                                nullary_operation!(variable!(Span::new_at("x", 11, 1, 12)))
+                                nullary_operation!(variable!(Span::new_at("x", 11, 1, 12))),
+                                Span::new_at("(x * x)", 6, 1, 7)
                             ),
                             binary_operation!(
-                                Multiplication,
+                                Multiplication, Span::new_at("*", 19, 1, 20),
                                 nullary_operation!(variable!(Span::new_at("y", 17, 1, 18))),
-                                nullary_operation!(variable!(Span::new_at("y", 21, 1, 22)))
-                            )
+                                nullary_operation!(variable!(Span::new_at("y", 21, 1, 22))),
+                                Span::new_at("(y * y)", 16, 1, 17)
+                            ),
+                            Span::new_at("(x * x) - (y * y)", 6, 1, 7)
                         ),
-                        nullary_operation!(variable!(Span::new_at("c", 26, 1, 27)))
+                        nullary_operation!(variable!(Span::new_at("c", 26, 1, 27))),
+                        Span::new_at("(x * x) - (y * y) + c", 6, 1, 7)
                     )
-                )
-            )
+                ),
+                kind    : DeclarationKind::Statement,
+                span    : Span::new_at("~ x = (x * x) - (y * y) + c", 0, 1, 1),
+                comments: Comments::default()
+            }
         ));
 
         assert_eq!(declaration(input), output);
@@ -175,16 +440,20 @@ mod tests {
         let input  = Span::new("~ z = 1.2 / 0.5\n");
         let output = Ok((
             Span::new_at("\n", 15, 1, 16),
-            Statement::Declaration (
-                Variable (Span::new_at("z", 2, 1, 3)),
-                Expression::NAryOperation(
+            Statement::Declaration {
+                variable: Variable(Span::new_at("z", 2, 1, 3)),
+                value   : Expression::NAryOperation(
                     binary_operation!(
-                        Division,
+                        Division, Span::new_at("/", 10, 1, 11),
                         nullary_operation!(real!(1.2, Span::new_at("1.2", 6, 1, 7))),
-                        nullary_operation!(real!(0.5, Span::new_at("0.5", 12, 1, 13)))
+                        nullary_operation!(real!(0.5, Span::new_at("0.5", 12, 1, 13))),
+                        Span::new_at("1.2 / 0.5", 6, 1, 7)
                     )
-                )
-            )
+                ),
+                kind    : DeclarationKind::Statement,
+                span    : Span::new_at("~ z = 1.2 / 0.5", 0, 1, 1),
+                comments: Comments::default()
+            }
         ));
 
         assert_eq!(declaration(input), output);
@@ -0,0 +1,298 @@
+use span::Span;
+use tokens;
+
+use ast::ast::{
+    Comments,
+    Knot,
+    Stitch,
+    Weave
+};
+
+use internal::ErrorKindExtension;
+use rules::expressions::variables::variable;
+use rules::identifier::identifier;
+use rules::skip::{
+    leading_comments,
+    trailing_comments
+};
+use super::weave::weave;
+use super::{
+    position,
+    span_to
+};
+
+use nom::{
+    Offset,
+    Slice
+};
+
+named_attr!(
+    #[doc="
+        Recognize a knot with its stitches, e.g. `== london ==`, or a
+        function, e.g. `== function lerp(a, b, k) ==`, and the comments
+        documenting it.
+    "],
+    pub knot<Span, Knot>,
+    do_parse!(
+        leading: leading_comments >>
+        start: position >>
+        tag!(tokens::KNOT) >>
+        opt!(incomplete!(is_a!("="))) >>
+        function: opt!(
+            first!(
+                terminated!(
+                    tag!(tokens::FUNCTION),
+                    is_a!(" \t")
+                )
+            )
+        ) >>
+        name: first!(expect!(ErrorKindExtension::Identifier, identifier)) >>
+        parameters: opt!(
+            delimited!(
+                first!(tag!(tokens::LEFT_PARENTHESIS)),
+                separated_list!(
+                    first!(tag!(tokens::COMMA)),
+                    first!(variable)
+                ),
+                first!(tag!(tokens::RIGHT_PARENTHESIS))
+            )
+        ) >>
+        opt!(first!(incomplete!(is_a!("=")))) >>
+        header: position >>
+        trailing: trailing_comments >>
+        body: weave >>
+        stitches: many0!(complete!(stitch)) >>
+        ({
+            let span = match stitches.last() {
+                Some(stitch) => span_to(start, stitch.span),
+                None         => header_and_body_span(start, header, &body)
+            };
+
+            Knot {
+                name,
+                function  : function.is_some(),
+                parameters: parameters.unwrap_or_default(),
+                body,
+                stitches,
+                comments  : Comments { leading, trailing },
+                span
+            }
+        })
+    )
+);
+
+named_attr!(
+    #[doc="
+        Recognize a stitch, e.g. `= in_first_class`, and the comments
+        documenting it.
+    "],
+    pub stitch<Span, Stitch>,
+    do_parse!(
+        leading: leading_comments >>
+        start: position >>
+        tag!(tokens::STITCH) >>
+        not!(tag!(tokens::STITCH)) >>
+        name: first!(identifier) >>
+        header: position >>
+        trailing: trailing_comments >>
+        body: weave >>
+        (
+            Stitch {
+                name,
+                span    : header_and_body_span(start, header, &body),
+                body,
+                comments: Comments { leading, trailing }
+            }
+        )
+    )
+);
+
+/// The span of a header starting at `start` and followed by `body`. It ends
+/// at the end of the body, or at `header`, the end of the header, if the
+/// body is empty.
+fn header_and_body_span<'a>(start: Span<'a>, header: Span<'a>, body: &Weave<'a>) -> Span<'a> {
+    if body.lines.is_empty() {
+        start.slice(..start.offset(&header))
+    } else {
+        span_to(start, body.span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        knot,
+        stitch
+    };
+    use internal::{
+        Context,
+        Error,
+        ErrorKind,
+        ErrorKindExtension
+    };
+    use span::Span;
+
+    use ast::ast::{
+        Comments,
+        Content,
+        ContentPart,
+        Knot,
+        Line,
+        Stitch,
+        Variable,
+        Weave
+    };
+
+    macro_rules! text {
+        ($span:expr) => (
+            Line::Content(Content(vec![ContentPart::Text($span)]))
+        )
+    }
+
+    #[test]
+    fn case_stitch() {
+        let input  = Span::new("= first_class\nHello\n");
+        let output = Ok((
+            Span::new_at("", 20, 3, 1),
+            Stitch {
+                name    : Span::new_at("first_class", 2, 1, 3),
+                body    : Weave {
+                    lines: vec![text!(Span::new_at("Hello", 14, 2, 1))],
+                    span : Span::new_at("Hello", 14, 2, 1)
+                },
+                comments: Comments::default(),
+                span    : Span::new_at("= first_class\nHello", 0, 1, 1)
+            }
+        ));
+
+        assert_eq!(stitch(input), output);
+    }
+
+    #[test]
+    fn case_knot() {
+        let input  = Span::new("=== london ===\nHello\n= first_class\nWorld\n");
+        let output = Ok((
+            Span::new_at("", 41, 5, 1),
+            Knot {
+                name      : Span::new_at("london", 4, 1, 5),
+                function  : false,
+                parameters: vec![],
+                body      : Weave {
+                    lines: vec![text!(Span::new_at("Hello", 15, 2, 1))],
+                    span : Span::new_at("Hello", 15, 2, 1)
+                },
+                stitches  : vec![
+                    Stitch {
+                        name    : Span::new_at("first_class", 23, 3, 3),
+                        body    : Weave {
+                            lines: vec![text!(Span::new_at("World", 35, 4, 1))],
+                            span : Span::new_at("World", 35, 4, 1)
+                        },
+                        comments: Comments::default(),
+                        span    : Span::new_at("= first_class\nWorld", 21, 3, 1)
+                    }
+                ],
+                comments  : Comments::default(),
+                span      : Span::new_at("=== london ===\nHello\n= first_class\nWorld", 0, 1, 1)
+            }
+        ));
+
+        assert_eq!(knot(input), output);
+    }
+
+    #[test]
+    fn case_knot_without_closing_equal_signs() {
+        let input  = Span::new("== london\n");
+        let output = Ok((
+            Span::new_at("", 10, 2, 1),
+            Knot {
+                name      : Span::new_at("london", 3, 1, 4),
+                function  : false,
+                parameters: vec![],
+                body      : Weave {
+                    lines: vec![],
+                    span : Span::new_at("", 10, 2, 1)
+                },
+                stitches  : vec![],
+                comments  : Comments::default(),
+                span      : Span::new_at("== london", 0, 1, 1)
+            }
+        ));
+
+        assert_eq!(knot(input), output);
+    }
+
+    #[test]
+    fn case_knot_function() {
+        let input  = Span::new("== function lerp(a, b) ==\n");
+        let output = Ok((
+            Span::new_at("", 26, 2, 1),
+            Knot {
+                name      : Span::new_at("lerp", 12, 1, 13),
+                function  : true,
+                parameters: vec![
+                    Variable(Span::new_at("a", 17, 1, 18)),
+                    Variable(Span::new_at("b", 20, 1, 21))
+                ],
+                body      : Weave {
+                    lines: vec![],
+                    span : Span::new_at("", 26, 2, 1)
+                },
+                stitches  : vec![],
+                comments  : Comments::default(),
+                span      : Span::new_at("== function lerp(a, b) ==", 0, 1, 1)
+            }
+        ));
+
+        assert_eq!(knot(input), output);
+    }
+
+    #[test]
+    fn case_stitch_with_comments() {
+        let input  = Span::new("// Not documenting.\n\n// First.\n/* Second. */\n= first_class // Third.\n");
+        let output = Ok((
+            Span::new_at("", 69, 6, 1),
+            Stitch {
+                name    : Span::new_at("first_class", 47, 5, 3),
+                body    : Weave {
+                    lines: vec![],
+                    span : Span::new_at("", 69, 6, 1)
+                },
+                comments: Comments {
+                    leading : vec![
+                        Span::new_at(" First.", 23, 3, 3),
+                        Span::new_at(" Second. ", 33, 4, 3)
+                    ],
+                    trailing: vec![Span::new_at(" Third.", 61, 5, 17)]
+                },
+                span    : Span::new_at("= first_class", 45, 5, 1)
+            }
+        ));
+
+        assert_eq!(stitch(input), output);
+    }
+
+    #[test]
+    fn case_knot_with_comments() {
+        let input  = Span::new("// Linear interpolation.\n== function lerp(a, b) == /* a + (b - a) * k */\n~ return a\n");
+        let output = knot(input).map(|(_, knot)| knot.comments);
+
+        assert_eq!(
+            output,
+            Ok(
+                Comments {
+                    leading : vec![Span::new_at(" Linear interpolation.", 2, 1, 3)],
+                    trailing: vec![Span::new_at(" a + (b - a) * k ", 53, 2, 29)]
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn case_invalid_knot_missing_name() {
+        let input  = Span::new("== ==\n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at("==\n", 3, 1, 4), ErrorKind::Custom(ErrorKindExtension::Identifier as u32))));
+
+        assert_eq!(knot(input), output);
+    }
+}
@@ -1,2 +1,67 @@
-mod declaration;
-mod returns;
\ No newline at end of file
+pub mod block;
+pub mod declaration;
+pub mod knot;
+pub mod returns;
+pub mod weave;
+
+use span::Span;
+use ast::ast::{
+    Comments,
+    Statement
+};
+use rules::skip::{
+    leading_comments,
+    trailing_comments
+};
+
+use self::declaration::{
+    declaration,
+    list_declaration
+};
+use self::returns::return_statement;
+
+use nom::{
+    IResult,
+    Offset,
+    Slice
+};
+
+/// Recognize all kind of statements, up to the end of their line.
+///
+/// The comments above a declaration or a list, up to the first blank line,
+/// and the comments following it on its line document it, like the comments
+/// of a knot.
+pub fn statement(input: Span) -> IResult<Span, Statement> {
+    let (input, leading) = leading_comments(input)?;
+    let (input, mut statement) = alt_complete!(input, return_statement | declaration | list_declaration)?;
+    let (input, trailing) = trailing_comments(input)?;
+
+    match statement {
+        Statement::Declaration { ref mut comments, .. } | Statement::List { ref mut comments, .. } => {
+            *comments = Comments { leading, trailing };
+        },
+
+        Statement::Return { .. } => {}
+    }
+
+    Ok((input, statement))
+}
+
+/// Recognize nothing, and return the input, to remember a position within a
+/// `do_parse!`.
+pub fn position(input: Span) -> IResult<Span, Span> {
+    Ok((input, input))
+}
+
+/// The lines consumed from `input` up to `next_input`, without the trailing
+/// whitespaces and line endings.
+pub fn consumed<'a>(input: Span<'a>, next_input: Span<'a>) -> Span<'a> {
+    let lines = &input.as_slice()[..input.offset(&next_input)];
+
+    input.slice(..lines.trim_end_matches([' ', '\t', '\r', '\n']).len())
+}
+
+/// The span of `input` up to the end of `last`, a span sliced from `input`.
+pub fn span_to<'a>(input: Span<'a>, last: Span<'a>) -> Span<'a> {
+    input.slice(..input.offset(&last) + last.as_slice().len())
+}
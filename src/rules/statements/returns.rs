@@ -5,28 +5,37 @@ use ast::ast::Expression;
 use ast::ast::Statement;
 use rules::expressions::operations::operation;
 
-named_attr!(
-    #[doc="
-        Recognize a return statement.
-    "],
-    pub return_statement<Span, Statement>,
-    map_res!(
+use nom::{
+    IResult,
+    Offset,
+    Slice
+};
+
+/// Recognize a return statement, e.g. `~ return x`.
+pub fn return_statement(input: Span) -> IResult<Span, Statement> {
+    let (next_input, value) = returned_value(input)?;
+    let length = input.offset(&next_input);
+
+    Ok((
+        next_input,
+        Statement::Return {
+            value,
+            span: input.slice(..length)
+        }
+    ))
+}
+
+named!(
+    returned_value<Span, Expression>,
+    preceded!(
+        tag!(tokens::STATEMENT),
         preceded!(
-            tag!(tokens::STATEMENT),
-            preceded!(
-                first!(tag!(tokens::RETURN)),
-                first!(operation)
-            )
-        ),
-        return_statement_mapper
+            first!(tag!(tokens::RETURN)),
+            first!(operation)
+        )
     )
 );
 
-#[inline]
-fn return_statement_mapper<'a>(expression: Expression<'a>) -> Result<Statement<'a>, ()> {
-    Ok(Statement::Return(expression))
-}
-
 #[cfg(test)]
 mod tests {
     use super::return_statement;
@@ -43,33 +52,33 @@ mod tests {
     };
 
     macro_rules! nullary {
-        ($expression:expr) => (
-            NAryOperation::Nullary(Box::new($expression))
+        ($kind:ident!($value:expr, $span:expr)) => (
+            nullary!($kind!($value, $span), $span)
+        );
+
+        (variable!($name:expr)) => (
+            nullary!(variable!($name), $name)
+        );
+
+        ($expression:expr, $span:expr) => (
+            NAryOperation::Nullary {
+                operand: Box::new($expression),
+                span   : $span
+            }
         )
     }
 
     macro_rules! binary {
-        ($operator:ident, $left_operand:expr, $right_operand:expr) => (
+        ($operator:ident, $operator_span:expr, $left_operand:expr, $right_operand:expr, $span:expr) => (
             NAryOperation::Binary {
-                operator     : BinaryOperator::$operator,
+                operator     : Token::new(BinaryOperator::$operator, $operator_span),
                 left_operand : Box::new($left_operand),
-                right_operand: Box::new($right_operand)
+                right_operand: Box::new($right_operand),
+                span         : $span
             }
         )
     }
 
-    macro_rules! integer {
-        ($value:expr, $span:expr) => (
-            Expression::Literal(Literal::Integer(Token::new($value, $span)))
-        )
-    }
-
-    macro_rules! real {
-        ($value:expr, $span:expr) => (
-            Expression::Literal(Literal::Real(Token::new($value, $span)))
-        )
-    }
-
     macro_rules! boolean {
         ($value:expr, $span:expr) => (
             Expression::Literal(Literal::Boolean(Token::new($value, $span)))
@@ -87,7 +96,10 @@ mod tests {
         let input = Span::new("~ return true\n");
         let output = Ok((
             Span::new_at("\n", 13, 1, 14),
-            Statement::Return(Expression::NAryOperation(nullary!(boolean!(true, Span::new_at("true", 9, 1, 10)))))
+            Statement::Return {
+                value: Expression::NAryOperation(nullary!(boolean!(true, Span::new_at("true", 9, 1, 10)))),
+                span : Span::new_at("~ return true", 0, 1, 1)
+            }
         ));
 
         assert_eq!(return_statement(input), output);
@@ -98,21 +110,27 @@ mod tests {
         let input = Span::new("~ return ((b - a) * k) + a\n");
         let output = Ok((
             Span::new_at("\n", 26, 1, 27),
-            Statement::Return(Expression::NAryOperation(
-                binary!(
-                    Addition,
+            Statement::Return {
+                value: Expression::NAryOperation(
                     binary!(
-                        Multiplication,
+                        Addition, Span::new_at("+", 23, 1, 24),
                         binary!(
-                            Subtraction,
-                            nullary!(variable!(Span::new_at("b", 11, 1, 12))),
-                            nullary!(variable!(Span::new_at("a", 15, 1, 16)))
+                            Multiplication, Span::new_at("*", 18, 1, 19),
+                            binary!(
+                                Subtraction, Span::new_at("-", 13, 1, 14),
+                                nullary!(variable!(Span::new_at("b", 11, 1, 12))),
+                                nullary!(variable!(Span::new_at("a", 15, 1, 16))),
+                                Span::new_at("(b - a)", 10, 1, 11)
+                            ),
+                            nullary!(variable!(Span::new_at("k", 20, 1, 21))),
+                            Span::new_at("((b - a) * k)", 9, 1, 10)
                         ),
-                        nullary!(variable!(Span::new_at("k", 20, 1, 21)))
-                    ),
-                    nullary!(variable!(Span::new_at("a", 25, 1, 26)))
-                )
-            ))
+                        nullary!(variable!(Span::new_at("a", 25, 1, 26))),
+                        Span::new_at("((b - a) * k) + a", 9, 1, 10)
+                    )
+                ),
+                span : Span::new_at("~ return ((b - a) * k) + a", 0, 1, 1)
+            }
         ));
 
         assert_eq!(return_statement(input), output);
@@ -0,0 +1,793 @@
+use span::Span;
+use state;
+use tokens;
+
+use ast::ast::{
+    Choice,
+    Gather,
+    Line,
+    Token,
+    Weave
+};
+use error::ParseError;
+use internal::ErrorKindExtension;
+
+use rules::comments::todo;
+use rules::identifier::parse_identifier;
+use rules::content::{
+    content,
+    choice_content
+};
+use rules::skip::{
+    end_of_line,
+    skip_lines,
+    skip_within_line
+};
+use super::block::block;
+use super::{
+    consumed,
+    position,
+    span_to,
+    statement
+};
+
+use nom::{
+    Err,
+    IResult,
+    InputLength,
+    Slice
+};
+
+/// The kind of a line, guessed from its first characters.
+#[derive(Clone, Copy)]
+enum LineStart {
+    /// A knot or a stitch header (`=`).
+    Header,
+
+    /// The end of a block (`}`).
+    BlockEnd,
+
+    /// A choice with its depth and whether it is sticky.
+    Choice(usize, bool),
+
+    /// A gather with its depth.
+    Gather(usize),
+
+    /// A statement (`~`), or a declaration (`VAR `, `CONST ` or `LIST `).
+    Statement,
+
+    /// A multiline block or a line starting with an interpolation (`{`).
+    Block,
+
+    /// An annotation (`TODO: …`).
+    Annotation,
+
+    /// A line of content.
+    Content
+}
+
+/// Count the bullets starting a line, e.g. `* *` has a depth of 2. Bullets
+/// can be separated by whitespaces. A `-` followed by `>` is a divert and
+/// is not counted.
+///
+/// Return the number of bullets and the byte length of the bullets.
+fn bullets(input: &str, symbols: &[char]) -> (usize, usize) {
+    let mut depth  = 0;
+    let mut length = 0;
+    let mut iterator = input.char_indices().peekable();
+
+    while let Some((index, item)) = iterator.next() {
+        if symbols.contains(&item) {
+            if item == '-' && matches!(iterator.peek(), Some(&(_, '>'))) {
+                break;
+            }
+
+            depth += 1;
+            length = index + 1;
+        } else if item != ' ' && item != '\t' {
+            break;
+        }
+    }
+
+    (depth, length)
+}
+
+fn line_start(span: Span) -> LineStart {
+    let input = span.as_slice();
+
+    if todo(span).is_ok() {
+        return LineStart::Annotation;
+    }
+
+    match input.chars().next() {
+        Some('=') => LineStart::Header,
+        Some('}') => LineStart::BlockEnd,
+        Some('~') => LineStart::Statement,
+        Some('{') => LineStart::Block,
+        Some('V') | Some('C') | Some('L') if is_declaration_keyword(input) => LineStart::Statement,
+        Some(item) if item == '*' || item == '+' => {
+            LineStart::Choice(bullets(input, &['*', '+']).0, item == '+')
+        },
+        Some('-') => {
+            match bullets(input, &['-']).0 {
+                0     => LineStart::Content,
+                depth => LineStart::Gather(depth)
+            }
+        },
+        _ => LineStart::Content
+    }
+}
+
+/// Check whether a line starts with the `VAR`, `CONST` or `LIST` keyword,
+/// followed by a space or a tab.
+fn is_declaration_keyword(input: &str) -> bool {
+    [tokens::GLOBAL_VARIABLE, tokens::CONSTANT, tokens::LIST]
+        .iter()
+        .any(|keyword| input.starts_with(keyword) && input[keyword.len()..].starts_with([' ', '\t']))
+}
+
+/// Recognize a weave, i.e. a run of lines up to the end of the input or to a
+/// structural terminator.
+pub fn weave(input: Span) -> IResult<Span, Weave> {
+    weave_at(input, 0, false)
+}
+
+/// Recognize a weave nested at a particular depth.
+///
+/// The weave ends before a choice or a gather whose depth is lower than or
+/// equal to `depth` (a dedent), before a knot or stitch header, and before the
+/// end of a block (`}`). Inside a block (`in_block`), a gather marker opens the
+/// next branch, so it ends the weave too.
+///
+/// When the parser recovers from errors (see `ParserOptions::recover`), a
+/// malformed line, or a `}` outside a block, becomes a `Line::Error` and the
+/// weave resumes at the next line.
+pub fn weave_at(input: Span, depth: usize, in_block: bool) -> IResult<Span, Weave> {
+    let mut lines = Vec::new();
+    let mut input = input;
+
+    // The start of the first line, and the input following the last line.
+    let mut first_line = None;
+    let mut end        = input;
+
+    loop {
+        let line_input      = input;
+        let (next_input, _) = skip_lines(input)?;
+
+        if next_input.input_len() == 0 {
+            input = next_input;
+
+            break;
+        }
+
+        let start = line_start(next_input);
+
+        // The comments above a header document it, so they are left to the
+        // header (see `knot` and `stitch`).
+        if let LineStart::Header = start {
+            break;
+        }
+
+        input = next_input;
+
+        match start {
+            LineStart::BlockEnd if in_block || !state::options().recover => break,
+
+            LineStart::Choice(choice_depth, _) if choice_depth <= depth => break,
+
+            LineStart::Gather(gather_depth) if in_block || gather_depth <= depth => break,
+
+            _ => {}
+        }
+
+        // Likewise, the comments above a statement document it.
+        let statement_input = match start {
+            LineStart::Statement => line_input,
+            _                    => input
+        };
+
+        let (next_input, line) = match weave_line(statement_input, start, in_block) {
+            Ok(result) => result,
+            Err(error) => {
+                if !state::options().recover {
+                    return Err(error);
+                }
+
+                error_line(input, error)
+            }
+        };
+
+        first_line.get_or_insert(input);
+        input = next_input;
+        end   = next_input;
+        lines.push(line);
+    }
+
+    let span = match first_line {
+        Some(first_line) => consumed(first_line, end),
+        None             => input.slice(..0)
+    };
+
+    Ok((input, Weave { lines, span }))
+}
+
+/// Recognize a line of a weave, with the body of a choice.
+fn weave_line(input: Span, start: LineStart, in_block: bool) -> IResult<Span, Line> {
+    use nom::ErrorKind;
+
+    match start {
+        LineStart::Choice(choice_depth, sticky) => {
+            let (next_input, mut choice) = choice_line(input, choice_depth, sticky)?;
+            let (next_input, body) = weave_at(next_input, choice_depth, in_block)?;
+
+            if !body.lines.is_empty() {
+                choice.span = span_to(input, body.span);
+            }
+
+            choice.body = body;
+
+            Ok((next_input, Line::Choice(choice)))
+        },
+
+        LineStart::Gather(gather_depth) => {
+            let (next_input, gather) = gather_line(input, gather_depth)?;
+
+            Ok((next_input, Line::Gather(gather)))
+        },
+
+        LineStart::Statement => {
+            map!(input, statement, Line::Statement)
+        },
+
+        LineStart::Annotation => {
+            terminated!(input, map!(todo, Line::Annotation), end_of_line)
+        },
+
+        LineStart::Block => {
+            terminated!(
+                input,
+                alt_complete!(
+                    block
+                    | map!(content, Line::Content)
+                ),
+                end_of_line
+            )
+        },
+
+        LineStart::Content => {
+            terminated!(input, map!(content, Line::Content), end_of_line)
+        },
+
+        LineStart::Header | LineStart::BlockEnd => {
+            Err(Err::Error(error_position!(input, ErrorKind::Tag)))
+        }
+    }
+}
+
+/// Skip a malformed line after `error`, and replace it by a `Line::Error`.
+///
+/// The line ending is not consumed. At least one character is skipped, so
+/// that the parser always makes progress.
+pub fn error_line<'a>(input: Span<'a>, error: Err<Span<'a>>) -> (Span<'a>, Line<'a>) {
+    let slice = input.as_slice();
+    let end   = match slice.find(['\r', '\n']) {
+        Some(0)   => slice.chars().next().map_or(0, char::len_utf8),
+        Some(end) => end,
+        None      => slice.len()
+    };
+
+    (
+        input.slice(end..),
+        Line::Error(Token::new(ParseError::from_nom(input, error), input.slice(..end)))
+    )
+}
+
+/// Recognize the label of a choice or of a gather, i.e. an identifier
+/// between parentheses, e.g. `(greet)`.
+///
+/// Anything else between parentheses is not a label but content, so this
+/// parser never fails hard.
+fn label(input: Span) -> IResult<Span, Span> {
+    delimited!(
+        input,
+        terminated!(tag!(tokens::LEFT_PARENTHESIS), skip_within_line),
+        parse_identifier,
+        preceded!(skip_within_line, tag!(tokens::RIGHT_PARENTHESIS))
+    )
+}
+
+/// Recognize the line of a choice, without its body.
+fn choice_line(input: Span, depth: usize, sticky: bool) -> IResult<Span, Choice> {
+    let (_, length) = bullets(input.as_slice(), &['*', '+']);
+
+    do_parse!(
+        input.slice(length..),
+        label: opt!(preceded!(skip_within_line, label)) >>
+        start: opt!(first!(choice_content)) >>
+        choice_only: opt!(
+            delimited!(
+                preceded!(skip_within_line, tag!(tokens::LEFT_SQUARE_BRACKET)),
+                opt!(choice_content),
+                expect!(ErrorKindExtension::RightSquareBracket, tag!(tokens::RIGHT_SQUARE_BRACKET))
+            )
+        ) >>
+        output: opt!(choice_content) >>
+        end_of_line >>
+        rest: position >>
+        (
+            Choice {
+                depth,
+                sticky,
+                label,
+                start,
+                choice_only: choice_only.and_then(|choice_only| choice_only),
+                output,
+                body: Weave {
+                    lines: Vec::new(),
+                    span : input.slice(..0)
+                },
+                span: consumed(input, rest)
+            }
+        )
+    )
+}
+
+/// Recognize the line of a gather.
+fn gather_line(input: Span, depth: usize) -> IResult<Span, Gather> {
+    let (_, length) = bullets(input.as_slice(), &['-']);
+
+    do_parse!(
+        input.slice(length..),
+        label: opt!(preceded!(skip_within_line, label)) >>
+        content: opt!(first!(content)) >>
+        end_of_line >>
+        rest: position >>
+        (
+            Gather {
+                depth,
+                label,
+                content,
+                span: consumed(input, rest)
+            }
+        )
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::weave;
+    use internal::{
+        Context,
+        Error,
+        ErrorKind,
+        ErrorKindExtension
+    };
+    use options::ParserOptions;
+    use span::Span;
+    use state;
+
+    use ast::ast::{
+        Annotation,
+        Choice,
+        Comments,
+        Content,
+        ContentPart,
+        DeclarationKind,
+        Gather,
+        Line,
+        Sequence,
+        SequenceKind,
+        Statement,
+        Weave
+    };
+
+    macro_rules! text {
+        ($span:expr) => (
+            Content(vec![ContentPart::Text($span)])
+        )
+    }
+
+    #[test]
+    fn case_weave_content() {
+        let input  = Span::new("Hello\n\n  world // comment\n");
+        let output = Ok((
+            Span::new_at("", 26, 4, 1),
+            Weave {
+                lines: vec![
+                    Line::Content(text!(Span::new_at("Hello", 0, 1, 1))),
+                    Line::Content(text!(Span::new_at("world ", 9, 3, 3)))
+                ],
+                span : Span::new_at("Hello\n\n  world // comment", 0, 1, 1)
+            }
+        ));
+
+        assert_eq!(weave(input), output);
+    }
+
+    #[test]
+    fn case_weave_choice() {
+        let input  = Span::new("* Hello [back] right back\n  Nice to hear.\n");
+        let output = Ok((
+            Span::new_at("", 42, 3, 1),
+            Weave {
+                lines: vec![
+                    Line::Choice(Choice {
+                        depth      : 1,
+                        sticky     : false,
+                        label      : None,
+                        start      : Some(text!(Span::new_at("Hello ", 2, 1, 3))),
+                        choice_only: Some(text!(Span::new_at("back", 9, 1, 10))),
+                        output     : Some(text!(Span::new_at(" right back", 14, 1, 15))),
+                        body       : Weave {
+                            lines: vec![Line::Content(text!(Span::new_at("Nice to hear.", 28, 2, 3)))],
+                            span : Span::new_at("Nice to hear.", 28, 2, 3)
+                        },
+                        span       : Span::new_at("* Hello [back] right back\n  Nice to hear.", 0, 1, 1)
+                    })
+                ],
+                span : Span::new_at("* Hello [back] right back\n  Nice to hear.", 0, 1, 1)
+            }
+        ));
+
+        assert_eq!(weave(input), output);
+    }
+
+    #[test]
+    fn case_weave_choice_only() {
+        let input  = Span::new("* [A] B\n");
+        let output = Ok((
+            Span::new_at("", 8, 2, 1),
+            Weave {
+                lines: vec![
+                    Line::Choice(Choice {
+                        depth      : 1,
+                        sticky     : false,
+                        label      : None,
+                        start      : None,
+                        choice_only: Some(text!(Span::new_at("A", 3, 1, 4))),
+                        output     : Some(text!(Span::new_at(" B", 5, 1, 6))),
+                        body       : Weave {
+                            lines: vec![],
+                            span : Span::new_at("", 8, 2, 1)
+                        },
+                        span       : Span::new_at("* [A] B", 0, 1, 1)
+                    })
+                ],
+                span : Span::new_at("* [A] B", 0, 1, 1)
+            }
+        ));
+
+        assert_eq!(weave(input), output);
+    }
+
+    #[test]
+    fn case_weave_labels() {
+        let input  = Span::new("* ( greet ) Hi\n- (end)\n- (a b) c\n");
+        let output = Ok((
+            Span::new_at("", 33, 4, 1),
+            Weave {
+                lines: vec![
+                    Line::Choice(Choice {
+                        depth      : 1,
+                        sticky     : false,
+                        label      : Some(Span::new_at("greet", 4, 1, 5)),
+                        start      : Some(text!(Span::new_at("Hi", 12, 1, 13))),
+                        choice_only: None,
+                        output     : None,
+                        body       : Weave {
+                            lines: vec![],
+                            span : Span::new_at("", 15, 2, 1)
+                        },
+                        span       : Span::new_at("* ( greet ) Hi", 0, 1, 1)
+                    }),
+                    Line::Gather(Gather {
+                        depth  : 1,
+                        label  : Some(Span::new_at("end", 18, 2, 4)),
+                        content: None,
+                        span   : Span::new_at("- (end)", 15, 2, 1)
+                    }),
+                    Line::Gather(Gather {
+                        depth  : 1,
+                        label  : None,
+                        content: Some(text!(Span::new_at("(a b) c", 25, 3, 3))),
+                        span   : Span::new_at("- (a b) c", 23, 3, 1)
+                    })
+                ],
+                span : Span::new_at("* ( greet ) Hi\n- (end)\n- (a b) c", 0, 1, 1)
+            }
+        ));
+
+        assert_eq!(weave(input), output);
+    }
+
+    #[test]
+    fn case_weave_nested_choice_only() {
+        let input = Span::new("* A\n* * [C]\n+ [A]\n");
+
+        match weave(input) {
+            Ok((_, Weave { ref lines, .. })) => {
+                match lines[..] {
+                    [Line::Choice(ref first), Line::Choice(ref second)] => {
+                        match first.body.lines[..] {
+                            [Line::Choice(ref nested)] => {
+                                assert_eq!(nested.depth, 2);
+                                assert_eq!(nested.start, None);
+                                assert_eq!(nested.choice_only, Some(text!(Span::new_at("C", 9, 2, 6))));
+                            },
+
+                            _ => panic!("expected a nested choice")
+                        }
+
+                        assert!(second.sticky);
+                        assert_eq!(second.start, None);
+                        assert_eq!(second.choice_only, Some(text!(Span::new_at("A", 15, 3, 4))));
+                    },
+
+                    _ => panic!("expected two choices")
+                }
+            },
+
+            result => panic!("unexpected result {:?}", result)
+        }
+    }
+
+    #[test]
+    fn case_weave_nested_choices_and_gathers() {
+        let input  = Span::new("* A\n* * B\n+ C\n- D\nE");
+        let output = Ok((
+            Span::new_at("", 19, 5, 2),
+            Weave {
+                lines: vec![
+                    Line::Choice(Choice {
+                        depth      : 1,
+                        sticky     : false,
+                        label      : None,
+                        start      : Some(text!(Span::new_at("A", 2, 1, 3))),
+                        choice_only: None,
+                        output     : None,
+                        body       : Weave {
+                            lines: vec![
+                                Line::Choice(Choice {
+                                    depth      : 2,
+                                    sticky     : false,
+                                    label      : None,
+                                    start      : Some(text!(Span::new_at("B", 8, 2, 5))),
+                                    choice_only: None,
+                                    output     : None,
+                                    body       : Weave {
+                                        lines: vec![],
+                                        span : Span::new_at("", 10, 3, 1)
+                                    },
+                                    span       : Span::new_at("* * B", 4, 2, 1)
+                                })
+                            ],
+                            span : Span::new_at("* * B", 4, 2, 1)
+                        },
+                        span       : Span::new_at("* A\n* * B", 0, 1, 1)
+                    }),
+                    Line::Choice(Choice {
+                        depth      : 1,
+                        sticky     : true,
+                        label      : None,
+                        start      : Some(text!(Span::new_at("C", 12, 3, 3))),
+                        choice_only: None,
+                        output     : None,
+                        body       : Weave {
+                            lines: vec![],
+                            span : Span::new_at("", 14, 4, 1)
+                        },
+                        span       : Span::new_at("+ C", 10, 3, 1)
+                    }),
+                    Line::Gather(Gather {
+                        depth  : 1,
+                        label  : None,
+                        content: Some(text!(Span::new_at("D", 16, 4, 3))),
+                        span   : Span::new_at("- D", 14, 4, 1)
+                    }),
+                    Line::Content(text!(Span::new_at("E", 18, 5, 1)))
+                ],
+                span : Span::new_at("* A\n* * B\n+ C\n- D\nE", 0, 1, 1)
+            }
+        ));
+
+        assert_eq!(weave(input), output);
+    }
+
+    #[test]
+    fn case_weave_annotations() {
+        let input  = Span::new("TODO: intro\nHello\n  // TODO: outro\n");
+        let output = Ok((
+            Span::new_at("", 35, 4, 1),
+            Weave {
+                lines: vec![
+                    Line::Annotation(Annotation::Todo(Span::new_at("intro", 6, 1, 7))),
+                    Line::Content(text!(Span::new_at("Hello", 12, 2, 1))),
+                    Line::Annotation(Annotation::Todo(Span::new_at("outro", 29, 3, 12)))
+                ],
+                span : Span::new_at("TODO: intro\nHello\n  // TODO: outro", 0, 1, 1)
+            }
+        ));
+
+        assert_eq!(weave(input), output);
+    }
+
+    #[test]
+    fn case_weave_stops_at_header() {
+        let input  = Span::new("Hello\n== knot ==\n");
+        let output = Ok((
+            Span::new_at("== knot ==\n", 6, 2, 1),
+            Weave {
+                lines: vec![Line::Content(text!(Span::new_at("Hello", 0, 1, 1)))],
+                span : Span::new_at("Hello", 0, 1, 1)
+            }
+        ));
+
+        assert_eq!(weave(input), output);
+    }
+
+    #[test]
+    fn case_invalid_weave_unexpected_closing_bracket() {
+        let input = Span::new("Hello } world\n");
+
+        assert!(weave(input).is_err());
+    }
+
+    #[test]
+    fn case_invalid_weave_choice_only_content_not_closed() {
+        let input  = Span::new("*[Hello\n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at("\n", 7, 1, 8), ErrorKind::Custom(ErrorKindExtension::RightSquareBracket as u32))));
+
+        assert_eq!(weave(input), output);
+    }
+
+    #[test]
+    fn case_weave_statement_and_block() {
+        let input  = Span::new("* A
+  ~ x = 1
+  {cycle:
+  - B
+  }
+");
+        let result = weave(input);
+
+        match result {
+            Ok((_, Weave { ref lines, .. })) => {
+                match lines[0] {
+                    Line::Choice(ref choice) => {
+                        assert_eq!(choice.body.lines.len(), 2);
+
+                        match choice.body.lines[1] {
+                            Line::Sequence(Sequence { kind: SequenceKind::Cycle, ref branches, .. }) => {
+                                assert_eq!(branches.len(), 1);
+                            },
+                            _ => panic!("expected a sequence")
+                        }
+                    },
+                    _ => panic!("expected a choice")
+                }
+            },
+            _ => panic!("expected a weave")
+        }
+    }
+
+    #[test]
+    fn case_weave_commented_list() {
+        let input  = Span::new("// The state of the kettle.\nLIST kettle = cold, boiling\nCONST max = 3\n");
+        let result = weave(input);
+
+        match result {
+            Ok((_, Weave { ref lines, .. })) => {
+                match lines[..] {
+                    [Line::Statement(Statement::List { ref name, ref comments, .. }), Line::Statement(Statement::Declaration { kind, .. })] => {
+                        assert_eq!(name.0, Span::new_at("kettle", 33, 2, 6));
+                        assert_eq!(comments.leading, vec![Span::new_at(" The state of the kettle.", 2, 1, 3)]);
+                        assert_eq!(kind, DeclarationKind::Constant);
+                    },
+                    _ => panic!("expected a list and a constant")
+                }
+            },
+            _ => panic!("expected a weave")
+        }
+    }
+
+    #[test]
+    fn case_weave_commented_declarations() {
+        let input  = Span::new("Hello\n\n// The score.\nVAR score = 0 // points\n~ x = 1 /* hp */\n");
+        let result = weave(input);
+
+        match result {
+            Ok((_, Weave { ref lines, .. })) => {
+                assert_eq!(lines.len(), 3);
+
+                match lines[1] {
+                    Line::Statement(Statement::Declaration { kind, ref comments, .. }) => {
+                        assert_eq!(kind, DeclarationKind::Global);
+                        assert_eq!(
+                            *comments,
+                            Comments {
+                                leading : vec![Span::new_at(" The score.", 9, 3, 3)],
+                                trailing: vec![Span::new_at(" points", 37, 4, 17)]
+                            }
+                        );
+                    },
+                    _ => panic!("expected a declaration")
+                }
+
+                match lines[2] {
+                    Line::Statement(Statement::Declaration { kind, ref comments, .. }) => {
+                        assert_eq!(kind, DeclarationKind::Statement);
+                        assert_eq!(
+                            *comments,
+                            Comments {
+                                leading : vec![],
+                                trailing: vec![Span::new_at(" hp ", 55, 5, 11)]
+                            }
+                        );
+                    },
+                    _ => panic!("expected a declaration")
+                }
+            },
+            _ => panic!("expected a weave")
+        }
+    }
+
+    #[test]
+    fn case_weave_recover_malformed_lines() {
+        let options = ParserOptions {
+            recover: true,
+            ..ParserOptions::default()
+        };
+        let input  = Span::new("Hello } world\n}\nNext\n");
+        let result = state::with_state(input.as_slice(), options, || weave(input));
+
+        match result {
+            Ok((rest, Weave { ref lines, .. })) => {
+                assert_eq!(rest, Span::new_at("", 21, 4, 1));
+                assert_eq!(lines.len(), 3);
+
+                match lines[0] {
+                    Line::Error(ref error) => assert_eq!(error.span, Span::new_at("Hello } world", 0, 1, 1)),
+                    _ => panic!("expected an error")
+                }
+
+                match lines[1] {
+                    Line::Error(ref error) => {
+                        assert_eq!(error.span, Span::new_at("}", 14, 2, 1));
+                        assert_eq!(error.value.message(), "unexpected `}`");
+                    },
+                    _ => panic!("expected an error")
+                }
+
+                assert_eq!(lines[2], Line::Content(text!(Span::new_at("Next", 16, 3, 1))));
+            },
+            _ => panic!("expected a weave")
+        }
+    }
+
+    #[test]
+    fn case_weave_recover_in_choice_body() {
+        let options = ParserOptions {
+            recover: true,
+            ..ParserOptions::default()
+        };
+        let input  = Span::new("* A\n  {x\n* B\n");
+        let result = state::with_state(input.as_slice(), options, || weave(input));
+
+        match result {
+            Ok((_, Weave { ref lines, .. })) => {
+                assert_eq!(lines.len(), 2);
+
+                match lines[0] {
+                    Line::Choice(ref choice) => {
+                        match choice.body.lines[..] {
+                            [Line::Error(ref error)] => assert_eq!(error.span, Span::new_at("{x", 6, 2, 3)),
+                            _ => panic!("expected an error")
+                        }
+                    },
+                    _ => panic!("expected a choice")
+                }
+            },
+            _ => panic!("expected a weave")
+        }
+    }
+}
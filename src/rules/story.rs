@@ -0,0 +1,319 @@
+use span::Span;
+use state;
+
+use ast::ast::{
+    Line,
+    Story,
+    Weave
+};
+
+use rules::skip::skip_lines;
+use rules::statements::knot::knot;
+use rules::statements::span_to;
+use rules::statements::weave::{
+    error_line,
+    weave
+};
+
+use nom::{
+    IResult,
+    InputLength,
+    Offset,
+    Slice
+};
+
+/// Recognize a story, i.e. the root weave followed by the knots, up to the
+/// end of the input.
+///
+/// When the parser recovers from errors (see `ParserOptions::recover`), a
+/// malformed knot or stitch header becomes a `Line::Error`. The header and
+/// the lines following it are appended to the last weave of the story.
+pub fn story(input: Span) -> IResult<Span, Story> {
+    use nom::{
+        Err,
+        ErrorKind
+    };
+
+    let source = input;
+    let (mut input, body) = weave(input)?;
+    let mut story = Story {
+        body,
+        knots: Vec::new(),
+        span : source
+    };
+
+    loop {
+        // The knot starts before the skipped lines, since it keeps the
+        // comments above its header; a malformed line starts after them.
+        let (line_input, _) = skip_lines(input)?;
+
+        if line_input.input_len() == 0 {
+            input = line_input;
+
+            break;
+        }
+
+        match knot(input) {
+            Ok((next_input, knot)) => {
+                story.knots.push(knot);
+                input = next_input;
+            },
+
+            Err(Err::Failure(error)) if !state::options().recover => {
+                return Err(Err::Failure(error));
+            },
+
+            Err(error) => {
+                if !state::options().recover {
+                    return Err(Err::Error(error_position!(line_input, ErrorKind::Eof)));
+                }
+
+                let (next_input, line) = error_line(line_input, error);
+                let (next_input, weave) = weave(next_input)?;
+
+                let end = match line {
+                    Line::Error(ref token) if weave.lines.is_empty() => token.span,
+                    _                                                 => weave.span
+                };
+
+                let mut lines = vec![line];
+                lines.extend(weave.lines);
+
+                append(source, &mut story, lines, span_to(line_input, end));
+
+                input = next_input;
+            }
+        }
+    }
+
+    Ok((input, story))
+}
+
+/// Append `lines`, which cover `span`, to the last weave of a story: the
+/// body of the last stitch or of the last knot, or the body of the story.
+/// The spans of the weave, and of its stitch and knot, are extended to
+/// cover the lines.
+fn append<'a>(source: Span<'a>, story: &mut Story<'a>, lines: Vec<Line<'a>>, span: Span<'a>) {
+    match story.knots.last_mut() {
+        Some(knot) => {
+            match knot.stitches.last_mut() {
+                Some(stitch) => {
+                    append_to_weave(source, &mut stitch.body, lines, span);
+                    stitch.span = cover(source, stitch.span, span);
+                },
+
+                None => append_to_weave(source, &mut knot.body, lines, span)
+            }
+
+            knot.span = cover(source, knot.span, span);
+        },
+
+        None => append_to_weave(source, &mut story.body, lines, span)
+    }
+}
+
+fn append_to_weave<'a>(source: Span<'a>, weave: &mut Weave<'a>, lines: Vec<Line<'a>>, span: Span<'a>) {
+    weave.span = if weave.lines.is_empty() {
+        span
+    } else {
+        cover(source, weave.span, span)
+    };
+
+    weave.lines.extend(lines);
+}
+
+/// The span of `source` from the start of `first` to the end of `last`, two
+/// spans sliced from it.
+fn cover<'a>(source: Span<'a>, first: Span<'a>, last: Span<'a>) -> Span<'a> {
+    span_to(source.slice(source.offset(&first)..), last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::story;
+    use internal::{
+        Context,
+        Error,
+        ErrorKind,
+        ErrorKindExtension
+    };
+    use options::{
+        Dialect,
+        ParserOptions
+    };
+    use span::Span;
+    use state;
+
+    use ast::ast::{
+        Choice,
+        Comments,
+        Content,
+        ContentPart,
+        Knot,
+        Line,
+        Stitch,
+        Story,
+        Weave
+    };
+
+    macro_rules! text {
+        ($span:expr) => (
+            Content(vec![ContentPart::Text($span)])
+        )
+    }
+
+    #[test]
+    fn case_story() {
+        let input  = Span::new("Hello\n\n== london ==\n* Go\n  Gone\n");
+        let output = Ok((
+            Span::new_at("", 32, 6, 1),
+            Story {
+                body : Weave {
+                    lines: vec![Line::Content(text!(Span::new_at("Hello", 0, 1, 1)))],
+                    span : Span::new_at("Hello", 0, 1, 1)
+                },
+                knots: vec![
+                    Knot {
+                        name      : Span::new_at("london", 10, 3, 4),
+                        function  : false,
+                        parameters: vec![],
+                        body      : Weave {
+                            lines: vec![
+                                Line::Choice(Choice {
+                                    depth      : 1,
+                                    sticky     : false,
+                                    label      : None,
+                                    start      : Some(text!(Span::new_at("Go", 22, 4, 3))),
+                                    choice_only: None,
+                                    output     : None,
+                                    body       : Weave {
+                                        lines: vec![Line::Content(text!(Span::new_at("Gone", 27, 5, 3)))],
+                                        span : Span::new_at("Gone", 27, 5, 3)
+                                    },
+                                    span       : Span::new_at("* Go\n  Gone", 20, 4, 1)
+                                })
+                            ],
+                            span : Span::new_at("* Go\n  Gone", 20, 4, 1)
+                        },
+                        stitches  : vec![],
+                        comments  : Comments::default(),
+                        span      : Span::new_at("== london ==\n* Go\n  Gone", 7, 3, 1)
+                    }
+                ],
+                span : Span::new("Hello\n\n== london ==\n* Go\n  Gone\n")
+            }
+        ));
+
+        assert_eq!(story(input), output);
+    }
+
+    #[test]
+    fn case_story_with_comments() {
+        let input  = Span::new("Hello\n// The capital.\n== london ==\n= first_class\n\n// Ignored.\n");
+        let output = Ok((
+            Span::new_at("", 62, 7, 1),
+            Story {
+                body : Weave {
+                    lines: vec![Line::Content(text!(Span::new_at("Hello", 0, 1, 1)))],
+                    span : Span::new_at("Hello", 0, 1, 1)
+                },
+                knots: vec![
+                    Knot {
+                        name      : Span::new_at("london", 25, 3, 4),
+                        function  : false,
+                        parameters: vec![],
+                        body      : Weave {
+                            lines: vec![],
+                            span : Span::new_at("", 35, 4, 1)
+                        },
+                        stitches  : vec![
+                            Stitch {
+                                name    : Span::new_at("first_class", 37, 4, 3),
+                                body    : Weave {
+                                    lines: vec![],
+                                    span : Span::new_at("", 62, 7, 1)
+                                },
+                                comments: Comments::default(),
+                                span    : Span::new_at("= first_class", 35, 4, 1)
+                            }
+                        ],
+                        comments  : Comments {
+                            leading : vec![Span::new_at(" The capital.", 8, 2, 3)],
+                            trailing: vec![]
+                        },
+                        span      : Span::new_at("== london ==\n= first_class", 22, 3, 1)
+                    }
+                ],
+                span : Span::new("Hello\n// The capital.\n== london ==\n= first_class\n\n// Ignored.\n")
+            }
+        ));
+
+        assert_eq!(story(input), output);
+    }
+
+    #[test]
+    fn case_story_empty() {
+        let input  = Span::new("");
+        let output = Ok((
+            Span::new_at("", 0, 1, 1),
+            Story {
+                body : Weave {
+                    lines: vec![],
+                    span : Span::new("")
+                },
+                knots: vec![],
+                span : Span::new("")
+            }
+        ));
+
+        assert_eq!(story(input), output);
+    }
+
+    #[test]
+    fn case_invalid_story_unexpected_block_end() {
+        let input = Span::new("Hello\n}\n");
+
+        assert!(story(input).is_err());
+    }
+
+    #[test]
+    fn case_invalid_story_strict_dialect() {
+        let input  = Span::new("Hello\n~ x = 0x2a\n");
+        let output = Err(Error::Failure(Context::Code(Span::new_at("0x", 12, 2, 7), ErrorKind::Custom(ErrorKindExtension::ExtendedSyntax as u32))));
+
+        assert_eq!(state::with_state(input.as_slice(), ParserOptions::new(Dialect::Strict), || story(input)), output);
+    }
+
+    #[test]
+    fn case_story_recover_malformed_stitch_header() {
+        let options = ParserOptions {
+            recover: true,
+            ..ParserOptions::default()
+        };
+        let input  = Span::new("== london ==\nA\n= bad stitch\nB\n");
+        let result = state::with_state(input.as_slice(), options, || story(input));
+
+        match result {
+            Ok((_, Story { ref knots, .. })) => {
+                assert_eq!(knots.len(), 1);
+
+                let lines = &knots[0].body.lines;
+
+                assert_eq!(lines.len(), 3);
+                assert_eq!(lines[0], Line::Content(text!(Span::new_at("A", 13, 2, 1))));
+
+                match lines[1] {
+                    Line::Error(ref error) => assert_eq!(error.span, Span::new_at("= bad stitch", 15, 3, 1)),
+                    _ => panic!("expected an error")
+                }
+
+                assert_eq!(lines[2], Line::Content(text!(Span::new_at("B", 28, 4, 1))));
+
+                // The spans cover the appended lines.
+                assert_eq!(knots[0].body.span, Span::new_at("A\n= bad stitch\nB", 13, 2, 1));
+                assert_eq!(knots[0].span, Span::new_at("== london ==\nA\n= bad stitch\nB", 0, 1, 1));
+            },
+            _ => panic!("expected a story")
+        }
+    }
+}
@@ -41,8 +41,8 @@ pub fn string(span: Span) -> IResult<Span, Literal> {
             if let Some((next_index, next_item)) = iterator.next() {
                 range = offset..index;
 
-                if let None = output {
-                    let mut data = (&input[range]).to_string();
+                if output.is_none() {
+                    let mut data = input[range].to_string();
                     data.push(next_item);
                     output = Some(data);
                 } else if let Some(data) = output.as_mut() {
@@ -57,8 +57,8 @@ pub fn string(span: Span) -> IResult<Span, Literal> {
         } else if item == '"' {
             range = offset..index;
 
-            if let None = output {
-                output = Some((&input[range]).to_string());
+            if output.is_none() {
+                output = Some(input[range].to_string());
             } else if let Some(data) = output.as_mut() {
                 data.push_str(&input[range]);
             }
@@ -197,7 +197,7 @@ mod tests {
     fn case_string_utf8_japanese() {
         let input  = Span::new("\"出テル村七らぐし鏡始ヤユ権連ふこ応法マチスイ忽引コマヱシ際31意団ナ割平と上内すめリ気両え検抜爆じずラ。経イフシ兄町ユフヤ造審くー集57事め東福オセ会爆レコラハ健投ケ尾2連製テオロコ結踊ホヌラヘ購最や話催俊ち疑質メ都能坂居にぜ。南ば続壮ス韓再いき揃問業りぱ氷3連乗ぶあょ属保しぶ意者ル功並ょ末手第場ケサ教施セヤ式5幹回ッみぽ娘公賀待種男のせぽ\"");
         let output = Ok((
-            Span::new_at("", 501, 1, 502),
+            Span::new_at("", 501, 1, 174),
             Literal::String(
                 Token::new(
                     "出テル村七らぐし鏡始ヤユ権連ふこ応法マチスイ忽引コマヱシ際31意団ナ割平と上内すめリ気両え検抜爆じずラ。経イフシ兄町ユフヤ造審くー集57事め東福オセ会爆レコラハ健投ケ尾2連製テオロコ結踊ホヌラヘ購最や話催俊ち疑質メ都能坂居にぜ。南ば続壮ス韓再いき揃問業りぱ氷3連乗ぶあょ属保しぶ意者ル功並ょ末手第場ケサ教施セヤ式5幹回ッみぽ娘公賀待種男のせぽ".to_string(),
@@ -213,7 +213,7 @@ mod tests {
     fn case_string_utf8_russian() {
         let input  = Span::new("\"Лорем ипсум долор сит амет, еи вис хабео мутат, меа децоре десеруиссе ут, лорем интеллегат вим ан. Еа цивибус епицуреи атоморум вис, семпер ессент интегре иус ад. Еи вих регионе сцрипсерит. Но сеа ерат маиестатис, ерипуит детрахит ат нам, ад цонгуе волуптуа медиоцрем при\"");
         let output = Ok((
-            Span::new_at("", 492, 1, 493),
+            Span::new_at("", 492, 1, 274),
             Literal::String(
                 Token::new(
                     "Лорем ипсум долор сит амет, еи вис хабео мутат, меа децоре десеруиссе ут, лорем интеллегат вим ан. Еа цивибус епицуреи атоморум вис, семпер ессент интегре иус ад. Еи вих регионе сцрипсерит. Но сеа ерат маиестатис, ерипуит детрахит ат нам, ад цонгуе волуптуа медиоцрем при".to_string(),
@@ -229,7 +229,7 @@ mod tests {
     fn case_string_utf8_greek() {
         let input  = Span::new("\"Λορεμ ιπσθμ δολορ σιτ αμετ, ει σιτ ινvιδθντ εθριπιδισ ινcορρθπτε. Σεα αδ διcατ δολορ, εαμ cομμοδο νθσqθαμ σιγνιφερθμqθε εα. Μολλισ σινγθλισ ατ μει. Ερρορ δισσεντιθντ εξ μεα, θτ vισ ηινc vιδε, τε vελ πρινcιπεσ σcριπσεριτ. Αδ εαμ λθcιλιθσ παρτιενδο ιμπερδιετ, qθο πθτεντ τιμεαμ ετ\"");
         let output = Ok((
-            Span::new_at("", 492, 1, 493),
+            Span::new_at("", 492, 1, 281),
             Literal::String(
                 Token::new(
                     "Λορεμ ιπσθμ δολορ σιτ αμετ, ει σιτ ινvιδθντ εθριπιδισ ινcορρθπτε. Σεα αδ διcατ δολορ, εαμ cομμοδο νθσqθαμ σιγνιφερθμqθε εα. Μολλισ σινγθλισ ατ μει. Ερρορ δισσεντιθντ εξ μεα, θτ vισ ηινc vιδε, τε vελ πρινcιπεσ σcριπσεριτ. Αδ εαμ λθcιλιθσ παρτιενδο ιμπερδιετ, qθο πθτεντ τιμεαμ ετ".to_string(),
@@ -250,7 +250,7 @@ mod tests {
 
         let mut input_data = "\"".to_string();
         input_data.push_str(lorem_ipsum);
-        input_data.push_str("\"");
+        input_data.push('"');
 
         let input  = Span::new(&input_data);
 
@@ -8,6 +8,17 @@ named_attr!(
     incomplete!(is_a!(" \t"))
 );
 
+named_attr!(
+    #[doc="
+        Recognize a line ending (`\\n` or `\\r\\n`).
+    "],
+    pub newline<Span, Span>,
+    alt_complete!(
+        tag!("\n")
+        | tag!("\r\n")
+    )
+);
+
 #[cfg(test)]
 mod tests {
     use super::super::super::internal::{
@@ -16,7 +27,10 @@ mod tests {
         ErrorKind
     };
     use super::super::super::span::Span;
-    use super::whitespace;
+    use super::{
+        whitespace,
+        newline
+    };
 
     #[test]
     fn case_whitespace_space() {
@@ -65,4 +79,28 @@ mod tests {
 
         assert_eq!(whitespace(input), output);
     }
+
+    #[test]
+    fn case_newline() {
+        let input  = Span::new("\nfoo");
+        let output = Ok((Span::new_at("foo", 1, 2, 1), Span::new_at("\n", 0, 1, 1)));
+
+        assert_eq!(newline(input), output);
+    }
+
+    #[test]
+    fn case_newline_carriage_return() {
+        let input  = Span::new("\r\nfoo");
+        let output = Ok((Span::new_at("foo", 2, 2, 1), Span::new_at("\r\n", 0, 1, 1)));
+
+        assert_eq!(newline(input), output);
+    }
+
+    #[test]
+    fn case_invalid_newline() {
+        let input  = Span::new("foo\n");
+        let output = Err(Error::Error(Context::Code(input, ErrorKind::Alt)));
+
+        assert_eq!(newline(input), output);
+    }
 }
\ No newline at end of file
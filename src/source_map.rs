@@ -0,0 +1,227 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{
+    Path,
+    PathBuf
+};
+
+use line_index::{
+    self,
+    ColumnUnit,
+    LineIndex
+};
+use span::Span;
+
+/// The identifier of a file of a `SourceMap`.
+///
+/// The default identifier is the one of the first file added to a source
+/// map, so that the spans of a single file story need no identifier.
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FileId(u32);
+
+impl FileId {
+    /// Create a file identifier from its index in a source map.
+    pub fn new(index: u32) -> Self {
+        FileId(index)
+    }
+
+    /// The index of the file in its source map.
+    pub fn index(&self) -> u32 {
+        self.0
+    }
+}
+
+/// A file loaded in a `SourceMap`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SourceFile {
+    /// The path of the file.
+    pub path: PathBuf,
+
+    /// The text of the file.
+    pub text: String,
+
+    /// The byte offsets of the starts of the lines of the text.
+    line_starts: Vec<usize>
+}
+
+impl SourceFile {
+    /// Create a file, and index the lines of its text.
+    pub fn new<P: Into<PathBuf>>(path: P, text: String) -> Self {
+        SourceFile {
+            path       : path.into(),
+            line_starts: line_index::line_starts(&text),
+            text
+        }
+    }
+
+    /// The index of the lines of the text, computed once when the file was
+    /// created.
+    pub fn line_index(&self) -> LineIndex<'_> {
+        LineIndex::from_line_starts(&self.text, &self.line_starts)
+    }
+}
+
+/// The location of a span in a file, displayed as `path:line:column`.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Location<'m> {
+    /// The path of the file.
+    pub path: &'m Path,
+
+    /// The line number, starting at 1.
+    pub line: u32,
+
+    /// The column number, in Unicode scalar values, starting at 1.
+    pub column: u32
+}
+
+impl<'m> fmt::Display for Location<'m> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}:{}:{}", self.path.display(), self.line, self.column)
+    }
+}
+
+/// A set of files owning their texts, e.g. a story and the files it
+/// includes (`INCLUDE newspaper.ink`).
+///
+/// Each file has a `FileId`, carried by the spans of its text, so that a
+/// span can be resolved to its file.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct SourceMap {
+    files: Vec<SourceFile>
+}
+
+impl SourceMap {
+    /// Create an empty source map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file, and get its identifier.
+    pub fn add<P: Into<PathBuf>>(&mut self, path: P, text: String) -> FileId {
+        self.files.push(SourceFile::new(path, text));
+
+        FileId::new(self.files.len() as u32 - 1)
+    }
+
+    /// Read a file from the file system and add it, unless it is already
+    /// loaded. Get its identifier.
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> io::Result<FileId> {
+        let path = path.as_ref();
+
+        if let Some(file) = self.find(path) {
+            return Ok(file);
+        }
+
+        let text = fs::read_to_string(path)?;
+
+        Ok(self.add(path, text))
+    }
+
+    /// Find a file by its path.
+    pub fn find<P: AsRef<Path>>(&self, path: P) -> Option<FileId> {
+        self.files
+            .iter()
+            .position(|file| file.path == path.as_ref())
+            .map(|index| FileId::new(index as u32))
+    }
+
+    /// Get a file.
+    pub fn file(&self, file: FileId) -> Option<&SourceFile> {
+        self.files.get(file.index() as usize)
+    }
+
+    /// Iterate over the files and their identifiers, in the order they were
+    /// added.
+    pub fn files(&self) -> impl Iterator<Item = (FileId, &SourceFile)> {
+        self.files
+            .iter()
+            .enumerate()
+            .map(|(index, file)| (FileId::new(index as u32), file))
+    }
+
+    /// Get a span covering the whole text of a file, to parse it. The lines
+    /// and the columns of the spans sliced from it are resolved through the
+    /// index of the lines of the file.
+    pub fn span(&self, file: FileId) -> Option<Span<'_>> {
+        self.file(file).map(|source| Span::new_indexed(&source.text, &source.line_starts).with_file(file))
+    }
+
+    /// Resolve a span to its location, if its file belongs to this source
+    /// map.
+    pub fn locate(&self, span: &Span) -> Option<Location<'_>> {
+        self.locate_offset(span.file, span.offset)
+    }
+
+    /// Resolve a byte offset of a file to its location, if the file belongs
+    /// to this source map.
+    pub fn locate_offset(&self, file: FileId, offset: usize) -> Option<Location<'_>> {
+        self.file(file).map(|source| {
+            let index = source.line_index();
+
+            Location {
+                path  : &source.path,
+                line  : index.line(offset),
+                column: index.column(offset, ColumnUnit::Char)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        FileId,
+        SourceMap
+    };
+    use span::Span;
+
+    use nom::Slice;
+
+    #[test]
+    fn case_source_map_add_and_find() {
+        let mut source_map = SourceMap::new();
+        let main           = source_map.add("main.ink", "Hello\n".to_string());
+        let newspaper      = source_map.add("newspaper.ink", "News\n".to_string());
+
+        assert_eq!(main, FileId::default());
+        assert_eq!(newspaper, FileId::new(1));
+        assert_eq!(source_map.find("newspaper.ink"), Some(newspaper));
+        assert_eq!(source_map.find("missing.ink"), None);
+        assert_eq!(source_map.file(newspaper).map(|file| file.text.as_str()), Some("News\n"));
+        assert_eq!(source_map.files().count(), 2);
+    }
+
+    #[test]
+    fn case_source_map_locate() {
+        let mut source_map = SourceMap::new();
+        source_map.add("main.ink", "Hello\n".to_string());
+        let newspaper      = source_map.add("news/paper.ink", "News\n  é = 1\n".to_string());
+
+        let span = source_map.span(newspaper).unwrap().slice(10..);
+
+        assert_eq!(span.file, newspaper);
+        assert_eq!(source_map.locate(&span).unwrap().to_string(), "news/paper.ink:2:5");
+        assert_eq!(source_map.locate_offset(newspaper, 10).unwrap().to_string(), "news/paper.ink:2:5");
+        assert_eq!(source_map.locate(&Span::new("x").with_file(FileId::new(7))), None);
+    }
+
+    #[test]
+    fn case_source_map_span_resolves_through_the_line_index() {
+        let mut source_map = SourceMap::new();
+        let file           = source_map.add("main.ink", "Hello\n\n  é = 1\n".to_string());
+        let span           = source_map.span(file).unwrap().slice(12..);
+
+        assert_eq!((span.line(), span.column()), (3, 5));
+        assert_eq!(span, Span::new("Hello\n\n  é = 1\n").slice(12..).with_file(file));
+        assert_eq!(source_map.file(file).unwrap().line_index().line_count(), 4);
+    }
+
+    #[test]
+    fn case_source_map_load_missing_file() {
+        let mut source_map = SourceMap::new();
+
+        assert!(source_map.load("/this/file/does/not/exist.ink").is_err());
+    }
+}
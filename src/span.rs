@@ -16,6 +16,8 @@ use nom::{
     IResult
 };
 
+use std::fmt;
+use std::ptr;
 use std::str::Chars;
 use std::str::CharIndices;
 use std::cmp::Ordering;
@@ -29,25 +31,168 @@ use std::ops::{
 
 use memchr;
 
+use line_index::{
+    ColumnUnit,
+    LineIndex
+};
+use source_map::FileId;
+use state;
+
+/// A position in the source.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Position {
+    /// The byte offset, starting at 0.
+    pub offset: usize,
+
+    /// The line number, starting at 1.
+    pub line: u32,
+
+    /// The column number, in Unicode scalar values, starting at 1.
+    pub column: u32
+}
+
+impl Position {
+    /// Compute the position following a consumed input, starting at this
+    /// position.
+    ///
+    /// The column is counted in Unicode scalar values, see `LineIndex` for
+    /// the other units.
+    pub fn advance(&self, consumed: &str) -> Self {
+        let consumed_as_bytes = consumed.as_bytes();
+        let number_of_newlines = memchr::Memchr::new(b'\n', consumed_as_bytes).count() as u32;
+
+        let column = match memchr::memrchr(b'\n', consumed_as_bytes) {
+            Some(last_newline_position) => consumed[last_newline_position + 1..].chars().count() as u32 + 1,
+            None                        => self.column + consumed.chars().count() as u32
+        };
+
+        Position {
+            offset: self.offset + consumed.len(),
+            line  : self.line + number_of_newlines,
+            column
+        }
+    }
+}
+
+/// A range of the source, from a start position (included) to an end
+/// position (excluded).
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SourceRange {
+    /// The position of the first byte of the range.
+    pub start: Position,
+
+    /// The position following the last byte of the range.
+    pub end: Position
+}
+
+impl SourceRange {
+    /// Create a range. The start must not be after the end.
+    pub fn new(start: Position, end: Position) -> Self {
+        debug_assert!(start.offset <= end.offset, "The start of a range must not be after its end.");
+
+        SourceRange {
+            start,
+            end
+        }
+    }
+
+    /// The smallest range covering both ranges, and the source between them.
+    pub fn merge(&self, other: &Self) -> Self {
+        SourceRange {
+            start: self.start.min(other.start),
+            end  : self.end.max(other.end)
+        }
+    }
+
+    /// Check whether another range is inside this range.
+    pub fn contains(&self, other: &Self) -> bool {
+        self.start.offset <= other.start.offset && other.end.offset <= self.end.offset
+    }
+
+    /// Check whether a byte offset is inside this range.
+    pub fn contains_offset(&self, offset: usize) -> bool {
+        self.start.offset <= offset && offset < self.end.offset
+    }
+
+    /// The byte range, to slice the source.
+    pub fn byte_range(&self) -> Range<usize> {
+        self.start.offset..self.end.offset
+    }
+
+    /// The length of the range, in bytes.
+    pub fn len(&self) -> usize {
+        self.end.offset - self.start.offset
+    }
+
+    /// Check whether the range is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a> From<Span<'a>> for SourceRange {
+    fn from(span: Span<'a>) -> Self {
+        span.range()
+    }
+}
+
+/// Where the line and the column of a span come from.
+#[derive(Debug, Copy, Clone)]
+enum Origin<'a> {
+    /// The whole source the span belongs to. The line and the column are
+    /// computed on demand from the offset, by scanning the source.
+    Source(&'a str),
+
+    /// The whole source the span belongs to, with the byte offsets of the
+    /// starts of its lines. The line is found by a binary search.
+    Indexed {
+        source     : &'a str,
+        line_starts: &'a [usize]
+    },
+
+    /// The whole source the span belongs to, with the line of the span and
+    /// the byte offset of the start of this line, found when the span was
+    /// sliced. The column is counted from the start of the line.
+    Line {
+        source    : &'a str,
+        line      : u32,
+        line_start: usize
+    },
+
+    /// A known line and column, for a span created without its source.
+    Position {
+        line  : u32,
+        column: u32
+    }
+}
+
 /// A span is a set of meta information about a token.
 ///
+/// A span is a byte range of a source. The line and the column are derived
+/// from the offset. A span created from a `LineIndex`, or by
+/// `SourceMap::span`, looks its line up in the starts of the lines of its
+/// source. While `parse` runs, the lines of its input are indexed too, and
+/// each sliced span finds its line in this index. Otherwise, a span created
+/// by `Span::new` scans its source up to its offset, which gets slow on a
+/// large source.
+///
 /// The `Span` structure can be used as an input of the nom parsers.
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Copy, Clone)]
 pub struct Span<'a> {
     /// The offset represents the position of the slice relatively to
     /// the input of the parser_old. It starts at offset 0.
     pub offset: usize,
 
-    /// The line number of the slice relatively to the input of the
-    /// parser_old. It starts at line 1.
-    pub line: u32,
-
-    /// The column number of the slice relatively to the input of the
-    /// parser_old. It starts at column 1.
-    pub column: u32,
+    /// The file of the slice, see `SourceMap`.
+    pub file: FileId,
 
     /// The fragment that is spanned.
-    fragment: &'a str
+    fragment: &'a str,
+
+    /// The origin of the line and the column of the slice.
+    origin: Origin<'a>
 }
 
 impl<'a> Span<'a> {
@@ -57,20 +202,70 @@ impl<'a> Span<'a> {
     /// `offset` starts at 0, `line` starts at 1, and `column` starts at 1.
     pub fn new(input: &'a str) -> Self {
         Span {
-            offset: 0,
-            line  : 1,
-            column: 1,
-            fragment : input
+            offset   : 0,
+            file     : FileId::default(),
+            fragment : input,
+            origin   : Origin::Source(input)
+        }
+    }
+
+    /// Create a span for a particular input, with the byte offsets of the
+    /// starts of its lines, see `line_index::line_starts`.
+    ///
+    /// The lines and the columns of the spans sliced from this one are
+    /// resolved through these offsets.
+    pub fn new_indexed(input: &'a str, line_starts: &'a [usize]) -> Self {
+        Span {
+            origin: Origin::Indexed { source: input, line_starts },
+            ..Self::new(input)
         }
     }
 
     /// Create a span for a particular input at a particular offset, line, and column.
+    ///
+    /// The span does not know its source, so the positions of the spans
+    /// sliced from it are computed while slicing.
     pub fn new_at(input: &'a str, offset: usize, line: u32, column: u32) -> Self {
         Span {
-            offset: offset,
-            line  : line,
-            column: column,
-            fragment : input
+            offset,
+            file     : FileId::default(),
+            fragment : input,
+            origin   : Origin::Position { line, column }
+        }
+    }
+
+    /// The line number of the slice relatively to the input of the
+    /// parser. It starts at line 1.
+    pub fn line(&self) -> u32 {
+        match self.origin {
+            Origin::Source(source) => {
+                memchr::Memchr::new(b'\n', &source.as_bytes()[..self.offset]).count() as u32 + 1
+            },
+
+            Origin::Indexed { source, line_starts } => LineIndex::from_line_starts(source, line_starts).line(self.offset),
+
+            Origin::Line { line, .. } | Origin::Position { line, .. } => line
+        }
+    }
+
+    /// The column number of the slice relatively to the input of the
+    /// parser, in Unicode scalar values. It starts at column 1.
+    pub fn column(&self) -> u32 {
+        match self.origin {
+            Origin::Source(source) => {
+                let before = &source[..self.offset];
+                let start  = memchr::memrchr(b'\n', before.as_bytes()).map_or(0, |newline| newline + 1);
+
+                before[start..].chars().count() as u32 + 1
+            },
+
+            Origin::Indexed { source, line_starts } => {
+                LineIndex::from_line_starts(source, line_starts).column(self.offset, ColumnUnit::Char)
+            },
+
+            Origin::Line { source, line_start, .. } => source[line_start..self.offset].chars().count() as u32 + 1,
+
+            Origin::Position { column, .. } => column
         }
     }
 
@@ -85,6 +280,159 @@ impl<'a> Span<'a> {
     pub fn as_slice(&self) -> &'a str {
         self.fragment
     }
+
+    /// Attach a file to the span. The file is carried to all the spans
+    /// sliced from this one.
+    #[inline]
+    pub fn with_file(self, file: FileId) -> Self {
+        Span {
+            file,
+            ..self
+        }
+    }
+
+    /// The position of the first byte of the span.
+    #[inline]
+    pub fn start(&self) -> Position {
+        Position {
+            offset: self.offset,
+            line  : self.line(),
+            column: self.column()
+        }
+    }
+
+    /// The position following the last byte of the span.
+    #[inline]
+    pub fn end(&self) -> Position {
+        self.start().advance(self.fragment)
+    }
+
+    /// The range of the source covered by the span.
+    #[inline]
+    pub fn range(&self) -> SourceRange {
+        SourceRange::new(self.start(), self.end())
+    }
+
+    /// The smallest range covering both spans, and the source between them.
+    #[inline]
+    pub fn merge(&self, other: &Self) -> SourceRange {
+        self.range().merge(&other.range())
+    }
+
+    /// The smallest span covering both spans, and the source between them.
+    ///
+    /// The spans must be sliced from the same source, so that the source
+    /// between them is known: a span created by `Span::new_at` does not know
+    /// its source, hence `None`.
+    pub fn join(&self, other: &Self) -> Option<Self> {
+        let source = match (self.source(), other.source()) {
+            (Some(source), Some(other_source)) if ptr::eq(source, other_source) && self.file == other.file => source,
+            _                                                                                          => return None
+        };
+
+        let start = self.offset.min(other.offset);
+        let end   = (self.offset + self.fragment.len()).max(other.offset + other.fragment.len());
+
+        Some(
+            Span {
+                offset  : start,
+                fragment: &source[start..end],
+                ..*self
+            }
+        )
+    }
+
+    /// The origin of the span starting `consumed` bytes after this one,
+    /// found by scanning the consumed bytes.
+    fn origin_after(&self, consumed: usize) -> Origin<'a> {
+        match self.origin {
+            Origin::Line { source, line, line_start } => {
+                let consumed_as_bytes = &self.fragment.as_bytes()[..consumed];
+
+                Origin::Line {
+                    source,
+                    line      : line + memchr::Memchr::new(b'\n', consumed_as_bytes).count() as u32,
+                    line_start: memchr::memrchr(b'\n', consumed_as_bytes).map_or(line_start, |newline| self.offset + newline + 1)
+                }
+            },
+
+            Origin::Position { .. } => {
+                let next_position = self.start().advance(&self.fragment[..consumed]);
+
+                Origin::Position {
+                    line  : next_position.line,
+                    column: next_position.column
+                }
+            },
+
+            Origin::Source(_) | Origin::Indexed { .. } => self.origin
+        }
+    }
+
+    /// The whole source the span belongs to, if the span knows it.
+    fn source(&self) -> Option<&'a str> {
+        match self.origin {
+            Origin::Source(source)           |
+            Origin::Indexed { source, .. }   |
+            Origin::Line { source, .. }      => Some(source),
+            Origin::Position { .. }          => None
+        }
+    }
+
+    /// Check whether another span is inside this span.
+    #[inline]
+    pub fn contains(&self, other: &Self) -> bool {
+        self.range().contains(&other.range())
+    }
+
+    /// The byte range of the span, to slice the source.
+    #[inline]
+    pub fn byte_range(&self) -> Range<usize> {
+        self.offset..self.offset + self.fragment.len()
+    }
+}
+
+/// Two spans are equal if they span the same fragment at the same offset
+/// of the same file. The line and the column follow from the offset, so
+/// they are not compared.
+impl<'a> PartialEq for Span<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.file == other.file
+            && self.offset == other.offset
+            && self.fragment == other.fragment
+    }
+}
+
+impl<'a> fmt::Debug for Span<'a> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("Span")
+            .field("offset", &self.offset)
+            .field("line", &self.line())
+            .field("column", &self.column())
+            .field("file", &self.file)
+            .field("fragment", &self.fragment)
+            .finish()
+    }
+}
+
+/// A span is serialized as an object with its `offset`, `line`, `column`,
+/// `file` and `fragment`, i.e. with the fields of an `OwnedSpan`, so that it
+/// can be deserialized into an owned span.
+#[cfg(feature = "serde")]
+impl<'a> ::serde::Serialize for Span<'a> {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("Span", 5)?;
+
+        state.serialize_field("offset", &self.offset)?;
+        state.serialize_field("line", &self.line())?;
+        state.serialize_field("column", &self.column())?;
+        state.serialize_field("file", &self.file)?;
+        state.serialize_field("fragment", self.fragment)?;
+        state.end()
+    }
 }
 
 /// Implement `InputLength` from nom to be able to use the `Span`
@@ -259,29 +607,19 @@ impl<'a, 'b> Compare<&'b str> for Span<'a> {
 impl<'a> Compare<char> for Span<'a> {
     /// Compare self to another input for equality.
     fn compare(&self, c: char) -> CompareResult {
-        match self.fragment.chars().nth(0) {
-            Some(first_char) => {
-                if first_char == c {
-                    CompareResult::Ok
-                } else {
-                    CompareResult::Error
-                }
-            }
-            None => { CompareResult::Error }
+        match self.fragment.chars().next() {
+            Some(first_char) if first_char == c => CompareResult::Ok,
+            _ => CompareResult::Error
         }
     }
 
     /// Compare self to another input for equality independently of the case.
     fn compare_no_case(&self, c: char) -> CompareResult {
-        match self.fragment.chars().nth(0) {
-            Some(first_char) => {
-                if first_char.to_lowercase().cmp(c.to_lowercase()) == Ordering::Equal {
-                    CompareResult::Ok
-                } else {
-                    CompareResult::Error
-                }
-            }
-            None => { CompareResult::Error }
+        match self.fragment.chars().next() {
+            Some(first_char) if first_char.to_lowercase().cmp(c.to_lowercase()) == Ordering::Equal => {
+                CompareResult::Ok
+            },
+            _ => CompareResult::Error
         }
     }
 }
@@ -311,40 +649,26 @@ macro_rules! impl_slice_for_range {
                 }
 
                 let next_offset = self.fragment.offset(&next_fragment);
-                if next_offset == 0 {
-                    return Span {
-                        line: self.line,
-                        offset: self.offset,
-                        column: self.column,
-                        fragment: next_fragment
-                    };
-                }
+                let origin      = match self.origin {
+                    Origin::Indexed { .. } => self.origin,
+
+                    _ if next_offset == 0 => self.origin,
 
-                let consumed = self.fragment.slice(..next_offset);
-                let consumed_as_bytes = consumed.as_bytes();
-                let iter = memchr::Memchr::new(b'\n', consumed_as_bytes);
-                let number_of_newlines = iter.count() as u32;
-
-                let next_column =
-                    if number_of_newlines == 0 {
-                        self.column + next_offset as u32
-                    } else {
-                        match memchr::memrchr(b'\n', consumed_as_bytes) {
-                            Some(last_newline_position) => {
-                                (next_offset - last_newline_position) as u32
-                            },
-
-                            None => {
-                                unreachable!();
-                            }
+                    Origin::Source(source) | Origin::Line { source, .. } => {
+                        match state::line(source, self.offset + next_offset) {
+                            Some((line, line_start)) => Origin::Line { source, line, line_start },
+                            None                     => self.origin_after(next_offset)
                         }
-                    };
+                    },
+
+                    Origin::Position { .. } => self.origin_after(next_offset)
+                };
 
                 Span {
-                    line: self.line + number_of_newlines,
-                    offset: self.offset + next_offset,
-                    column: next_column,
-                    fragment: next_fragment
+                    offset  : self.offset + next_offset,
+                    file    : self.file,
+                    fragment: next_fragment,
+                    origin
                 }
             }
         }
@@ -358,7 +682,15 @@ impl_slice_for_range!(RangeFull);
 
 #[cfg(test)]
 mod tests {
-    use super::Span;
+    use super::{
+        Position,
+        SourceRange,
+        Span
+    };
+    use options::ParserOptions;
+    use line_index::line_starts;
+    use source_map::FileId;
+    use state;
     use nom::{
         Compare,
         CompareResult,
@@ -371,12 +703,7 @@ mod tests {
     #[test]
     fn case_span_new() {
         let input  = "foobar";
-        let output = Span {
-            offset: 0,
-            line  : 1,
-            column: 1,
-            fragment : input
-        };
+        let output = Span::new_at(input, 0, 1, 1);
 
         assert_eq!(Span::new(input), output);
     }
@@ -384,28 +711,166 @@ mod tests {
     #[test]
     fn case_span_new_at() {
         let input  = "foobar";
-        let output = Span {
-            offset: 1,
-            line  : 2,
-            column: 3,
-            fragment : input
-        };
+        let output = Span::new_at(input, 1, 2, 3);
+
+        assert_eq!(output.offset, 1);
+        assert_eq!(output.line(), 2);
+        assert_eq!(output.column(), 3);
+        assert_eq!(output.as_slice(), input);
+    }
+
+    #[test]
+    fn case_span_lazy_line_and_column() {
+        let input = "foo\nbär\n\nbaz qux";
+        let span  = Span::new(input).slice(input.find("qux").unwrap()..);
+
+        assert_eq!(span.offset, 14);
+        assert_eq!(span.line(), 4);
+        assert_eq!(span.column(), 5);
+        assert_eq!(Span::new(input).slice(9..).line(), 3);
+        assert_eq!(Span::new(input).slice(9..).column(), 1);
+        assert_eq!(Span::new(input).slice(7..).column(), 3);
+    }
+
+    #[test]
+    fn case_span_indexed_line_and_column() {
+        let input       = "foo\nbär\n\nbaz qux";
+        let line_starts = line_starts(input);
+        let span        = Span::new_indexed(input, &line_starts).slice(input.find("qux").unwrap()..);
+
+        assert_eq!(span.line(), 4);
+        assert_eq!(span.column(), 5);
+        assert_eq!(span, Span::new(input).slice(14..));
+        assert_eq!(Span::new_indexed(input, &line_starts).slice(7..).column(), 3);
+        assert_eq!(Span::new_indexed(input, &line_starts).slice(9..).line(), 3);
+    }
+
+    #[test]
+    fn case_span_slice_detached() {
+        let span = Span::new_at("foo\nbär baz", 4, 2, 3).slice(9..);
+
+        assert_eq!(span, Span::new_at("baz", 13, 3, 5));
+        assert_eq!(span.slice(1..), Span::new_at("az", 14, 3, 6));
+    }
+
+    #[test]
+    fn case_span_equality_ignores_origin() {
+        let input = "foo\nbar";
+
+        assert_eq!(Span::new(input).slice(4..), Span::new_at("bar", 4, 2, 1));
+
+        // The position follows from the offset: it is not compared.
+        assert_eq!(Span::new(input).slice(4..), Span::new_at("bar", 4, 1, 5));
+        assert_ne!(Span::new(input).slice(4..), Span::new_at("bar", 5, 2, 1));
+    }
 
-        assert_eq!(Span::new_at(input, 1, 2, 3), output);
+    #[test]
+    fn case_span_line_found_while_parsing() {
+        let input = "foo\nbar\nbaz";
+        let span  = state::with_state(input, ParserOptions::default(), || Span::new(input).slice(5..));
+
+        assert_eq!((span.line(), span.column()), (2, 2));
+
+        // Once the parse is over, the lines are counted while slicing.
+        assert_eq!((span.slice(3..).line(), span.slice(3..).column()), (3, 1));
+        assert_eq!((span.slice(1..).line(), span.slice(1..).column()), (2, 3));
+        assert_eq!(span.slice(3..), Span::new(input).slice(8..));
     }
 
     #[test]
     fn case_span_empty() {
-        let output = Span {
-            offset: 0,
-            line  : 1,
-            column: 1,
-            fragment : ""
-        };
+        let output = Span::new_at("", 0, 1, 1);
 
         assert_eq!(Span::empty(), output);
     }
 
+    #[test]
+    fn case_span_start_and_end() {
+        let input = Span::new_at("foo", 4, 2, 3);
+
+        assert_eq!(input.start(), Position { offset: 4, line: 2, column: 3 });
+        assert_eq!(input.end(), Position { offset: 7, line: 2, column: 6 });
+    }
+
+    #[test]
+    fn case_span_end_multiline() {
+        let input = Span::new_at("foo\nbar\r\nbaz", 4, 2, 3);
+
+        assert_eq!(input.end(), Position { offset: 16, line: 4, column: 4 });
+    }
+
+    #[test]
+    fn case_span_end_empty() {
+        let input = Span::new_at("", 4, 2, 3);
+
+        assert_eq!(input.end(), input.start());
+        assert!(input.range().is_empty());
+    }
+
+    #[test]
+    fn case_span_range() {
+        let input  = Span::new("foo\nbar");
+        let output = SourceRange::new(
+            Position { offset: 0, line: 1, column: 1 },
+            Position { offset: 7, line: 2, column: 4 }
+        );
+
+        assert_eq!(input.range(), output);
+        assert_eq!(SourceRange::from(input), output);
+        assert_eq!(output.len(), 7);
+        assert_eq!(input.byte_range(), 0..7);
+        assert_eq!(output.byte_range(), 0..7);
+    }
+
+    #[test]
+    fn case_span_merge() {
+        let input  = Span::new("foo + bar");
+        let left   = input.slice(..3);
+        let right  = input.slice(6..);
+        let output = SourceRange::new(
+            Position { offset: 0, line: 1, column: 1 },
+            Position { offset: 9, line: 1, column: 10 }
+        );
+
+        assert_eq!(left.merge(&right), output);
+        assert_eq!(right.merge(&left), output);
+    }
+
+    #[test]
+    fn case_span_join() {
+        let input = Span::new("foo + bar");
+        let left  = input.slice(..3);
+        let right = input.slice(6..);
+
+        assert_eq!(left.join(&right), Some(input));
+        assert_eq!(right.join(&left), Some(input));
+        assert_eq!(left.join(&left), Some(left));
+        assert_eq!(left.join(&Span::new("bar + foo")), None);
+        assert_eq!(Span::new_at("foo", 0, 1, 1).join(&right), None);
+    }
+
+    #[test]
+    fn case_span_contains() {
+        let input = Span::new("foo + bar");
+        let inner = input.slice(2..5);
+
+        assert!(input.contains(&inner));
+        assert!(input.contains(&input));
+        assert!(!inner.contains(&input));
+        assert!(inner.range().contains_offset(2));
+        assert!(!inner.range().contains_offset(5));
+    }
+
+    #[test]
+    fn case_span_with_file() {
+        let file  = FileId::new(1);
+        let input = Span::new("foobar").with_file(file);
+
+        assert_eq!(input.file, file);
+        assert_eq!(input.slice(3..).file, file);
+        assert_ne!(input, Span::new("foobar"));
+    }
+
     #[test]
     fn case_span_as_slice() {
         let input  = Span::new("foobar");
@@ -637,12 +1102,7 @@ mod tests {
     fn case_span_slice_with_range() {
         let range  = 2..5;
         let input  = "foobar";
-        let output = Span {
-            offset: 2,
-            line  : 1,
-            column: 3,
-            fragment : &input[range.clone()]
-        };
+        let output = Span::new_at(&input[range.clone()], 2, 1, 3);
 
         assert_eq!(Span::new(input).slice(range.clone()), output);
     }
@@ -651,12 +1111,7 @@ mod tests {
     fn case_span_slice_with_range_to() {
         let range  = 2..;
         let input  = "foobar";
-        let output = Span {
-            offset: 2,
-            line  : 1,
-            column: 3,
-            fragment : &input[range.clone()]
-        };
+        let output = Span::new_at(&input[range.clone()], 2, 1, 3);
 
         assert_eq!(Span::new(input).slice(range.clone()), output);
     }
@@ -665,28 +1120,18 @@ mod tests {
     fn case_span_slice_with_range_from() {
         let range  = ..3;
         let input  = "foobar";
-        let output = Span {
-            offset: 0,
-            line  : 1,
-            column: 1,
-            fragment : &input[range.clone()]
-        };
+        let output = Span::new_at(&input[range], 0, 1, 1);
 
-        assert_eq!(Span::new(input).slice(range.clone()), output);
+        assert_eq!(Span::new(input).slice(range), output);
     }
 
     #[test]
     fn case_span_slice_with_range_full() {
         let range  = ..;
         let input  = "foobar";
-        let output = Span {
-            offset: 0,
-            line  : 1,
-            column: 1,
-            fragment : &input[range.clone()]
-        };
+        let output = Span::new_at(input, 0, 1, 1);
 
-        assert_eq!(Span::new(input).slice(range.clone()), output);
+        assert_eq!(Span::new(input).slice(range), output);
     }
 
     #[test]
@@ -710,43 +1155,13 @@ mod tests {
 
         let input = "foo bar\nbaz\n \n  baz   qux";
         let output = Ok((
-            Span {
-                offset: 25,
-                line  : 4,
-                column: 12,
-                fragment : ""
-            },
+            Span::new_at("", 25, 4, 12),
             vec![
-                Span {
-                    offset: 0,
-                    line  : 1,
-                    column: 1,
-                    fragment : "foo",
-                },
-                Span {
-                    offset: 4,
-                    line  : 1,
-                    column: 5,
-                    fragment : "bar",
-                },
-                Span {
-                    offset: 8,
-                    line  : 2,
-                    column: 1,
-                    fragment : "baz",
-                },
-                Span {
-                    offset: 16,
-                    line  : 4,
-                    column: 3,
-                    fragment : "baz",
-                },
-                Span {
-                    offset: 22,
-                    line  : 4,
-                    column: 9,
-                    fragment : "qux",
-                }
+                Span::new_at("foo", 0, 1, 1),
+                Span::new_at("bar", 4, 1, 5),
+                Span::new_at("baz", 8, 2, 1),
+                Span::new_at("baz", 16, 4, 3),
+                Span::new_at("qux", 22, 4, 9)
             ]
         ));
 
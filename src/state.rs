@@ -0,0 +1,138 @@
+//! The state of a parse.
+//!
+//! The parsers only receive their input, so the state shared by a whole
+//! parse, i.e. the index of the lines and the options of the parser, lives
+//! in a thread-local for the duration of the parse, see `with_state`.
+
+use std::cell::RefCell;
+
+use line_index::{
+    line_starts,
+    LineIndex
+};
+use options::ParserOptions;
+
+thread_local! {
+    static STATE: RefCell<Option<State>> = const { RefCell::new(None) };
+}
+
+/// The state of a parse.
+struct State {
+    /// The address and the length of the parsed source, to recognize it.
+    source: (usize, usize),
+
+    /// The byte offset of the start of each line of the parsed source,
+    /// computed once per parse.
+    line_starts: Vec<usize>,
+
+    /// The options of the parser.
+    options    : ParserOptions
+}
+
+/// Restore the previous state when a parse ends, even by a panic.
+struct Restore(Option<State>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+
+        STATE.with(|state| *state.borrow_mut() = previous);
+    }
+}
+
+fn key(source: &str) -> (usize, usize) {
+    (source.as_ptr() as usize, source.len())
+}
+
+/// Run a parse of `source` with `options`, while the spans of `source`
+/// find their lines in an index of the lines of `source`, built once.
+pub fn with_state<R, F: FnOnce() -> R>(source: &str, options: ParserOptions, parse: F) -> R {
+    let state = State {
+        source     : key(source),
+        line_starts: line_starts(source),
+        options
+    };
+    let _restore = Restore(STATE.with(|current| current.replace(Some(state))));
+
+    parse()
+}
+
+/// The line of an offset of `source`, and the offset of the start of this
+/// line, if `source` is being parsed.
+pub fn line(source: &str, offset: usize) -> Option<(u32, usize)> {
+    STATE.with(|state| {
+        match *state.borrow() {
+            Some(ref state) if state.source == key(source) => {
+                let index = LineIndex::from_line_starts(source, &state.line_starts);
+                let line  = index.line(offset);
+
+                index.line_start(line).map(|start| (line, start))
+            },
+
+            _ => None
+        }
+    })
+}
+
+/// The options of the running parse, or the default options outside of a
+/// parse.
+pub fn options() -> ParserOptions {
+    STATE.with(|state| state.borrow().as_ref().map_or_else(ParserOptions::default, |state| state.options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        line,
+        options,
+        with_state
+    };
+    use options::{
+        Dialect,
+        ParserOptions
+    };
+
+    #[test]
+    fn case_line_while_parsing() {
+        let source = "a\nbc\nd";
+
+        assert_eq!(line(source, 3), None);
+
+        with_state(source, ParserOptions::default(), || {
+            assert_eq!(line(source, 0), Some((1, 0)));
+            assert_eq!(line(source, 3), Some((2, 2)));
+            assert_eq!(line(source, 6), Some((3, 5)));
+            assert_eq!(line("a\nbc\nd\n", 3), None);
+        });
+
+        assert_eq!(line(source, 3), None);
+    }
+
+    #[test]
+    fn case_nested_states() {
+        let outer = "a\nb";
+        let inner = "c\nd";
+
+        with_state(outer, ParserOptions::default(), || {
+            with_state(inner, ParserOptions::new(Dialect::Strict), || {
+                assert_eq!(line(inner, 2), Some((2, 2)));
+                assert_eq!(line(outer, 2), None);
+                assert_eq!(options().dialect, Dialect::Strict);
+            });
+
+            assert_eq!(line(outer, 2), Some((2, 2)));
+            assert_eq!(options(), ParserOptions::default());
+        });
+    }
+
+    #[test]
+    fn case_options_while_parsing() {
+        let strict = ParserOptions::new(Dialect::Strict);
+
+        assert_eq!(options(), ParserOptions::default());
+
+        with_state("a", strict, || assert_eq!(options(), strict));
+
+        assert_eq!(options(), ParserOptions::default());
+    }
+}
@@ -101,6 +101,16 @@ token!(
     "THe `INLINE_COMMENT` token.\n\nRepresent an inline comment, e.g. `// comment`."
 );
 
+token!(
+    pub TODO: "TODO";
+    "The `TODO` token.\n\nRepresent a writing task, e.g. `TODO: describe the room` or `// TODO describe the room`."
+);
+
+token!(
+    pub ALLOW: "ink-allow";
+    "The `ALLOW` token.\n\nRepresent the suppression of diagnostics in a file, e.g. `// ink-allow: INK0005`."
+);
+
 token!(
     pub BLOCK_COMMENT_OPEN: "/*";
     "The `BLOCK_COMMENT_OPEN` token.\n\nRepresent the beginning of a block comment, e.g. `/* comment */`."
@@ -126,6 +136,46 @@ token!(
     "The `BOOLEAN_OR` token.\n\nRepresent the boolean disjunction operator, e.g. `x || y`."
 );
 
+token!(
+    pub AND: "and";
+    "The `AND` token.\n\nRepresent the boolean conjunction keyword, e.g. `x and y`."
+);
+
+token!(
+    pub NOT: "not";
+    "The `NOT` token.\n\nRepresent the boolean negation keyword, e.g. `not x`."
+);
+
+token!(
+    pub OR: "or";
+    "The `OR` token.\n\nRepresent the boolean disjunction keyword, e.g. `x or y`."
+);
+
+token!(
+    pub MOD: "mod";
+    "The `MOD` token.\n\nRepresent the modulus keyword, e.g. `x mod y`."
+);
+
+token!(
+    pub CONTAINS: "?";
+    "The `CONTAINS` token.\n\nRepresent the list containment operator, e.g. `x ? y`."
+);
+
+token!(
+    pub HAS: "has";
+    "The `HAS` token.\n\nRepresent the list containment keyword, e.g. `x has y`."
+);
+
+token!(
+    pub NOT_CONTAINS: "!?";
+    "The `NOT_CONTAINS` token.\n\nRepresent the list exclusion operator, e.g. `x !? y`."
+);
+
+token!(
+    pub HASNT: "hasnt";
+    "The `HASNT` token.\n\nRepresent the list exclusion keyword, e.g. `x hasnt y`."
+);
+
 token!(
     pub BITWISE_AND: "&";
     "The `BITWISE_AND` token.\n\nRepresent the bitwise conjunction operator, e.g. `x & y`."
@@ -172,8 +222,8 @@ token!(
 );
 
 token!(
-    pub BITWISE_XOR: "^";
-    "The `BITWISE_XOR` token.\n\nRepresent the exclusive bitwise disjunction operator, e.g. `x ^ y`."
+    pub INTERSECT: "^";
+    "The `INTERSECT` token.\n\nRepresent the list intersection operator, e.g. `x ^ y`."
 );
 
 token!(
@@ -206,6 +256,21 @@ token!(
     "The `GLUE` token.\n\nRepresent a glue, e.g. `We hurried home <>`."
 );
 
+token!(
+    pub GATHER: "-";
+    "The `GATHER` token.\n\nRepresent a gather, e.g. `- They argued long into the night.`."
+);
+
+token!(
+    pub KNOT: "==";
+    "The `KNOT` token.\n\nRepresent a knot, e.g. `== back_in_london ==`."
+);
+
+token!(
+    pub FUNCTION: "function";
+    "The `FUNCTION` token.\n\nRepresent a function, e.g. `== function lerp(a, b, k) ==`."
+);
+
 token!(
     pub STITCH: "=";
     "The `STITCH` token.\n\nRepresent a stitch, e.g. `= in_first_class`."
@@ -226,13 +291,28 @@ token!(
     "The `RIGHT_PARENTHESIS` token.\n\nUsed to close a group, e.g. `(a, b)`."
 );
 
+token!(
+    pub COMMA: ",";
+    "The `COMMA` token.\n\nUsed to separate items, e.g. `(a, b)`."
+);
+
+token!(
+    pub DOT: ".";
+    "The `DOT` token.\n\nUsed to separate the knot and the stitch of a path, e.g. `-> london.first_class`."
+);
+
+token!(
+    pub COLON: ":";
+    "The `COLON` token.\n\nRepresent the end of a condition, e.g. `{ x > 0: … }`."
+);
+
 token!(
     pub LEFT_SQUARE_BRACKET: "[";
     "The `LEFT_SQUARE_BRACKET` token.\n\nRepresent the beginning of a suppressing choice text, e.g. `* [choice]`."
 );
 
 token!(
-    pub RIGHT_SQUARE_BRACKET: "[";
+    pub RIGHT_SQUARE_BRACKET: "]";
     "The `RIGHT_SQUARE_BRACKET` token.\n\nRepresent the end of a suppressing choice text, e.g. `* [choice]`."
 );
 
@@ -246,6 +326,26 @@ token!(
     "The `RIGHT_CURLY_BRACKET` token.\n\nUsed to close a block, e.g. `{ … }`."
 );
 
+token!(
+    pub STOPPING: "stopping";
+    "The `STOPPING` token.\n\nRepresent a stopping sequence, e.g. `{ stopping: - … }`."
+);
+
+token!(
+    pub CYCLE: "cycle";
+    "The `CYCLE` token.\n\nRepresent a cycle sequence, e.g. `{ cycle: - … }`."
+);
+
+token!(
+    pub ONCE: "once";
+    "The `ONCE` token.\n\nRepresent a once-only sequence, e.g. `{ once: - … }`."
+);
+
+token!(
+    pub SHUFFLE: "shuffle";
+    "The `SHUFFLE` token.\n\nRepresent a shuffle sequence, e.g. `{ shuffle: - … }`."
+);
+
 token!(
     pub END: "END";
     "The `END` token.\n\nRepresent the end of the flow, e.g. `-> END`."